@@ -0,0 +1,188 @@
+use crate::event::{EventStore, Occurrence};
+use chrono::prelude::*;
+use chrono::Duration;
+
+/**
+ * Number of days with events an agenda loads at once.
+ */
+pub const AGENDA_PAGE_DAYS: usize = 20;
+
+/**
+ * Number of days an agenda scans at most for every page, so that loading a
+ * page of a sparse calendar stops after a year of empty days.
+ */
+pub const AGENDA_MAX_SCAN_DAYS: i64 = 366;
+
+/**
+ * Number of days expanded per query while scanning for a page.
+ */
+const SCAN_CHUNK_DAYS: i64 = 31;
+
+/**
+ * A day of the agenda with the events covering it, all-day events first,
+ * then by start.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgendaDay<'a> {
+    pub date: NaiveDate,
+    pub occurrences: Vec<Occurrence<'a>>,
+}
+
+/**
+ * The range of dates listed by an agenda view.
+ *
+ * The agenda starts on a chosen date and is extended page by page as the
+ * user scrolls; only the range is kept, the events are queried again from the
+ * store whenever the agenda is displayed.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Agenda {
+    start: NaiveDate,
+    // First date not loaded yet, saturating at the last date `chrono` supports.
+    end: NaiveDate,
+}
+
+impl Agenda {
+    /**
+     * Creates an agenda starting on `start` and loads its first page.
+     */
+    pub fn new(store: &EventStore, start: NaiveDate) -> Agenda {
+        let mut agenda = Agenda { start, end: start };
+        agenda.load_more(store);
+        agenda
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    /**
+     * Returns whether later dates can still be loaded.
+     */
+    pub fn has_more(&self) -> bool {
+        self.end < NaiveDate::MAX
+    }
+
+    /**
+     * Function to load the next page of the agenda.
+     *
+     * Scans the dates after the loaded range until `AGENDA_PAGE_DAYS` more
+     * days with events have been found or `AGENDA_MAX_SCAN_DAYS` days have
+     * been scanned.
+     *
+     * Returns:
+     * - The number of days with events that were added.
+     */
+    pub fn load_more(&mut self, store: &EventStore) -> usize {
+        let mut found = 0;
+        let mut scanned = 0;
+        while self.has_more() && found < AGENDA_PAGE_DAYS && scanned < AGENDA_MAX_SCAN_DAYS {
+            let from = self.end;
+            let chunk_end = from
+                .checked_add_signed(Duration::days(SCAN_CHUNK_DAYS - 1))
+                .unwrap_or(NaiveDate::MAX);
+            let days = store.occurrences_by_date(from, chunk_end);
+            // Stop right after the page is complete, so the next page starts there.
+            let mut next = chunk_end.succ_opt().unwrap_or(NaiveDate::MAX);
+            for date in days.keys() {
+                if found == AGENDA_PAGE_DAYS {
+                    next = *date;
+                    break;
+                }
+                found += 1;
+            }
+            scanned += (chunk_end - from).num_days() + 1;
+            self.end = next;
+        }
+        found
+    }
+
+    /**
+     * Function to list the loaded part of the agenda.
+     *
+     * Parameters:
+     * - `store`: The events to list, normally the store the agenda was loaded from.
+     *
+     * Returns:
+     * - The days with events in chronological order; days without events are left out.
+     */
+    pub fn days<'a>(&self, store: &'a EventStore) -> Vec<AgendaDay<'a>> {
+        let Some(last) = self.end.pred_opt().filter(|last| *last >= self.start) else {
+            return Vec::new();
+        };
+        store
+            .occurrences_by_date(self.start, last)
+            .into_iter()
+            .map(|(date, occurrences)| AgendaDay { date, occurrences })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, EventTime};
+    use crate::recurrence::{Frequency, Recurrence, RecurrenceRule};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn all_day(store: &mut EventStore, title: &str, day: NaiveDate) {
+        store.insert(Event::new(title, EventTime::all_day(day, day).unwrap()));
+    }
+
+    #[test]
+    fn days_without_events_are_left_out() {
+        let mut store = EventStore::new();
+        all_day(&mut store, "Past", date(2026, 10, 1));
+        all_day(&mut store, "Dentist", date(2026, 10, 20));
+        all_day(&mut store, "Party", date(2026, 10, 31));
+        all_day(&mut store, "Flight", date(2026, 10, 20));
+
+        let agenda = Agenda::new(&store, date(2026, 10, 16));
+        let days: Vec<(NaiveDate, usize)> = agenda
+            .days(&store)
+            .iter()
+            .map(|day| (day.date, day.occurrences.len()))
+            .collect();
+        assert_eq!(days, [(date(2026, 10, 20), 2), (date(2026, 10, 31), 1)]);
+        assert!(agenda.has_more());
+    }
+
+    #[test]
+    fn pages_are_loaded_lazily() {
+        let mut store = EventStore::new();
+        let mut event = Event::new(
+            "Standup",
+            EventTime::all_day(date(2026, 1, 1), date(2026, 1, 1)).unwrap(),
+        );
+        event.recurrence = Some(Recurrence::from_rule(RecurrenceRule::new(Frequency::Daily)));
+        store.insert(event);
+
+        let start = date(2026, 10, 16);
+        let mut agenda = Agenda::new(&store, start);
+        let days = agenda.days(&store);
+        assert_eq!(days.len(), AGENDA_PAGE_DAYS);
+        assert_eq!(days[0].date, start);
+
+        assert_eq!(agenda.load_more(&store), AGENDA_PAGE_DAYS);
+        let days = agenda.days(&store);
+        assert_eq!(days.len(), 2 * AGENDA_PAGE_DAYS);
+        assert_eq!(
+            days.last().unwrap().date,
+            start + Duration::days(2 * AGENDA_PAGE_DAYS as i64 - 1)
+        );
+    }
+
+    #[test]
+    fn sparse_calendars_stop_after_the_scan_limit() {
+        let mut store = EventStore::new();
+        all_day(&mut store, "Next year", date(2028, 1, 1));
+
+        let mut agenda = Agenda::new(&store, date(2026, 10, 16));
+        assert!(agenda.days(&store).is_empty());
+        agenda.load_more(&store);
+        assert_eq!(agenda.days(&store).len(), 1);
+    }
+}
@@ -0,0 +1,408 @@
+use crate::holiday::HolidayCalendar;
+use chrono::prelude::*;
+use chrono::{Days, Duration};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+/**
+ * Decides which days are working days, for computing deadlines and SLAs.
+ *
+ * A day is a business day unless it falls on one of the weekend days or is a
+ * holiday: a holiday of one of the holiday calendars (including the days
+ * holidays falling on a weekend are observed on) or an extra closing day.
+ */
+#[derive(Debug, Clone)]
+pub struct BusinessCalendar {
+    // Whether each weekday, starting with Monday, is a weekend day.
+    weekend: [bool; 7],
+    holidays: Vec<HolidayCalendar>,
+    closed: BTreeSet<NaiveDate>,
+    // The holidays of every year looked at so far, computed on first use.
+    holiday_dates: RefCell<BTreeMap<i32, BTreeSet<NaiveDate>>>,
+}
+
+impl BusinessCalendar {
+    /**
+     * Creates a calendar without holidays.
+     *
+     * Parameters:
+     * - `weekend`: The days of the week that are never business days.
+     *
+     * Returns:
+     * - The calendar, or `None` if every day of the week is a weekend day.
+     */
+    pub fn new(weekend: impl IntoIterator<Item = Weekday>) -> Option<BusinessCalendar> {
+        let mut days = [false; 7];
+        for weekday in weekend {
+            days[weekday.num_days_from_monday() as usize] = true;
+        }
+        days.contains(&false).then_some(BusinessCalendar {
+            weekend: days,
+            holidays: Vec::new(),
+            closed: BTreeSet::new(),
+            holiday_dates: RefCell::default(),
+        })
+    }
+
+    /**
+     * Adds the holidays of a country or region.
+     */
+    pub fn with_holidays(mut self, holidays: HolidayCalendar) -> BusinessCalendar {
+        self.holidays.push(holidays);
+        self.holiday_dates.get_mut().clear();
+        self
+    }
+
+    /**
+     * Adds single closing days, such as company holidays.
+     */
+    pub fn with_closed_days(
+        mut self,
+        days: impl IntoIterator<Item = NaiveDate>,
+    ) -> BusinessCalendar {
+        self.closed.extend(days);
+        self
+    }
+
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend[date.weekday().num_days_from_monday() as usize]
+    }
+
+    /**
+     * Returns whether `date` is a holiday or closing day, whatever its weekday.
+     */
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        if self.closed.contains(&date) {
+            return true;
+        }
+        let mut holiday_dates = self.holiday_dates.borrow_mut();
+        let year = holiday_dates.entry(date.year()).or_insert_with(|| {
+            let (Some(first), Some(last)) = (
+                NaiveDate::from_ymd_opt(date.year(), 1, 1),
+                NaiveDate::from_ymd_opt(date.year(), 12, 31),
+            ) else {
+                return self.holidays_between(date, date);
+            };
+            self.holidays_between(first, last)
+        });
+        year.contains(&date)
+    }
+
+    /**
+     * Returns the dates between `start` and `end` (inclusive) that are a
+     * holiday of any of the holiday calendars.
+     */
+    fn holidays_between(&self, start: NaiveDate, end: NaiveDate) -> BTreeSet<NaiveDate> {
+        self.holidays
+            .iter()
+            .flat_map(|calendar| calendar.holidays_between(start, end).into_keys())
+            .collect()
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_holiday(date)
+    }
+
+    /**
+     * Function to find the first business day after `date`.
+     *
+     * Returns:
+     * - The business day, or `None` if there is none before the end of the
+     *   supported date range.
+     */
+    pub fn next_business_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut day = date.succ_opt()?;
+        while !self.is_business_day(day) {
+            day = day.succ_opt()?;
+        }
+        Some(day)
+    }
+
+    /**
+     * Function to find the last business day before `date`.
+     *
+     * Returns:
+     * - The business day, or `None` if there is none after the start of the
+     *   supported date range.
+     */
+    pub fn previous_business_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut day = date.pred_opt()?;
+        while !self.is_business_day(day) {
+            day = day.pred_opt()?;
+        }
+        Some(day)
+    }
+
+    /**
+     * Function to add business days to a date.
+     *
+     * `date` itself is not counted and need not be a business day: one
+     * business day after a Friday or a Saturday is the Monday with a
+     * Saturday/Sunday weekend.
+     *
+     * Parameters:
+     * - `date`: The date to count from.
+     * - `days`: The number of business days to move, backwards if negative.
+     *
+     * Returns:
+     * - The date `days` business days after `date`. For `days == 0` that is
+     *   `date` if it is a business day, and the next business day otherwise.
+     *   `None` if the result is outside the supported date range.
+     */
+    pub fn add_business_days(&self, date: NaiveDate, days: i64) -> Option<NaiveDate> {
+        if days == 0 {
+            return if self.is_business_day(date) {
+                Some(date)
+            } else {
+                self.next_business_day(date)
+            };
+        }
+        // Skip whole weeks first, leaving at least one business day to step
+        // to one by one so that the result is a business day.
+        let working_per_week = self.weekend.iter().filter(|weekend| !**weekend).count() as u64;
+        let mut day = date;
+        let mut remaining = days.unsigned_abs();
+        while remaining > working_per_week {
+            let weeks = Days::new(((remaining - 1) / working_per_week).checked_mul(7)?);
+            let skipped = if days > 0 {
+                let end = day.checked_add_days(weeks)?;
+                let skipped = self.business_days_between(day.succ_opt()?, end.succ_opt()?);
+                day = end;
+                skipped
+            } else {
+                let start = day.checked_sub_days(weeks)?;
+                let skipped = self.business_days_between(start, day);
+                day = start;
+                skipped
+            };
+            remaining -= skipped as u64;
+        }
+        for _ in 0..remaining {
+            day = if days > 0 {
+                self.next_business_day(day)?
+            } else {
+                self.previous_business_day(day)?
+            };
+        }
+        Some(day)
+    }
+
+    /**
+     * Function to count the business days between two dates.
+     *
+     * Parameters:
+     * - `start`: The first date, counted if it is a business day.
+     * - `end`: The date to count to, not counted.
+     *
+     * Returns:
+     * - The number of business days from `start` up to but excluding `end`,
+     *   negative if `end` is before `start`. Adding the result to `start`
+     *   with `add_business_days` gives `end` whenever both are business days.
+     */
+    pub fn business_days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        if end < start {
+            return -self.business_days_between(end, start);
+        }
+        let days = (end - start).num_days();
+        let working_per_week = self.weekend.iter().filter(|weekend| !**weekend).count() as i64;
+        let mut count = days / 7 * working_per_week;
+        // Count the remaining days one by one; the whole weeks contain every
+        // weekday exactly once.
+        let mut day = start + Duration::days(days / 7 * 7);
+        while day < end {
+            if !self.is_weekend(day) {
+                count += 1;
+            }
+            day = day.succ_opt().unwrap_or(end);
+        }
+
+        let Some(last) = end.pred_opt() else {
+            return count;
+        };
+        let mut holidays = self.holidays_between(start, last);
+        holidays.extend(self.closed.range(start..end));
+        count
+            - holidays
+                .into_iter()
+                .filter(|date| !self.is_weekend(*date))
+                .count() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn weekdays() -> BusinessCalendar {
+        BusinessCalendar::new([Weekday::Sat, Weekday::Sun]).unwrap()
+    }
+
+    fn us() -> BusinessCalendar {
+        weekdays().with_holidays(HolidayCalendar::load("US", None).unwrap())
+    }
+
+    #[test]
+    fn a_week_without_working_days_is_rejected() {
+        let every_day = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+            Weekday::Sat,
+            Weekday::Sun,
+        ];
+        assert!(BusinessCalendar::new(every_day).is_none());
+        assert!(BusinessCalendar::new(every_day[1..].to_vec()).is_some());
+    }
+
+    #[test]
+    fn adding_business_days_skips_weekends() {
+        let calendar = weekdays();
+        // October 16th 2026 is a Friday.
+        let friday = date(2026, 10, 16);
+        assert_eq!(
+            calendar.add_business_days(friday, 1),
+            Some(date(2026, 10, 19))
+        );
+        assert_eq!(
+            calendar.add_business_days(friday, 5),
+            Some(date(2026, 10, 23))
+        );
+        assert_eq!(
+            calendar.add_business_days(friday, -5),
+            Some(date(2026, 10, 9))
+        );
+        let saturday = date(2026, 10, 17);
+        assert_eq!(
+            calendar.add_business_days(saturday, 1),
+            Some(date(2026, 10, 19))
+        );
+        assert_eq!(calendar.add_business_days(saturday, -1), Some(friday));
+        assert_eq!(
+            calendar.add_business_days(saturday, 0),
+            Some(date(2026, 10, 19))
+        );
+        assert_eq!(calendar.add_business_days(friday, 0), Some(friday));
+    }
+
+    #[test]
+    fn holidays_are_not_business_days() {
+        let calendar = us();
+        // Thanksgiving 2026 is Thursday November 26th.
+        let wednesday = date(2026, 11, 25);
+        assert!(!calendar.is_business_day(date(2026, 11, 26)));
+        assert_eq!(
+            calendar.next_business_day(wednesday),
+            Some(date(2026, 11, 27))
+        );
+        assert_eq!(
+            calendar.add_business_days(wednesday, 2),
+            Some(date(2026, 11, 30))
+        );
+        // Independence Day 2026 is a Saturday, observed on Friday July 3rd.
+        assert!(!calendar.is_business_day(date(2026, 7, 3)));
+        assert_eq!(
+            calendar.previous_business_day(date(2026, 7, 6)),
+            Some(date(2026, 7, 2))
+        );
+    }
+
+    #[test]
+    fn closed_days_are_not_business_days() {
+        let calendar = weekdays().with_closed_days([date(2026, 12, 24), date(2026, 12, 31)]);
+        assert_eq!(
+            calendar.business_days_between(date(2026, 12, 21), date(2027, 1, 4)),
+            8
+        );
+        assert_eq!(
+            calendar.next_business_day(date(2026, 12, 23)),
+            Some(date(2026, 12, 25))
+        );
+    }
+
+    #[test]
+    fn counting_business_days() {
+        let calendar = weekdays();
+        let monday = date(2026, 10, 12);
+        assert_eq!(calendar.business_days_between(monday, monday), 0);
+        assert_eq!(
+            calendar.business_days_between(monday, date(2026, 10, 17)),
+            5
+        );
+        assert_eq!(
+            calendar.business_days_between(monday, date(2026, 10, 19)),
+            5
+        );
+        assert_eq!(
+            calendar.business_days_between(monday, date(2026, 10, 20)),
+            6
+        );
+        assert_eq!(
+            calendar.business_days_between(date(2026, 10, 20), monday),
+            -6
+        );
+        // 2026 has 261 weekdays, 11 US federal holidays are observed on weekdays.
+        assert_eq!(
+            calendar.business_days_between(date(2026, 1, 1), date(2027, 1, 1)),
+            261
+        );
+        assert_eq!(
+            us().business_days_between(date(2026, 1, 1), date(2027, 1, 1)),
+            250
+        );
+    }
+
+    #[test]
+    fn other_weekends() {
+        // A Friday/Saturday weekend, as in much of the Middle East.
+        let calendar = BusinessCalendar::new([Weekday::Fri, Weekday::Sat]).unwrap();
+        let thursday = date(2026, 10, 15);
+        assert_eq!(
+            calendar.next_business_day(thursday),
+            Some(date(2026, 10, 18))
+        );
+        assert!(calendar.is_business_day(date(2026, 10, 18)));
+        // A six-day week.
+        let calendar = BusinessCalendar::new([Weekday::Sun]).unwrap();
+        assert_eq!(
+            calendar.business_days_between(date(2026, 10, 12), date(2026, 10, 26)),
+            12
+        );
+    }
+
+    #[test]
+    fn the_end_of_the_date_range_is_reported() {
+        let calendar = weekdays();
+        assert_eq!(calendar.next_business_day(NaiveDate::MAX), None);
+        assert_eq!(calendar.add_business_days(NaiveDate::MIN, -1), None);
+        assert_eq!(
+            calendar.add_business_days(date(2026, 10, 16), i64::MAX),
+            None
+        );
+        assert_eq!(
+            calendar.add_business_days(date(2026, 10, 16), i64::MIN),
+            None
+        );
+    }
+
+    #[test]
+    fn many_business_days_skip_whole_weeks() {
+        let calendar = us();
+        let start = date(2026, 10, 16);
+        let mut stepped = start;
+        for _ in 0..300 {
+            stepped = calendar.next_business_day(stepped).unwrap();
+        }
+        assert_eq!(calendar.add_business_days(start, 300), Some(stepped));
+        assert_eq!(calendar.add_business_days(stepped, -300), Some(start));
+
+        let calendar = weekdays();
+        let later = calendar.add_business_days(start, 1_000_000).unwrap();
+        assert_eq!(calendar.business_days_between(start, later), 1_000_000);
+    }
+}
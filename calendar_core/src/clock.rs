@@ -0,0 +1,203 @@
+/*!
+ * Sources of the current time.
+ *
+ * Everything that depends on "now" — highlighting today, firing reminders,
+ * jumping to today — asks a `Clock` instead of calling `Utc::now()`, so that
+ * tests can pin the time to a midnight, the end of a month or a daylight
+ * saving transition with a `FixedClock`.
+ */
+
+use crate::settings::Settings;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::cell::Cell;
+
+/**
+ * A source of the current point in time.
+ */
+pub trait Clock {
+    /**
+     * Returns the current point in time.
+     */
+    fn now(&self) -> DateTime<Utc>;
+
+    /**
+     * Function to determine the current wall-clock time.
+     *
+     * Returns:
+     * - The local time in the time zone of `settings`.
+     */
+    fn local_now(&self, settings: &Settings) -> NaiveDateTime {
+        settings.local_time(self.now())
+    }
+
+    /**
+     * Function to determine the current date.
+     *
+     * Returns:
+     * - The date in the time zone of `settings`.
+     */
+    fn today(&self, settings: &Settings) -> NaiveDate {
+        settings.today(self.now())
+    }
+}
+
+/**
+ * The clock of the operating system.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/**
+ * A clock that stands still until it is set or advanced.
+ */
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Cell<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> FixedClock {
+        FixedClock {
+            now: Cell::new(now),
+        }
+    }
+
+    /**
+     * Function to move the clock to another point in time, also backwards.
+     */
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.now.set(now);
+    }
+
+    /**
+     * Function to let time pass.
+     */
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+}
+
+/**
+ * Remembers the current date to notice when it changes, e.g. at midnight
+ * while the application is open.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayWatch {
+    today: NaiveDate,
+}
+
+impl DayWatch {
+    pub fn new(today: NaiveDate) -> DayWatch {
+        DayWatch { today }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.today
+    }
+
+    /**
+     * Function to check whether the date changed since the last call.
+     *
+     * Parameters:
+     * - `today`: The current date.
+     *
+     * Returns:
+     * - Whether `today` differs from the remembered date, which it replaces.
+     *   A clock set back to the previous day counts as a change too.
+     */
+    pub fn update(&mut self, today: NaiveDate) -> bool {
+        let changed = today != self.today;
+        self.today = today;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn in_zone(name: &str) -> Settings {
+        Settings {
+            time_zone: Some(name.to_string()),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn the_date_rolls_over_at_local_midnight() {
+        let settings = in_zone("Europe/Berlin");
+        // 23:59 in Berlin on the last day of October.
+        let clock = FixedClock::new(utc(2026, 10, 31, 22, 59));
+        let mut watch = DayWatch::new(clock.today(&settings));
+        assert_eq!(watch.today(), date(2026, 10, 31));
+
+        clock.advance(Duration::seconds(59));
+        assert!(!watch.update(clock.today(&settings)));
+        clock.advance(Duration::seconds(1));
+        assert!(watch.update(clock.today(&settings)));
+        assert_eq!(watch.today(), date(2026, 11, 1));
+        assert!(!watch.update(clock.today(&settings)));
+
+        // The year rolls over as well.
+        clock.set(utc(2026, 12, 31, 23, 0));
+        assert!(watch.update(clock.today(&settings)));
+        assert_eq!(watch.today(), date(2027, 1, 1));
+    }
+
+    #[test]
+    fn daylight_saving_transitions_keep_the_date() {
+        let settings = in_zone("America/New_York");
+        // 1:59 EST, one minute before the clocks jump to 3:00.
+        let clock = FixedClock::new(utc(2026, 3, 8, 6, 59));
+        let mut watch = DayWatch::new(clock.today(&settings));
+        clock.advance(Duration::minutes(1));
+        assert_eq!(
+            clock.local_now(&settings),
+            date(2026, 3, 8).and_hms_opt(3, 0, 0).unwrap()
+        );
+        assert!(!watch.update(clock.today(&settings)));
+
+        // 1:59 EDT, one minute before the clocks fall back to 1:00.
+        clock.set(utc(2026, 11, 1, 5, 59));
+        assert!(watch.update(clock.today(&settings)));
+        clock.advance(Duration::minutes(1));
+        assert_eq!(
+            clock.local_now(&settings),
+            date(2026, 11, 1).and_hms_opt(1, 0, 0).unwrap()
+        );
+        assert!(!watch.update(clock.today(&settings)));
+        assert_eq!(watch.today(), date(2026, 11, 1));
+    }
+
+    #[test]
+    fn clocks_set_back_are_noticed() {
+        let settings = in_zone("Asia/Tokyo");
+        let clock = FixedClock::new(utc(2026, 10, 16, 3, 0));
+        let mut watch = DayWatch::new(clock.today(&settings));
+        clock.set(utc(2026, 10, 14, 3, 0));
+        assert!(watch.update(clock.today(&settings)));
+        assert_eq!(watch.today(), date(2026, 10, 14));
+    }
+}
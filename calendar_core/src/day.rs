@@ -0,0 +1,176 @@
+use crate::event::{EventStore, Occurrence};
+use crate::week::{layout_day, TimedBlock, MINUTES_PER_DAY};
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/**
+ * The length of the slots the time axis of the day view is divided into.
+ *
+ * Stored in the settings as the number of minutes.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum SlotSize {
+    Quarter,
+    #[default]
+    Half,
+    Hour,
+}
+
+impl SlotSize {
+    /** Every slot size, shortest first. */
+    pub const ALL: [SlotSize; 3] = [SlotSize::Quarter, SlotSize::Half, SlotSize::Hour];
+
+    pub fn minutes(self) -> u32 {
+        match self {
+            SlotSize::Quarter => 15,
+            SlotSize::Half => 30,
+            SlotSize::Hour => 60,
+        }
+    }
+
+    /**
+     * Returns the start of every slot of a day, in minutes after midnight.
+     */
+    pub fn slots(self) -> impl Iterator<Item = u32> {
+        (0..MINUTES_PER_DAY).step_by(self.minutes() as usize)
+    }
+}
+
+impl TryFrom<u32> for SlotSize {
+    type Error = String;
+
+    fn try_from(minutes: u32) -> Result<SlotSize, String> {
+        SlotSize::ALL
+            .into_iter()
+            .find(|size| size.minutes() == minutes)
+            .ok_or_else(|| format!("unsupported slot size of {minutes} minutes"))
+    }
+}
+
+impl From<SlotSize> for u32 {
+    fn from(size: SlotSize) -> u32 {
+        size.minutes()
+    }
+}
+
+impl fmt::Display for SlotSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} min", self.minutes())
+    }
+}
+
+/**
+ * The events of a single day, laid out for a timeline with an agenda next to it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayLayout<'a> {
+    pub date: NaiveDate,
+    /** Every event covering the day, all-day events first, then by start. */
+    pub agenda: Vec<Occurrence<'a>>,
+    /** The timed events drawn on the time axis. */
+    pub timed: Vec<TimedBlock<'a>>,
+}
+
+impl<'a> DayLayout<'a> {
+    /**
+     * Lays out the events covering `date`.
+     */
+    pub fn new(store: &'a EventStore, date: NaiveDate) -> DayLayout<'a> {
+        let agenda = store.events_on(date);
+        let timed: Vec<Occurrence> = agenda
+            .iter()
+            .filter(|occurrence| !occurrence.time.is_all_day())
+            .copied()
+            .collect();
+        DayLayout {
+            date,
+            timed: layout_day(date, 0, &timed),
+            agenda,
+        }
+    }
+
+    /**
+     * Function to position the current-time indicator.
+     *
+     * Parameters:
+     * - `now`: The current wall-clock time.
+     *
+     * Returns:
+     * - The minutes after midnight of `now`, or `None` if `now` is on another day.
+     */
+    pub fn now_minute(&self, now: NaiveDateTime) -> Option<u32> {
+        (now.date() == self.date).then(|| now.hour() * 60 + now.minute())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, EventTime};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn slots_cover_the_whole_day() {
+        assert_eq!(SlotSize::Quarter.slots().count(), 96);
+        assert_eq!(SlotSize::Half.slots().count(), 48);
+        assert_eq!(SlotSize::Hour.slots().count(), 24);
+        assert_eq!(SlotSize::Half.slots().nth(3), Some(90));
+        assert_eq!(SlotSize::Hour.slots().last(), Some(23 * 60));
+    }
+
+    #[test]
+    fn slot_sizes_are_stored_as_minutes() {
+        assert_eq!(serde_json::to_string(&SlotSize::Quarter).unwrap(), "15");
+        assert_eq!(
+            serde_json::from_str::<SlotSize>("60").unwrap(),
+            SlotSize::Hour
+        );
+        assert!(serde_json::from_str::<SlotSize>("20").is_err());
+    }
+
+    #[test]
+    fn agenda_lists_every_event_of_the_day() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        store.insert(Event::new(
+            "Lunch",
+            EventTime::timed(at(day, 12, 0), at(day, 13, 0)).unwrap(),
+        ));
+        store.insert(Event::new("Holiday", EventTime::all_day(day, day).unwrap()));
+        store.insert(Event::new(
+            "Tomorrow",
+            EventTime::timed(at(date(2026, 10, 17), 9, 0), at(date(2026, 10, 17), 10, 0)).unwrap(),
+        ));
+
+        let layout = DayLayout::new(&store, day);
+        let titles: Vec<&str> = layout
+            .agenda
+            .iter()
+            .map(|occurrence| occurrence.event.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Holiday", "Lunch"]);
+        assert_eq!(layout.timed.len(), 1);
+        assert_eq!(
+            (layout.timed[0].start_minute, layout.timed[0].end_minute),
+            (720, 780)
+        );
+    }
+
+    #[test]
+    fn now_line_is_only_shown_on_the_current_day() {
+        let store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let layout = DayLayout::new(&store, day);
+        assert_eq!(layout.now_minute(at(day, 14, 35)), Some(875));
+        assert_eq!(layout.now_minute(at(date(2026, 10, 17), 0, 5)), None);
+    }
+}
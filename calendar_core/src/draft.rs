@@ -0,0 +1,627 @@
+use crate::event::{Color, Event, EventTime};
+use crate::recurrence::{Recurrence, RecurrenceRule};
+use crate::reminder::Reminder;
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::Tz;
+use std::fmt;
+
+/**
+ * How long a timed event lasts when no end time is given, in minutes.
+ */
+pub const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/**
+ * The units reminder offsets are written in, largest first, with their
+ * length in seconds.
+ */
+const REMINDER_UNITS: [(char, i64); 5] = [
+    ('w', 7 * 24 * 3600),
+    ('d', 24 * 3600),
+    ('h', 3600),
+    ('m', 60),
+    ('s', 1),
+];
+
+/**
+ * Errors reported when the fields of an event draft do not describe an event.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DraftError {
+    EmptyTitle,
+    /** A date that is not written as YYYY-MM-DD. */
+    InvalidDate(String),
+    /** A time that is not written as HH:MM. */
+    InvalidTime(String),
+    /** A color that is not written as #rrggbb. */
+    InvalidColor(String),
+    /** A name that is not an IANA time zone. */
+    InvalidTimeZone(String),
+    /** An RRULE value that cannot be parsed, with the reason. */
+    InvalidRecurrence(String),
+    /** A reminder that is neither an offset nor a date and time. */
+    InvalidReminder(String),
+    /** An end time without a start time. */
+    MissingStartTime,
+    EndBeforeStart,
+}
+
+impl fmt::Display for DraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftError::EmptyTitle => write!(f, "the title is empty"),
+            DraftError::InvalidDate(text) => write!(f, "{text:?} is not a date (YYYY-MM-DD)"),
+            DraftError::InvalidTime(text) => write!(f, "{text:?} is not a time (HH:MM)"),
+            DraftError::InvalidColor(text) => write!(f, "{text:?} is not a color (#rrggbb)"),
+            DraftError::InvalidTimeZone(text) => {
+                write!(f, "{text:?} is not a time zone (e.g. Europe/Berlin)")
+            }
+            DraftError::InvalidRecurrence(reason) => {
+                write!(f, "invalid recurrence rule: {reason}")
+            }
+            DraftError::InvalidReminder(text) => write!(
+                f,
+                "{text:?} is not a reminder (e.g. 15m, 2h, 1d or YYYY-MM-DD HH:MM)"
+            ),
+            DraftError::MissingStartTime => write!(f, "an end time needs a start time"),
+            DraftError::EndBeforeStart => write!(f, "the event ends before it starts"),
+        }
+    }
+}
+
+impl std::error::Error for DraftError {}
+
+/**
+ * The fields of a draft problems are reported for, in the order they are
+ * usually displayed.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DraftField {
+    Title,
+    StartDate,
+    StartTime,
+    EndDate,
+    EndTime,
+    TimeZone,
+    Recurrence,
+    Reminders,
+    Color,
+}
+
+/**
+ * The fields of an event as entered by the user, before validation.
+ *
+ * Every front-end creating or editing events fills a draft and converts it
+ * with `validate`, `to_event` or `apply`, so that they all accept the same
+ * input. Dates are written as YYYY-MM-DD, times as HH:MM and colors as
+ * #rrggbb. The time zone is an IANA name and the recurrence an RRULE value
+ * such as `FREQ=WEEKLY;BYDAY=MO`. Reminders are separated by commas, each
+ * either an offset before the start such as `15m`, `2h`, `1d` or `1w`, an
+ * offset after it such as `5m after`, or a date and time written as
+ * YYYY-MM-DD HH:MM.
+ *
+ * A draft without start and end time describes an all-day event. Empty
+ * optional fields take their defaults: the end date is the start date, a
+ * timed event without end time lasts `DEFAULT_DURATION_MINUTES`, the color
+ * is the default event color, and the event floats in local time, does not
+ * repeat, has no reminders and belongs to the default calendar.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventDraft {
+    pub title: String,
+    pub start_date: String,
+    pub start_time: String,
+    pub end_date: String,
+    pub end_time: String,
+    pub location: String,
+    pub notes: String,
+    pub color: String,
+    pub time_zone: String,
+    pub recurrence: String,
+    pub reminders: String,
+    pub calendar: String,
+}
+
+impl EventDraft {
+    /**
+     * Fills a draft with the fields of an existing event, for editing it.
+     */
+    pub fn from_event(event: &Event) -> EventDraft {
+        let (start_time, end_time) = match event.time {
+            EventTime::AllDay { .. } => (String::new(), String::new()),
+            EventTime::Timed { start, end } => (
+                start.format("%H:%M").to_string(),
+                end.format("%H:%M").to_string(),
+            ),
+        };
+        let recurrence = event
+            .recurrence
+            .as_ref()
+            .and_then(|recurrence| recurrence.rule.as_ref())
+            .map(|rule| rule.to_ical(event.time.is_all_day()));
+        let reminders: Vec<String> = event.reminders.iter().map(format_reminder).collect();
+        EventDraft {
+            title: event.title.clone(),
+            start_date: event.time.start_date().format("%Y-%m-%d").to_string(),
+            start_time,
+            end_date: event.time.end_date().format("%Y-%m-%d").to_string(),
+            end_time,
+            location: event.location.clone().unwrap_or_default(),
+            notes: event.notes.clone().unwrap_or_default(),
+            color: event.color.to_hex(),
+            time_zone: event.time_zone.clone().unwrap_or_default(),
+            recurrence: recurrence.unwrap_or_default(),
+            reminders: reminders.join(", "),
+            calendar: event.calendar.clone().unwrap_or_default(),
+        }
+    }
+
+    /**
+     * Function to validate the draft and build the event it describes.
+     *
+     * Returns:
+     * - A new event, or the first problem found in the draft.
+     */
+    pub fn to_event(&self) -> Result<Event, DraftError> {
+        self.validate(None).map_err(first)
+    }
+
+    /**
+     * Function to apply the draft to an existing event.
+     *
+     * Parameters:
+     * - `event`: The event being edited; its identity is kept, and so are
+     *   the exceptions of its recurrence unless the rule changed.
+     *
+     * Returns:
+     * - The edited event, or the first problem found in the draft.
+     */
+    pub fn apply(&self, event: &Event) -> Result<Event, DraftError> {
+        self.validate(Some(event)).map_err(first)
+    }
+
+    /**
+     * Function to validate every field of the draft, for forms showing each
+     * problem next to the field it concerns.
+     *
+     * Parameters:
+     * - `original`: The event being edited, if any, as for `apply`.
+     *
+     * Returns:
+     * - The event, or at least one problem, at most one per field, ordered
+     *   by field.
+     */
+    pub fn validate(
+        &self,
+        original: Option<&Event>,
+    ) -> Result<Event, Vec<(DraftField, DraftError)>> {
+        let mut errors = Vec::new();
+        let title = self.title.trim();
+        if title.is_empty() {
+            errors.push((DraftField::Title, DraftError::EmptyTitle));
+        }
+        let time = self.time(&mut errors);
+        let color = check(&mut errors, DraftField::Color, self.color());
+        let time_zone = check(&mut errors, DraftField::TimeZone, self.time_zone());
+        let rule = check(&mut errors, DraftField::Recurrence, self.rule());
+        let reminders = check(&mut errors, DraftField::Reminders, self.reminders());
+        errors.sort_by_key(|(field, _)| *field);
+        let (Some(time), Some(color), Some(time_zone), Some(rule), Some(reminders)) =
+            (time, color, time_zone, rule, reminders)
+        else {
+            return Err(errors);
+        };
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut event = Event::new(title, time);
+        event.location = non_empty(&self.location);
+        event.notes = non_empty(&self.notes);
+        event.color = color;
+        event.time_zone = time_zone;
+        event.recurrence = match original.and_then(|event| event.recurrence.as_ref()) {
+            // An unchanged rule keeps the exceptions of the series.
+            Some(recurrence) if recurrence.rule == rule => Some(recurrence.clone()),
+            _ => rule.map(Recurrence::from_rule),
+        };
+        event.reminders = reminders;
+        event.calendar = non_empty(&self.calendar);
+        event.uid = original.and_then(|event| event.uid.clone());
+        Ok(event)
+    }
+
+    /**
+     * Function to validate the dates and times of the draft.
+     *
+     * Returns:
+     * - The time of the event, or `None` after adding the problems found
+     *   to `errors`.
+     */
+    fn time(&self, errors: &mut Vec<(DraftField, DraftError)>) -> Option<EventTime> {
+        let start_date = check(errors, DraftField::StartDate, parse_date(&self.start_date));
+        let end_date = match self.end_date.trim() {
+            "" => start_date,
+            text => check(errors, DraftField::EndDate, parse_date(text)),
+        };
+        let start_time = check(errors, DraftField::StartTime, parse_time(&self.start_time));
+        let end_time = check(errors, DraftField::EndTime, parse_time(&self.end_time));
+        let (start_date, end_date) = (start_date?, end_date?);
+        let time = match (start_time?, end_time?) {
+            (None, None) => EventTime::all_day(start_date, end_date),
+            (None, Some(_)) => {
+                errors.push((DraftField::StartTime, DraftError::MissingStartTime));
+                return None;
+            }
+            (Some(start), None) => {
+                let start = start_date.and_time(start);
+                start
+                    .checked_add_signed(Duration::minutes(DEFAULT_DURATION_MINUTES))
+                    .and_then(|end| EventTime::timed(start, end))
+            }
+            (Some(start), Some(end)) => {
+                EventTime::timed(start_date.and_time(start), end_date.and_time(end))
+            }
+        };
+        if time.is_none() {
+            let field = if end_date < start_date {
+                DraftField::EndDate
+            } else {
+                DraftField::EndTime
+            };
+            errors.push((field, DraftError::EndBeforeStart));
+        }
+        time
+    }
+
+    fn color(&self) -> Result<Color, DraftError> {
+        match self.color.trim() {
+            "" => Ok(Color::default()),
+            color => {
+                Color::from_hex(color).ok_or_else(|| DraftError::InvalidColor(color.to_string()))
+            }
+        }
+    }
+
+    fn time_zone(&self) -> Result<Option<String>, DraftError> {
+        match self.time_zone.trim() {
+            "" => Ok(None),
+            name => match name.parse::<Tz>() {
+                Ok(_) => Ok(Some(name.to_string())),
+                Err(_) => Err(DraftError::InvalidTimeZone(name.to_string())),
+            },
+        }
+    }
+
+    fn rule(&self) -> Result<Option<RecurrenceRule>, DraftError> {
+        match self.recurrence.trim() {
+            "" => Ok(None),
+            rule => rule
+                .parse::<RecurrenceRule>()
+                .map(Some)
+                .map_err(|err| DraftError::InvalidRecurrence(err.0)),
+        }
+    }
+
+    fn reminders(&self) -> Result<Vec<Reminder>, DraftError> {
+        self.reminders
+            .split(',')
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(parse_reminder)
+            .collect()
+    }
+}
+
+/**
+ * Function to record the problem of a field.
+ *
+ * Returns:
+ * - The value of the field, or `None` if it is invalid.
+ */
+fn check<T>(
+    errors: &mut Vec<(DraftField, DraftError)>,
+    field: DraftField,
+    result: Result<T, DraftError>,
+) -> Option<T> {
+    result.map_err(|error| errors.push((field, error))).ok()
+}
+
+/**
+ * Returns the first problem of a failed validation, which always has one.
+ */
+fn first(mut errors: Vec<(DraftField, DraftError)>) -> DraftError {
+    errors.remove(0).1
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, DraftError> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map_err(|_| DraftError::InvalidDate(text.trim().to_string()))
+}
+
+/**
+ * Parses an optional time, an empty text meaning no time.
+ */
+fn parse_time(text: &str) -> Result<Option<NaiveTime>, DraftError> {
+    match text.trim() {
+        "" => Ok(None),
+        text => NaiveTime::parse_from_str(text, "%H:%M")
+            .map(Some)
+            .map_err(|_| DraftError::InvalidTime(text.to_string())),
+    }
+}
+
+/**
+ * Function to parse a reminder written as in `EventDraft`, e.g. `15m`,
+ * `5m after` or `2026-10-16 09:00`.
+ */
+fn parse_reminder(text: &str) -> Result<Reminder, DraftError> {
+    let invalid = || DraftError::InvalidReminder(text.to_string());
+    if let Ok(at) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M") {
+        return Ok(Reminder::Absolute(at));
+    }
+    let (offset, after) = match text.strip_suffix("after") {
+        Some(offset) => (offset.trim_end(), true),
+        None => (text, false),
+    };
+    let unit = offset.chars().last().ok_or_else(invalid)?;
+    let (_, size) = REMINDER_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit.to_ascii_lowercase())
+        .ok_or_else(invalid)?;
+    let amount: i64 = offset[..offset.len() - unit.len_utf8()]
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| invalid())?
+        .into();
+    let offset = Duration::try_seconds(amount * size).ok_or_else(invalid)?;
+    Ok(Reminder::Relative(if after { offset } else { -offset }))
+}
+
+/**
+ * Function to write a reminder the way `parse_reminder` reads it, using the
+ * largest unit the offset is a multiple of.
+ */
+fn format_reminder(reminder: &Reminder) -> String {
+    match *reminder {
+        Reminder::Absolute(at) => at.format("%Y-%m-%d %H:%M").to_string(),
+        Reminder::Relative(offset) => {
+            let seconds = offset.num_seconds();
+            let (unit, size) = REMINDER_UNITS
+                .iter()
+                .find(|(_, size)| seconds != 0 && seconds % size == 0)
+                .unwrap_or(&('m', 60));
+            let text = format!("{}{}", seconds.abs() / size, unit);
+            if seconds > 0 {
+                format!("{text} after")
+            } else {
+                text
+            }
+        }
+    }
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recurrence::Frequency;
+
+    fn draft(title: &str, start_date: &str) -> EventDraft {
+        EventDraft {
+            title: title.to_string(),
+            start_date: start_date.to_string(),
+            ..EventDraft::default()
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn a_draft_without_times_is_all_day() {
+        let event = draft(" Holiday ", "2026-10-16").to_event().unwrap();
+        assert_eq!(event.title, "Holiday");
+        assert_eq!(
+            event.time,
+            EventTime::AllDay {
+                start: at(16, 0, 0).date(),
+                end: at(16, 0, 0).date()
+            }
+        );
+        assert_eq!(event.color, Color::default());
+        assert_eq!(event.location, None);
+    }
+
+    #[test]
+    fn timed_drafts_default_to_an_hour() {
+        let mut timed = draft("Dentist", "2026-10-16");
+        timed.start_time = "09:30".to_string();
+        timed.location = "Main St 1".to_string();
+        timed.color = "#ff0000".to_string();
+        let event = timed.to_event().unwrap();
+        assert_eq!(
+            event.time,
+            EventTime::timed(at(16, 9, 30), at(16, 10, 30)).unwrap()
+        );
+        assert_eq!(event.location.as_deref(), Some("Main St 1"));
+        assert_eq!(event.color, Color::rgb(255, 0, 0));
+
+        timed.end_date = "2026-10-17".to_string();
+        timed.end_time = "08:00".to_string();
+        let event = timed.to_event().unwrap();
+        assert_eq!(
+            event.time,
+            EventTime::timed(at(16, 9, 30), at(17, 8, 0)).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_fields_are_reported() {
+        let base = draft("Dentist", "2026-10-16");
+        let with = |change: fn(&mut EventDraft)| {
+            let mut draft = base.clone();
+            change(&mut draft);
+            draft.to_event().unwrap_err()
+        };
+        assert_eq!(with(|d| d.title = "  ".to_string()), DraftError::EmptyTitle);
+        assert_eq!(
+            with(|d| d.start_date = "16.10.2026".to_string()),
+            DraftError::InvalidDate("16.10.2026".to_string())
+        );
+        assert_eq!(
+            with(|d| d.start_time = "9am".to_string()),
+            DraftError::InvalidTime("9am".to_string())
+        );
+        assert_eq!(
+            with(|d| d.color = "red".to_string()),
+            DraftError::InvalidColor("red".to_string())
+        );
+        assert_eq!(
+            with(|d| d.end_time = "10:00".to_string()),
+            DraftError::MissingStartTime
+        );
+        assert_eq!(
+            with(|d| d.end_date = "2026-10-15".to_string()),
+            DraftError::EndBeforeStart
+        );
+    }
+
+    #[test]
+    fn editing_keeps_the_identity_of_the_event() {
+        let mut event = draft("Standup", "2026-10-16").to_event().unwrap();
+        event.uid = Some("standup@example.com".to_string());
+        event.recurrence = Some(Recurrence::from_rule(RecurrenceRule::new(Frequency::Daily)));
+
+        let mut edit = EventDraft::from_event(&event);
+        assert_eq!(edit.start_date, "2026-10-16");
+        assert_eq!(edit.end_date, "2026-10-16");
+        edit.title = "Daily standup".to_string();
+        edit.start_time = "09:00".to_string();
+        edit.end_time = "09:15".to_string();
+        let edited = edit.apply(&event).unwrap();
+        assert_eq!(edited.title, "Daily standup");
+        assert_eq!(
+            edited.time,
+            EventTime::timed(at(16, 9, 0), at(16, 9, 15)).unwrap()
+        );
+        assert_eq!(edited.uid, event.uid);
+        assert_eq!(edited.recurrence, event.recurrence);
+        assert_eq!(EventDraft::from_event(&edited).apply(&edited), Ok(edited));
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let invalid = EventDraft {
+            title: " ".to_string(),
+            start_date: "2026-10-16".to_string(),
+            start_time: "25:00".to_string(),
+            end_date: "2026-13-01".to_string(),
+            time_zone: "Europe/Atlantis".to_string(),
+            recurrence: "FREQ=SOMETIMES".to_string(),
+            reminders: "15m, soon".to_string(),
+            ..EventDraft::default()
+        };
+        let errors = invalid.validate(None).unwrap_err();
+        let fields: Vec<DraftField> = errors.iter().map(|(field, _)| *field).collect();
+        assert_eq!(
+            fields,
+            [
+                DraftField::Title,
+                DraftField::StartTime,
+                DraftField::EndDate,
+                DraftField::TimeZone,
+                DraftField::Recurrence,
+                DraftField::Reminders
+            ]
+        );
+        assert_eq!(
+            errors[2].1,
+            DraftError::InvalidDate("2026-13-01".to_string())
+        );
+        assert_eq!(errors[5].1, DraftError::InvalidReminder("soon".to_string()));
+        assert_eq!(invalid.to_event(), Err(DraftError::EmptyTitle));
+
+        // The end is reported where it has to be corrected.
+        let mut timed = draft("Dentist", "2026-10-16");
+        timed.start_time = "10:00".to_string();
+        timed.end_time = "09:00".to_string();
+        assert_eq!(
+            timed.validate(None).unwrap_err(),
+            [(DraftField::EndTime, DraftError::EndBeforeStart)]
+        );
+        timed.end_date = "2026-10-15".to_string();
+        assert_eq!(
+            timed.validate(None).unwrap_err(),
+            [(DraftField::EndDate, DraftError::EndBeforeStart)]
+        );
+    }
+
+    #[test]
+    fn reminders_time_zones_and_recurrences_are_parsed() {
+        let mut full = draft("Standup", "2026-10-16");
+        full.start_time = "09:00".to_string();
+        full.time_zone = " Europe/Berlin ".to_string();
+        full.recurrence = "FREQ=WEEKLY;BYDAY=MO,WE".to_string();
+        full.reminders = "15m, 2h,1d, 5m after, 90s, 2026-10-15 18:00".to_string();
+        full.calendar = "Work".to_string();
+        let event = full.to_event().unwrap();
+        assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(event.calendar.as_deref(), Some("Work"));
+        let rule = event
+            .recurrence
+            .as_ref()
+            .and_then(|r| r.rule.as_ref())
+            .unwrap();
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(
+            event.reminders,
+            [
+                Reminder::minutes_before(15),
+                Reminder::minutes_before(120),
+                Reminder::minutes_before(24 * 60),
+                Reminder::Relative(Duration::minutes(5)),
+                Reminder::Relative(-Duration::seconds(90)),
+                Reminder::Absolute(at(15, 18, 0)),
+            ]
+        );
+
+        let again = EventDraft::from_event(&event);
+        assert_eq!(
+            again.reminders,
+            "15m, 2h, 1d, 5m after, 90s, 2026-10-15 18:00"
+        );
+        assert_eq!(again.to_event(), Ok(event));
+    }
+
+    #[test]
+    fn changing_the_rule_drops_the_exceptions_of_a_series() {
+        let mut series = draft("Standup", "2026-10-16").to_event().unwrap();
+        series.recurrence = Some(Recurrence {
+            rule: Some(RecurrenceRule::new(Frequency::Daily)),
+            exdates: vec![at(17, 0, 0)],
+            rdates: Vec::new(),
+        });
+
+        let mut edit = EventDraft::from_event(&series);
+        edit.reminders = "10m".to_string();
+        let kept = edit.apply(&series).unwrap();
+        assert_eq!(kept.recurrence, series.recurrence);
+
+        edit.recurrence = "FREQ=WEEKLY".to_string();
+        let changed = edit.apply(&series).unwrap();
+        let weekly = Recurrence::from_rule(RecurrenceRule::new(Frequency::Weekly));
+        assert_eq!(changed.recurrence, Some(weekly));
+
+        edit.recurrence = String::new();
+        assert_eq!(edit.apply(&series).unwrap().recurrence, None);
+    }
+}
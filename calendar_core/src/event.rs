@@ -0,0 +1,1110 @@
+use crate::recurrence::Recurrence;
+use crate::reminder::Reminder;
+use chrono::prelude::*;
+use chrono::{Days, Duration};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::BuildHasher;
+
+/**
+ * Days by which converting a wall-clock time between two time zones can
+ * move its date, offsets ranging from UTC-12 to UTC+14.
+ */
+const ZONE_MARGIN_DAYS: u64 = 2;
+
+/**
+ * Identifier of an event inside an `EventStore`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EventId(pub u64);
+
+/**
+ * When an event takes place.
+ *
+ * All-day events cover whole dates, both ends inclusive. Timed events are
+ * expressed in local wall-clock time and end exclusively, so an event ending
+ * at midnight does not show up on the following day.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTime {
+    AllDay {
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+    Timed {
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    },
+}
+
+impl EventTime {
+    /**
+     * Creates an all-day span, or `None` if `end` is before `start`.
+     */
+    pub fn all_day(start: NaiveDate, end: NaiveDate) -> Option<EventTime> {
+        (start <= end).then_some(EventTime::AllDay { start, end })
+    }
+
+    /**
+     * Creates a timed span, or `None` if `end` is before `start`.
+     */
+    pub fn timed(start: NaiveDateTime, end: NaiveDateTime) -> Option<EventTime> {
+        (start <= end).then_some(EventTime::Timed { start, end })
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        matches!(self, EventTime::AllDay { .. })
+    }
+
+    /**
+     * Returns the date on which the event starts.
+     */
+    pub fn start_date(&self) -> NaiveDate {
+        match *self {
+            EventTime::AllDay { start, .. } => start,
+            EventTime::Timed { start, .. } => start.date(),
+        }
+    }
+
+    /**
+     * Returns the last date the event covers.
+     */
+    pub fn end_date(&self) -> NaiveDate {
+        match *self {
+            EventTime::AllDay { start, end } => end.max(start),
+            EventTime::Timed { start, end } => {
+                if end > start && end.time() == NaiveTime::MIN {
+                    end.date().pred_opt().unwrap_or(start.date())
+                } else {
+                    end.max(start).date()
+                }
+            }
+        }
+    }
+
+    /**
+     * Returns the start as a point in time, all-day events starting at midnight.
+     */
+    pub fn start(&self) -> NaiveDateTime {
+        match *self {
+            EventTime::AllDay { start, .. } => start.and_time(NaiveTime::MIN),
+            EventTime::Timed { start, .. } => start,
+        }
+    }
+
+    /**
+     * Returns whether the event covers at least part of `date`.
+     */
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start_date() <= date && date <= self.end_date()
+    }
+
+    /**
+     * Returns every date the event covers.
+     */
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> {
+        let end = self.end_date();
+        self.start_date()
+            .iter_days()
+            .take_while(move |date| *date <= end)
+    }
+
+    /**
+     * Function to convert a span between time zones. All-day spans keep
+     * their dates.
+     *
+     * Parameters:
+     * - `from`: The zone the wall-clock times of the span belong to.
+     * - `to`: The zone to convert to, the system's local zone if `None`.
+     */
+    pub fn converted(&self, from: Tz, to: Option<Tz>) -> EventTime {
+        match *self {
+            EventTime::AllDay { .. } => *self,
+            EventTime::Timed { start, end } => EventTime::Timed {
+                start: convert(start, from, to),
+                end: convert(end, from, to),
+            },
+        }
+    }
+
+    /**
+     * Returns the same span moved by `offset`. All-day spans move by whole days.
+     */
+    pub fn shifted(&self, offset: Duration) -> EventTime {
+        match *self {
+            EventTime::AllDay { start, end } => {
+                let days = Duration::days(offset.num_days());
+                EventTime::AllDay {
+                    start: start + days,
+                    end: end + days,
+                }
+            }
+            EventTime::Timed { start, end } => EventTime::Timed {
+                start: start + offset,
+                end: end + offset,
+            },
+        }
+    }
+}
+
+/**
+ * Function to convert a wall-clock time between time zones. Times skipped by
+ * a daylight saving transition are taken as the time an hour later, times
+ * that occur twice as the first of them.
+ */
+fn convert(time: NaiveDateTime, from: Tz, to: Option<Tz>) -> NaiveDateTime {
+    let instant = from.from_local_datetime(&time).earliest().or_else(|| {
+        let later = time.checked_add_signed(Duration::hours(1))?;
+        from.from_local_datetime(&later).earliest()
+    });
+    match (instant, to) {
+        (Some(instant), Some(to)) => instant.with_timezone(&to).naive_local(),
+        (Some(instant), None) => instant.with_timezone(&Local).naive_local(),
+        (None, _) => time,
+    }
+}
+
+/**
+ * An RGB color used to tell events apart in the UI.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /**
+     * Parses a color written as `#rrggbb`.
+     */
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let digits = hex.strip_prefix('#')?;
+        if digits.len() != 6 || !digits.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+        Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /**
+     * Formats the color as `#rrggbb`.
+     */
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color::rgb(0x48, 0x3d, 0x8b) // darkslateblue
+    }
+}
+
+/**
+ * A calendar entry.
+ *
+ * `uid` is the globally unique identifier used when exchanging the event with
+ * other calendar applications. `time_zone` names the IANA zone the wall-clock
+ * times belong to, and the `EventStore` converts occurrences from it to the
+ * zone the calendar is shown in; events without one float in the user's
+ * local time.
+ *
+ * For recurring events `time` is the first occurrence and `recurrence`
+ * describes the following ones. `reminders` go off for every occurrence.
+ * `calendar` names the calendar the event is filed under, e.g. "Work";
+ * events without one belong to the default calendar.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub title: String,
+    pub time: EventTime,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub color: Color,
+    pub uid: Option<String>,
+    pub time_zone: Option<String>,
+    pub recurrence: Option<Recurrence>,
+    pub reminders: Vec<Reminder>,
+    pub calendar: Option<String>,
+}
+
+impl Event {
+    /**
+     * Creates an event with the given title and time, leaving everything else empty.
+     */
+    pub fn new(title: impl Into<String>, time: EventTime) -> Event {
+        Event {
+            title: title.into(),
+            time,
+            location: None,
+            notes: None,
+            color: Color::default(),
+            uid: None,
+            time_zone: None,
+            recurrence: None,
+            reminders: Vec::new(),
+            calendar: None,
+        }
+    }
+}
+
+/**
+ * A single appearance of an event in the calendar. Events without a
+ * recurrence have exactly one occurrence.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence<'a> {
+    pub id: EventId,
+    pub event: &'a Event,
+    /** The time of this occurrence in the zone the calendar is shown in. */
+    pub time: EventTime,
+    /**
+     * The start of this occurrence in the time zone of the event, which
+     * identifies it, e.g. for `update_occurrence`.
+     */
+    pub recurrence_id: NaiveDateTime,
+}
+
+impl Occurrence<'_> {
+    pub fn start(&self) -> NaiveDateTime {
+        self.time.start()
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        self.event.recurrence.is_some()
+    }
+}
+
+/**
+ * Which occurrences of a recurring event an edit or removal applies to.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditScope {
+    /** Only the selected occurrence, which is detached from the series. */
+    ThisOccurrence,
+    /** The selected occurrence and every later one; the series is split in two. */
+    ThisAndFollowing,
+    /** Every occurrence of the series. */
+    All,
+}
+
+/**
+ * Function to generate a globally unique identifier for an event.
+ *
+ * The identifier mixes the current time with the randomly seeded standard
+ * library hasher, which is unique enough to exchange events with other
+ * calendars without pulling in a UUID implementation.
+ */
+fn generate_uid(id: EventId) -> String {
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let random = RandomState::new().hash_one((id, nanos));
+    format!("{:016x}-{:016x}@idk2", nanos, random)
+}
+
+/**
+ * In-memory collection of events, indexed by every date they cover.
+ *
+ * Recurring events cannot be indexed by date as their occurrences may never
+ * end; they are kept aside and expanded only for the dates that are asked for.
+ *
+ * Every event in the store has a `uid`; events added without one are
+ * assigned a freshly generated identifier.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct EventStore {
+    next_id: u64,
+    events: BTreeMap<EventId, Event>,
+    by_date: BTreeMap<NaiveDate, Vec<EventId>>,
+    recurring: BTreeSet<EventId>,
+    /** The zone occurrences are shown in, the system's local zone if `None`. */
+    zone: Option<Tz>,
+}
+
+impl EventStore {
+    pub fn new() -> EventStore {
+        EventStore::default()
+    }
+
+    pub fn zone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    /**
+     * Sets the zone occurrences of events with a `time_zone` are shown in.
+     */
+    pub fn set_zone(&mut self, zone: Option<Tz>) {
+        self.zone = zone;
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /**
+     * Adds an event to the store.
+     *
+     * Returns:
+     * - The identifier assigned to the event.
+     */
+    pub fn insert(&mut self, event: Event) -> EventId {
+        let id = EventId(self.next_id);
+        self.next_id += 1;
+        self.store(id, event);
+        id
+    }
+
+    /**
+     * Adds an event under a known identifier, e.g. when loading a saved calendar.
+     *
+     * Returns:
+     * - The event previously stored under that identifier, if any.
+     */
+    pub fn insert_with_id(&mut self, id: EventId, event: Event) -> Option<Event> {
+        let previous = self.remove(id);
+        self.next_id = self.next_id.max(id.0 + 1);
+        self.store(id, event);
+        previous
+    }
+
+    /**
+     * Replaces the event with the given identifier. A replacement without a
+     * `uid` keeps the one of the previous version.
+     *
+     * Returns:
+     * - The previous version of the event, or `None` if there is no such event.
+     */
+    pub fn update(&mut self, id: EventId, mut event: Event) -> Option<Event> {
+        let previous = self.remove(id)?;
+        if event.uid.is_none() {
+            event.uid = previous.uid.clone();
+        }
+        self.store(id, event);
+        Some(previous)
+    }
+
+    /**
+     * Removes the event with the given identifier from the store.
+     */
+    pub fn remove(&mut self, id: EventId) -> Option<Event> {
+        let event = self.events.remove(&id)?;
+        if self.recurring.remove(&id) {
+            return Some(event);
+        }
+        for date in event.time.dates() {
+            if let Some(ids) = self.by_date.get_mut(&date) {
+                ids.retain(|other| *other != id);
+                if ids.is_empty() {
+                    self.by_date.remove(&date);
+                }
+            }
+        }
+        Some(event)
+    }
+
+    pub fn get(&self, id: EventId) -> Option<&Event> {
+        self.events.get(&id)
+    }
+
+    /**
+     * Returns the identifier of the event with the given `uid`, if any.
+     */
+    pub fn find_by_uid(&self, uid: &str) -> Option<EventId> {
+        self.iter()
+            .find(|(_, event)| event.uid.as_deref() == Some(uid))
+            .map(|(id, _)| id)
+    }
+
+    /**
+     * Returns all events in the order they were added.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (EventId, &Event)> {
+        self.events.iter().map(|(id, event)| (*id, event))
+    }
+
+    /**
+     * Returns the occurrences covering `date`, all-day events first and the
+     * remaining ones ordered by start time.
+     */
+    pub fn events_on(&self, date: NaiveDate) -> Vec<Occurrence<'_>> {
+        self.occurrences_by_date(date, date)
+            .remove(&date)
+            .unwrap_or_default()
+    }
+
+    /**
+     * Returns the events with an occurrence covering at least one date between
+     * `start` and `end` (inclusive), each event listed once and ordered by start.
+     */
+    pub fn events_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<(EventId, &Event)> {
+        // One occurrence is enough, so open-ended series stop at the first.
+        let mut events: Vec<_> = self
+            .candidates(start, end)
+            .into_iter()
+            .filter(|id| self.expand(*id, start, end).next().is_some())
+            .map(|id| (id, &self.events[&id]))
+            .collect();
+        events.sort_by_key(|(id, event)| (event.time.start(), *id));
+        events
+    }
+
+    /**
+     * Function to list the occurrences covering a range of dates.
+     *
+     * Recurring events are expanded for the range only, so asking for a month
+     * materializes that month's occurrences and nothing else. Occurrences of
+     * events with a `time_zone` are converted to the zone set with `set_zone`.
+     *
+     * Parameters:
+     * - `start`: The first date of the range.
+     * - `end`: The last date of the range (inclusive).
+     *
+     * Returns:
+     * - The occurrences covering at least one date of the range, ordered by start.
+     */
+    pub fn occurrences_between(&self, start: NaiveDate, end: NaiveDate) -> Vec<Occurrence<'_>> {
+        let mut occurrences: Vec<Occurrence> = self
+            .candidates(start, end)
+            .into_iter()
+            .flat_map(|id| self.expand(id, start, end))
+            .collect();
+        occurrences.sort_by_key(|occurrence| (occurrence.start(), occurrence.id));
+        occurrences
+    }
+
+    /**
+     * Returns the events that may have an occurrence between `start` and
+     * `end` once converted to the zone of the store.
+     */
+    fn candidates(&self, start: NaiveDate, end: NaiveDate) -> BTreeSet<EventId> {
+        let margin = Days::new(ZONE_MARGIN_DAYS);
+        let first = start.checked_sub_days(margin).unwrap_or(NaiveDate::MIN);
+        let last = end.checked_add_days(margin).unwrap_or(NaiveDate::MAX);
+        self.by_date
+            .range(first..=last)
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .chain(self.recurring.iter().copied())
+            .collect()
+    }
+
+    /**
+     * Returns the occurrences of an event covering a date between `start` and
+     * `end`, in the order of their start in the zone of the event.
+     */
+    fn expand(
+        &self,
+        id: EventId,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl Iterator<Item = Occurrence<'_>> {
+        let event = &self.events[&id];
+        let zone = Self::event_zone(event);
+        let margin = Days::new(if zone.is_some() { ZONE_MARGIN_DAYS } else { 0 });
+        // Occurrences starting before the range may still reach into it.
+        let span = event.time.end_date() - event.time.start_date();
+        let from = start
+            .checked_sub_days(margin)
+            .and_then(|date| date.checked_sub_signed(span))
+            .unwrap_or(NaiveDate::MIN)
+            .and_time(NaiveTime::MIN);
+        let to = end
+            .checked_add_days(margin)
+            .and_then(|date| date.succ_opt())
+            .map_or(NaiveDateTime::MAX, |date| date.and_time(NaiveTime::MIN));
+
+        let dtstart = event.time.start();
+        let starts: Box<dyn Iterator<Item = NaiveDateTime>> = match &event.recurrence {
+            Some(recurrence) => Box::new(recurrence.iter_between(dtstart, from, to)),
+            None => Box::new(std::iter::once(dtstart)),
+        };
+        starts
+            .map(move |occurrence_start| {
+                let time = event.time.shifted(occurrence_start - dtstart);
+                Occurrence {
+                    id,
+                    event,
+                    time: zone.map_or(time, |zone| time.converted(zone, self.zone)),
+                    recurrence_id: occurrence_start,
+                }
+            })
+            .filter(move |occurrence| {
+                occurrence.time.start_date() <= end && occurrence.time.end_date() >= start
+            })
+    }
+
+    /**
+     * Returns the zone the wall-clock times of an event belong to, or `None`
+     * if they float in the zone of the store.
+     */
+    fn event_zone(event: &Event) -> Option<Tz> {
+        if event.time.is_all_day() {
+            return None;
+        }
+        event.time_zone.as_deref()?.parse().ok()
+    }
+
+    /**
+     * Returns `time`, a span in the zone of `event`, converted to the zone
+     * of the store.
+     */
+    pub fn local_time(&self, event: &Event, time: EventTime) -> EventTime {
+        match Self::event_zone(event) {
+            Some(zone) => time.converted(zone, self.zone),
+            None => time,
+        }
+    }
+
+    /**
+     * Returns the occurrences between `start` and `end` grouped by the dates
+     * they cover, each date ordered like `events_on`. Dates without any
+     * occurrence are left out.
+     */
+    pub fn occurrences_by_date(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BTreeMap<NaiveDate, Vec<Occurrence<'_>>> {
+        let mut by_date: BTreeMap<NaiveDate, Vec<Occurrence>> = BTreeMap::new();
+        for occurrence in self.occurrences_between(start, end) {
+            for date in occurrence.time.dates() {
+                if (start..=end).contains(&date) {
+                    by_date.entry(date).or_default().push(occurrence);
+                }
+            }
+        }
+        for occurrences in by_date.values_mut() {
+            occurrences.sort_by(|a, b| {
+                (!a.time.is_all_day(), a.start(), &a.event.title, a.id).cmp(&(
+                    !b.time.is_all_day(),
+                    b.start(),
+                    &b.event.title,
+                    b.id,
+                ))
+            });
+        }
+        by_date
+    }
+
+    /**
+     * Returns the event as it appears in the occurrence starting at `start`,
+     * or `None` if the event has no such occurrence.
+     */
+    pub fn occurrence(&self, id: EventId, start: NaiveDateTime) -> Option<Event> {
+        let event = self.get(id)?;
+        let dtstart = event.time.start();
+        let exists = match &event.recurrence {
+            Some(recurrence) => !recurrence.occurrences(dtstart, start, start).is_empty(),
+            None => dtstart == start,
+        };
+        exists.then(|| Event {
+            time: event.time.shifted(start - dtstart),
+            ..event.clone()
+        })
+    }
+
+    /**
+     * Function to edit one or more occurrences of an event.
+     *
+     * `edited` is the occurrence starting at `start` as it should look after
+     * the edit, typically obtained from `occurrence` and then modified. For
+     * `ThisOccurrence` the occurrence is excluded from the series and stored
+     * as a separate event. For `ThisAndFollowing` the series ends before the
+     * occurrence and a new series starts with the edited one, taking its
+     * recurrence from `edited`. For `All` the series is replaced, moving every
+     * occurrence by as much as the edited one moved. Events without a
+     * recurrence are simply updated.
+     *
+     * Parameters:
+     * - `id`: The event to edit.
+     * - `start`: The start of the selected occurrence.
+     * - `scope`: Which occurrences the edit applies to.
+     * - `edited`: The new version of the selected occurrence.
+     *
+     * Returns:
+     * - The identifier of the event holding the edited occurrence, or `None`
+     *   if there is no such event.
+     */
+    pub fn update_occurrence(
+        &mut self,
+        id: EventId,
+        start: NaiveDateTime,
+        scope: EditScope,
+        mut edited: Event,
+    ) -> Option<EventId> {
+        let mut series = self.get(id)?.clone();
+        let Some(recurrence) = series.recurrence.as_mut() else {
+            self.update(id, edited);
+            return Some(id);
+        };
+        let dtstart = series.time.start();
+        let moved_by = edited.time.start() - start;
+
+        match scope {
+            EditScope::ThisOccurrence => {
+                recurrence.exdates.push(start);
+                self.update(id, series);
+                edited.uid = None;
+                edited.recurrence = None;
+                Some(self.insert(edited))
+            }
+            EditScope::ThisAndFollowing if start > dtstart => {
+                let before = recurrence.count_before(dtstart, start);
+                truncate(recurrence, start, before);
+                self.update(id, series);
+
+                edited.uid = None;
+                if let Some(recurrence) = edited.recurrence.as_mut() {
+                    if let Some(rule) = recurrence.rule.as_mut() {
+                        rule.count = rule.count.map(|count| count.saturating_sub(before).max(1));
+                    }
+                    recurrence.exdates.retain(|date| *date > start);
+                    recurrence.rdates.retain(|date| *date > start);
+                    shift_dates(recurrence, moved_by);
+                }
+                Some(self.insert(edited))
+            }
+            EditScope::ThisAndFollowing | EditScope::All => {
+                edited.time = edited.time.shifted(dtstart - start);
+                if let Some(recurrence) = edited.recurrence.as_mut() {
+                    shift_dates(recurrence, moved_by);
+                }
+                self.update(id, edited);
+                Some(id)
+            }
+        }
+    }
+
+    /**
+     * Removes one or more occurrences of an event, see `update_occurrence`
+     * for the meaning of `scope`. Events without a recurrence are removed.
+     *
+     * Returns:
+     * - Whether the event exists.
+     */
+    pub fn remove_occurrence(
+        &mut self,
+        id: EventId,
+        start: NaiveDateTime,
+        scope: EditScope,
+    ) -> bool {
+        let Some(mut series) = self.get(id).cloned() else {
+            return false;
+        };
+        let dtstart = series.time.start();
+        match (scope, series.recurrence.as_mut()) {
+            (EditScope::ThisOccurrence, Some(recurrence)) => {
+                recurrence.exdates.push(start);
+                self.update(id, series);
+            }
+            (EditScope::ThisAndFollowing, Some(recurrence)) if start > dtstart => {
+                let before = recurrence.count_before(dtstart, start);
+                truncate(recurrence, start, before);
+                self.update(id, series);
+            }
+            _ => {
+                self.remove(id);
+            }
+        }
+        true
+    }
+
+    fn store(&mut self, id: EventId, mut event: Event) {
+        if event.uid.is_none() {
+            event.uid = Some(generate_uid(id));
+        }
+        if event.recurrence.is_some() {
+            self.recurring.insert(id);
+        } else {
+            for date in event.time.dates() {
+                self.by_date.entry(date).or_default().push(id);
+            }
+        }
+        self.events.insert(id, event);
+    }
+}
+
+/**
+ * Ends a series just before the occurrence starting at `start`, which is
+ * preceded by `before` occurrences generated by the rule.
+ */
+fn truncate(recurrence: &mut Recurrence, start: NaiveDateTime, before: u32) {
+    if let Some(rule) = recurrence.rule.as_mut() {
+        if rule.count.is_some() {
+            rule.count = Some(before);
+        } else {
+            let until = start - Duration::seconds(1);
+            rule.until = Some(rule.until.map_or(until, |other| other.min(until)));
+        }
+    }
+    recurrence.exdates.retain(|date| *date < start);
+    recurrence.rdates.retain(|date| *date < start);
+}
+
+/**
+ * Moves the explicitly listed occurrences of a series along with its start.
+ */
+fn shift_dates(recurrence: &mut Recurrence, offset: Duration) {
+    for date in recurrence
+        .exdates
+        .iter_mut()
+        .chain(recurrence.rdates.iter_mut())
+    {
+        *date += offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn single_day(date: NaiveDate) -> EventTime {
+        EventTime::all_day(date, date).unwrap()
+    }
+
+    /**
+     * Inserts a 15 minute daily event at 9:00 starting on 2026-10-01, following `rule`.
+     */
+    fn daily_standup(store: &mut EventStore, rule: &str) -> EventId {
+        let day = date(2026, 10, 1);
+        let mut event = Event::new(
+            "Standup",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        event.recurrence = Some(Recurrence::from_rule(rule.parse().unwrap()));
+        store.insert(event)
+    }
+
+    fn starts(
+        store: &EventStore,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Vec<(EventId, NaiveDateTime)> {
+        store
+            .occurrences_between(start, end)
+            .iter()
+            .map(|occurrence| (occurrence.id, occurrence.start()))
+            .collect()
+    }
+
+    #[test]
+    fn colors_round_trip_through_hex() {
+        let color = Color::rgb(0x12, 0xab, 0xff);
+        assert_eq!(color.to_hex(), "#12abff");
+        assert_eq!(Color::from_hex("#12ABFF"), Some(color));
+        assert_eq!(Color::from_hex("12abff"), None);
+        assert_eq!(Color::from_hex("#12abf"), None);
+        assert_eq!(Color::from_hex("#12abfg"), None);
+    }
+
+    #[test]
+    fn spans_reject_reversed_ends() {
+        let day = date(2026, 10, 16);
+        assert_eq!(EventTime::all_day(day, day.pred_opt().unwrap()), None);
+        assert_eq!(EventTime::timed(at(day, 10, 0), at(day, 9, 0)), None);
+    }
+
+    #[test]
+    fn timed_events_ending_at_midnight_stay_on_their_day() {
+        let day = date(2026, 10, 16);
+        let time = EventTime::timed(at(day, 22, 0), at(day.succ_opt().unwrap(), 0, 0)).unwrap();
+        assert_eq!(time.dates().collect::<Vec<_>>(), vec![day]);
+
+        let overnight =
+            EventTime::timed(at(day, 22, 0), at(day.succ_opt().unwrap(), 1, 0)).unwrap();
+        assert_eq!(overnight.dates().count(), 2);
+    }
+
+    #[test]
+    fn multi_day_events_are_indexed_on_every_date() {
+        let mut store = EventStore::new();
+        let time = EventTime::all_day(date(2026, 10, 30), date(2026, 11, 2)).unwrap();
+        let id = store.insert(Event::new("Trip", time));
+
+        for day in [date(2026, 10, 30), date(2026, 11, 1), date(2026, 11, 2)] {
+            assert_eq!(store.events_on(day).len(), 1);
+        }
+        assert!(store.events_on(date(2026, 11, 3)).is_empty());
+        assert_eq!(
+            store
+                .events_between(date(2026, 10, 1), date(2026, 11, 30))
+                .len(),
+            1
+        );
+
+        store.remove(id);
+        assert!(store.is_empty());
+        assert!(store.events_on(date(2026, 10, 31)).is_empty());
+    }
+
+    #[test]
+    fn events_on_puts_all_day_events_first() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        store.insert(Event::new(
+            "Lunch",
+            EventTime::timed(at(day, 12, 0), at(day, 13, 0)).unwrap(),
+        ));
+        store.insert(Event::new(
+            "Standup",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        ));
+        store.insert(Event::new("Holiday", EventTime::all_day(day, day).unwrap()));
+
+        let titles: Vec<_> = store
+            .events_on(day)
+            .iter()
+            .map(|occurrence| occurrence.event.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Holiday", "Standup", "Lunch"]);
+    }
+
+    #[test]
+    fn update_moves_the_event_between_dates() {
+        let mut store = EventStore::new();
+        let id = store.insert(Event::new(
+            "Review",
+            EventTime::all_day(date(2026, 10, 1), date(2026, 10, 1)).unwrap(),
+        ));
+        let moved = Event::new(
+            "Review",
+            EventTime::all_day(date(2026, 10, 8), date(2026, 10, 8)).unwrap(),
+        );
+
+        let uid = store.get(id).unwrap().uid.clone();
+        assert!(store.update(id, moved).is_some());
+        assert_eq!(store.get(id).unwrap().uid, uid);
+        assert!(store.events_on(date(2026, 10, 1)).is_empty());
+        assert_eq!(store.events_on(date(2026, 10, 8))[0].id, id);
+        assert!(store
+            .update(
+                EventId(42),
+                Event::new(
+                    "Missing",
+                    EventTime::all_day(date(2026, 10, 8), date(2026, 10, 8)).unwrap()
+                )
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn recurring_events_are_expanded_per_range() {
+        let mut store = EventStore::new();
+        let id = daily_standup(&mut store, "FREQ=WEEKLY;BYDAY=MO,TH");
+        let mut trip = Event::new(
+            "Trip",
+            EventTime::all_day(date(2026, 1, 30), date(2026, 2, 1)).unwrap(),
+        );
+        trip.recurrence = Some(Recurrence::from_rule("FREQ=MONTHLY".parse().unwrap()));
+        let trip = store.insert(trip);
+
+        let november = store.occurrences_by_date(date(2026, 11, 1), date(2026, 11, 30));
+        let standups = november
+            .values()
+            .flatten()
+            .filter(|occurrence| occurrence.id == id)
+            .count();
+        assert_eq!(standups, 9);
+        // The trip starting on October 30 reaches into November.
+        assert_eq!(november[&date(2026, 11, 1)][0].id, trip);
+        assert_eq!(
+            november[&date(2026, 11, 1)][0].time,
+            EventTime::all_day(date(2026, 10, 30), date(2026, 11, 1)).unwrap()
+        );
+
+        let on_thursday = store.events_on(date(2026, 10, 15));
+        assert_eq!(on_thursday.len(), 1);
+        assert_eq!(on_thursday[0].start(), at(date(2026, 10, 15), 9, 0));
+        assert!(store.events_on(date(2026, 10, 14)).is_empty());
+        assert_eq!(
+            store
+                .events_between(date(2030, 6, 1), date(2030, 6, 30))
+                .len(),
+            2
+        );
+        // Ranges left open stop at the first occurrence of each series.
+        assert_eq!(
+            store
+                .events_between(date(2026, 11, 1), NaiveDate::MAX)
+                .len(),
+            2
+        );
+        assert_eq!(
+            store.events_between(NaiveDate::MIN, NaiveDate::MAX).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn events_in_other_zones_are_shown_at_the_local_time() {
+        let mut store = EventStore::new();
+        store.set_zone(Some(chrono_tz::Europe::Berlin));
+        let start = at(date(2026, 10, 16), 20, 0);
+        let mut call = Event::new(
+            "Call",
+            EventTime::timed(start, start + Duration::hours(1)).unwrap(),
+        );
+        call.time_zone = Some("America/New_York".to_owned());
+        call.recurrence = Some(Recurrence::from_rule("FREQ=DAILY;COUNT=3".parse().unwrap()));
+        let id = store.insert(call);
+
+        // 20:00 in New York is 02:00 the next day in Berlin.
+        assert!(store.events_on(date(2026, 10, 16)).is_empty());
+        let occurrences = store.events_on(date(2026, 10, 17));
+        assert_eq!(occurrences.len(), 1);
+        assert_eq!(occurrences[0].start(), at(date(2026, 10, 17), 2, 0));
+        assert_eq!(occurrences[0].recurrence_id, start);
+        assert!(store.occurrence(id, occurrences[0].recurrence_id).is_some());
+        // The last of the three occurrences is moved to the day after the series.
+        let last = store.events_on(date(2026, 10, 19));
+        assert_eq!(last[0].start(), at(date(2026, 10, 19), 2, 0));
+        assert_eq!(
+            store
+                .events_between(date(2026, 10, 19), date(2026, 10, 30))
+                .len(),
+            1
+        );
+        assert!(store
+            .events_between(date(2026, 10, 20), date(2026, 10, 30))
+            .is_empty());
+    }
+
+    #[test]
+    fn editing_a_single_occurrence_detaches_it() {
+        let mut store = EventStore::new();
+        let id = daily_standup(&mut store, "FREQ=DAILY;COUNT=5");
+        let start = at(date(2026, 10, 3), 9, 0);
+
+        let mut edited = store.occurrence(id, start).unwrap();
+        assert_eq!(store.occurrence(id, at(date(2026, 10, 3), 10, 0)), None);
+        edited.title = "Planning".to_string();
+        edited.time = edited.time.shifted(Duration::hours(1));
+        let detached = store
+            .update_occurrence(id, start, EditScope::ThisOccurrence, edited)
+            .unwrap();
+
+        assert_ne!(detached, id);
+        assert_ne!(store.get(detached).unwrap().uid, store.get(id).unwrap().uid);
+        assert_eq!(store.get(detached).unwrap().recurrence, None);
+        let october = starts(&store, date(2026, 10, 1), date(2026, 10, 31));
+        assert_eq!(october.len(), 5);
+        assert_eq!(october[2], (detached, at(date(2026, 10, 3), 10, 0)));
+
+        assert!(store.remove_occurrence(
+            id,
+            at(date(2026, 10, 4), 9, 0),
+            EditScope::ThisOccurrence
+        ));
+        assert_eq!(
+            starts(&store, date(2026, 10, 1), date(2026, 10, 31)).len(),
+            4
+        );
+    }
+
+    #[test]
+    fn editing_following_occurrences_splits_the_series() {
+        let mut store = EventStore::new();
+        let counted = daily_standup(&mut store, "FREQ=DAILY;COUNT=10");
+        let start = at(date(2026, 10, 4), 9, 0);
+
+        let mut edited = store.occurrence(counted, start).unwrap();
+        edited.time = edited.time.shifted(Duration::minutes(30));
+        let tail = store
+            .update_occurrence(counted, start, EditScope::ThisAndFollowing, edited)
+            .unwrap();
+
+        let october = starts(&store, date(2026, 10, 1), date(2026, 10, 31));
+        assert_eq!(october.len(), 10);
+        assert!(october[..3].iter().all(|(id, _)| *id == counted));
+        assert_eq!(october[3], (tail, at(date(2026, 10, 4), 9, 30)));
+        assert_eq!(october[9], (tail, at(date(2026, 10, 10), 9, 30)));
+
+        let mut store = EventStore::new();
+        let open = daily_standup(&mut store, "FREQ=DAILY");
+        assert!(store.remove_occurrence(open, start, EditScope::ThisAndFollowing));
+        assert_eq!(
+            starts(&store, date(2026, 10, 1), date(2026, 12, 31)).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn editing_all_occurrences_moves_the_series() {
+        let mut store = EventStore::new();
+        let id = daily_standup(&mut store, "FREQ=DAILY;COUNT=3");
+        let start = at(date(2026, 10, 2), 9, 0);
+
+        let mut edited = store.occurrence(id, start).unwrap();
+        edited.title = "Sync".to_string();
+        edited.time = edited.time.shifted(Duration::days(1));
+        assert_eq!(
+            store.update_occurrence(id, start, EditScope::All, edited),
+            Some(id)
+        );
+
+        assert_eq!(store.get(id).unwrap().title, "Sync");
+        assert_eq!(
+            starts(&store, date(2026, 10, 1), date(2026, 10, 31)),
+            [
+                (id, at(date(2026, 10, 2), 9, 0)),
+                (id, at(date(2026, 10, 3), 9, 0)),
+                (id, at(date(2026, 10, 4), 9, 0)),
+            ]
+        );
+
+        assert!(store.remove_occurrence(id, start, EditScope::All));
+        assert!(store.is_empty());
+        assert!(!store.remove_occurrence(id, start, EditScope::All));
+    }
+
+    #[test]
+    fn insert_with_id_keeps_identifiers_unique() {
+        let mut store = EventStore::new();
+        let event = Event::new("Review", single_day(date(2026, 10, 1)));
+
+        assert!(store.insert_with_id(EventId(7), event.clone()).is_none());
+        assert!(store.insert_with_id(EventId(7), event.clone()).is_some());
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.insert(event), EventId(8));
+    }
+
+    #[test]
+    fn events_get_unique_uids() {
+        let mut store = EventStore::new();
+        let mut other = EventStore::new();
+        let event = Event::new("Review", single_day(date(2026, 10, 1)));
+        let mut imported = event.clone();
+        imported.uid = Some("review@example.com".to_string());
+
+        let a = store.insert(event.clone());
+        let b = other.insert(event);
+        let c = store.insert(imported);
+
+        let uid_a = store.get(a).unwrap().uid.as_deref().unwrap();
+        let uid_b = other.get(b).unwrap().uid.as_deref().unwrap();
+        assert!(uid_a.ends_with("@idk2"));
+        assert_ne!(uid_a, uid_b);
+        assert_eq!(
+            store.get(c).unwrap().uid.as_deref(),
+            Some("review@example.com")
+        );
+        assert_eq!(store.find_by_uid("review@example.com"), Some(c));
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Which month a cell of the grid belongs to.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    /** A trailing day of the previous month, padding the first week. */
+    PreviousMonth,
+    /** A day of the month the grid was generated for. */
+    CurrentMonth,
+    /** A leading day of the next month, padding the last week. */
+    NextMonth,
+}
+
+/**
+ * How the weeks of a year are numbered.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekNumbering {
+    /**
+     * ISO 8601: weeks start on Monday and week 1 is the week containing the
+     * first Thursday of the year, so the first days of January can belong
+     * to week 52 or 53 of the previous year.
+     */
+    Iso,
+    /**
+     * US: weeks start on Sunday and week 1 is the week containing January 1st,
+     * so the last days of December can belong to week 1 of the next year.
+     */
+    Us,
+    /**
+     * Simple: week 1 is January 1st to 7th, regardless of the weekday.
+     */
+    Simple,
+}
+
+/**
+ * A single cell of the month grid.
+ *
+ * Cells before the first and after the last day of the month belong to the
+ * adjacent months; `in_month` and `kind` tell them apart.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayCell {
+    pub date: NaiveDate,
+    pub in_month: bool,
+    pub kind: CellKind,
+    pub weekday: Weekday,
+}
+
+impl DayCell {
+    /**
+     * Returns whether the cell falls on a Saturday or Sunday.
+     */
+    pub fn is_weekend(&self) -> bool {
+        matches!(self.weekday, Weekday::Sat | Weekday::Sun)
+    }
+}
+
+/**
+ * The days displayed for one month, laid out in rows of seven starting on a
+ * configurable day of the week.
+ *
+ * The grid always consists of whole weeks, padded with the trailing days of the
+ * previous month and the leading days of the next month.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthGrid {
+    year: i32,
+    month: u32,
+    week_start: Weekday,
+    cells: Vec<DayCell>,
+}
+
+impl MonthGrid {
+    /**
+     * Builds the grid for the given month.
+     *
+     * Parameters:
+     * - `year`: The year for which the grid is being generated.
+     * - `month`: The month (1-12) for which the grid is being generated.
+     * - `week_start`: The day each row of the grid starts with.
+     *
+     * Returns:
+     * - The grid, or `None` if the month does not exist.
+     */
+    pub fn new(year: i32, month: u32, week_start: Weekday) -> Option<MonthGrid> {
+        let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let days_of_month = days_in_month(year, month)?;
+        let last_day = NaiveDate::from_ymd_opt(year, month, days_of_month)?;
+
+        let leading = get_last_days_of_prev_month(first_day.weekday(), week_start);
+        let trailing = get_first_days_of_next_month(last_day.weekday(), week_start);
+
+        let start = first_day - chrono::Duration::days(leading as i64);
+        let len = leading + days_of_month + trailing;
+
+        let cells = start
+            .iter_days()
+            .take(len as usize)
+            .map(|date| {
+                let kind = if date < first_day {
+                    CellKind::PreviousMonth
+                } else if date > last_day {
+                    CellKind::NextMonth
+                } else {
+                    CellKind::CurrentMonth
+                };
+                DayCell {
+                    date,
+                    in_month: kind == CellKind::CurrentMonth,
+                    kind,
+                    weekday: date.weekday(),
+                }
+            })
+            .collect();
+
+        Some(MonthGrid {
+            year,
+            month,
+            week_start,
+            cells,
+        })
+    }
+
+    /**
+     * Returns the year this grid was generated for.
+     */
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /**
+     * Returns the month (1-12) this grid was generated for.
+     */
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /**
+     * Returns the day each row of the grid starts with.
+     */
+    pub fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    /**
+     * Returns all cells of the grid in display order.
+     */
+    pub fn cells(&self) -> &[DayCell] {
+        &self.cells
+    }
+
+    /**
+     * Returns the cells of the grid split into rows of seven days.
+     */
+    pub fn weeks(&self) -> impl Iterator<Item = &[DayCell]> {
+        self.cells.chunks(7)
+    }
+
+    /**
+     * Function to number the rows of the grid.
+     *
+     * A row spans two weeks of any numbering unless it starts on the first
+     * day of a week; it is given the number of the week covering most of it,
+     * i.e. the week of its fourth day.
+     *
+     * Returns:
+     * - The week number of every row, top to bottom.
+     */
+    pub fn week_numbers(&self, numbering: WeekNumbering) -> Vec<u32> {
+        self.weeks()
+            .map(|week| week_number(week[3].date, numbering))
+            .collect()
+    }
+
+    /**
+     * Returns the first and last date shown in the grid, including padding days.
+     */
+    pub fn range(&self) -> (NaiveDate, NaiveDate) {
+        (self.cells[0].date, self.cells[self.cells.len() - 1].date)
+    }
+}
+
+/**
+ * Function to calculate the number of days from the previous month that
+ * should be displayed at the start of the current month's calendar grid.
+ * The calculation is based on the weekday of the first day of the current month
+ * and the day the weeks of the grid start with.
+ */
+pub fn get_last_days_of_prev_month(weekday: Weekday, week_start: Weekday) -> u32 {
+    (weekday.num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7
+}
+
+/**
+ * Function to calculate the number of days from the next month that
+ * should be displayed at the end of the current month's calendar grid.
+ * The calculation is based on the weekday of the last day of the current month
+ * and the day the weeks of the grid start with.
+ */
+pub fn get_first_days_of_next_month(weekday: Weekday, week_start: Weekday) -> u32 {
+    6 - get_last_days_of_prev_month(weekday, week_start)
+}
+
+/**
+ * Function to list the days of the week in display order.
+ *
+ * Returns:
+ * - The seven weekdays, starting with `week_start`.
+ */
+pub fn week_days(week_start: Weekday) -> [Weekday; 7] {
+    let mut days = [week_start; 7];
+    for i in 1..7 {
+        days[i] = days[i - 1].succ();
+    }
+    days
+}
+
+/**
+ * Function to determine the week number of a given date.
+ *
+ * Parameters:
+ * - `date`: The date to number.
+ * - `numbering`: The scheme to number the weeks with.
+ *
+ * Returns:
+ * - The week number, from 1 to 53. Weeks spanning the new year get the
+ *   number of the year they belong to in the scheme.
+ */
+pub fn week_number(date: NaiveDate, numbering: WeekNumbering) -> u32 {
+    match numbering {
+        WeekNumbering::Iso => date.iso_week().week(),
+        WeekNumbering::Us => {
+            let from_sunday = date.weekday().num_days_from_sunday();
+            let saturday = date
+                .checked_add_signed(Duration::days(6 - from_sunday as i64))
+                .unwrap_or(date);
+            if saturday.year() > date.year() {
+                // The week contains January 1st of the next year.
+                return 1;
+            }
+            let first = date.with_ordinal(1).expect("every year has a first day");
+            (date.ordinal0() + first.weekday().num_days_from_sunday()) / 7 + 1
+        }
+        WeekNumbering::Simple => date.ordinal0() / 7 + 1,
+    }
+}
+
+/**
+ * Function to determine the weekday of a given date.
+ *
+ * Returns:
+ * - The `Weekday` corresponding to the provided date, or `None` if the date is invalid.
+ */
+pub fn get_week_day(year: i32, month: u32, day: u32) -> Option<Weekday> {
+    NaiveDate::from_ymd_opt(year, month, day).map(|date| date.weekday())
+}
+
+/**
+ * Function to calculate the number of days in a given month of a given year.
+ *
+ * Returns:
+ * - The number of days in the specified year and month, or `None` if the month is invalid.
+ */
+pub fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some(next.signed_duration_since(first).num_days() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn padding_matches_weekday() {
+        assert_eq!(get_last_days_of_prev_month(Weekday::Sun, Weekday::Sun), 0);
+        assert_eq!(get_last_days_of_prev_month(Weekday::Sat, Weekday::Sun), 6);
+        assert_eq!(get_first_days_of_next_month(Weekday::Sun, Weekday::Sun), 6);
+        assert_eq!(get_first_days_of_next_month(Weekday::Sat, Weekday::Sun), 0);
+
+        assert_eq!(get_last_days_of_prev_month(Weekday::Sun, Weekday::Mon), 6);
+        assert_eq!(get_last_days_of_prev_month(Weekday::Mon, Weekday::Mon), 0);
+        assert_eq!(get_first_days_of_next_month(Weekday::Sun, Weekday::Mon), 0);
+        assert_eq!(get_first_days_of_next_month(Weekday::Thu, Weekday::Sat), 1);
+    }
+
+    #[test]
+    fn padding_and_week_days_agree_for_every_start() {
+        for week_start in week_days(Weekday::Mon) {
+            let days = week_days(week_start);
+            assert_eq!(days[0], week_start);
+            for (position, weekday) in days.iter().enumerate() {
+                assert_eq!(
+                    get_last_days_of_prev_month(*weekday, week_start),
+                    position as u32
+                );
+                assert_eq!(
+                    get_first_days_of_next_month(*weekday, week_start),
+                    6 - position as u32
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn iso_weeks_spanning_the_new_year() {
+        // 2026 ends on a Thursday and has 53 ISO weeks, which run into 2027.
+        assert_eq!(week_number(date(2026, 12, 31), WeekNumbering::Iso), 53);
+        assert_eq!(week_number(date(2027, 1, 3), WeekNumbering::Iso), 53);
+        assert_eq!(week_number(date(2027, 1, 4), WeekNumbering::Iso), 1);
+        // 2024 ends on a Tuesday, so its last days are week 1 of 2025.
+        assert_eq!(week_number(date(2024, 12, 30), WeekNumbering::Iso), 1);
+        assert_eq!(week_number(date(2024, 12, 29), WeekNumbering::Iso), 52);
+    }
+
+    #[test]
+    fn us_weeks_start_on_sunday_with_january_first() {
+        // January 1st 2026 is a Thursday.
+        assert_eq!(week_number(date(2026, 1, 1), WeekNumbering::Us), 1);
+        assert_eq!(week_number(date(2026, 1, 3), WeekNumbering::Us), 1);
+        assert_eq!(week_number(date(2026, 1, 4), WeekNumbering::Us), 2);
+        // The week of December 27th 2026 contains January 1st 2027.
+        assert_eq!(week_number(date(2026, 12, 26), WeekNumbering::Us), 52);
+        assert_eq!(week_number(date(2026, 12, 27), WeekNumbering::Us), 1);
+        // 2000 is a leap year starting on a Saturday: December 24th is in week 53.
+        assert_eq!(week_number(date(2000, 12, 24), WeekNumbering::Us), 53);
+        assert_eq!(week_number(date(2000, 12, 31), WeekNumbering::Us), 1);
+    }
+
+    #[test]
+    fn simple_weeks_count_from_january_first() {
+        assert_eq!(week_number(date(2026, 1, 7), WeekNumbering::Simple), 1);
+        assert_eq!(week_number(date(2026, 1, 8), WeekNumbering::Simple), 2);
+        assert_eq!(week_number(date(2026, 12, 31), WeekNumbering::Simple), 53);
+    }
+
+    #[test]
+    fn grid_rows_take_the_week_of_their_fourth_day() {
+        let grid = MonthGrid::new(2027, 1, Weekday::Mon).unwrap();
+        assert_eq!(grid.week_numbers(WeekNumbering::Iso), [53, 1, 2, 3, 4]);
+        let grid = MonthGrid::new(2027, 1, Weekday::Sun).unwrap();
+        assert_eq!(grid.week_numbers(WeekNumbering::Us), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn days_in_month_handles_leap_years() {
+        assert_eq!(days_in_month(2024, 2), Some(29));
+        assert_eq!(days_in_month(2023, 2), Some(28));
+        assert_eq!(days_in_month(1900, 2), Some(28));
+        assert_eq!(days_in_month(2000, 2), Some(29));
+        assert_eq!(days_in_month(2026, 12), Some(31));
+        assert_eq!(days_in_month(2026, 13), None);
+        assert_eq!(days_in_month(2026, 0), None);
+    }
+
+    #[test]
+    fn invalid_dates_are_rejected() {
+        assert_eq!(get_week_day(2026, 2, 30), None);
+        assert_eq!(MonthGrid::new(2026, 13, Weekday::Sun), None);
+    }
+
+    #[test]
+    fn october_2026_layout() {
+        // October 1st 2026 is a Thursday, October 31st a Saturday.
+        let grid = MonthGrid::new(2026, 10, Weekday::Sun).unwrap();
+        assert_eq!(grid.cells().len(), 35);
+        assert_eq!(grid.range(), (date(2026, 9, 27), date(2026, 10, 31)));
+        assert!(!grid.cells()[3].in_month);
+        assert_eq!(grid.cells()[3].kind, CellKind::PreviousMonth);
+        assert_eq!(grid.cells()[4].date, date(2026, 10, 1));
+        assert!(grid.cells()[4].in_month);
+        assert_eq!(grid.cells()[4].kind, CellKind::CurrentMonth);
+        assert!(grid.cells()[34].is_weekend());
+        assert!(!grid.cells()[33].is_weekend());
+
+        let monday_first = MonthGrid::new(2026, 10, Weekday::Mon).unwrap();
+        assert_eq!(monday_first.range(), (date(2026, 9, 28), date(2026, 11, 1)));
+        let saturday_first = MonthGrid::new(2026, 10, Weekday::Sat).unwrap();
+        assert_eq!(
+            saturday_first.range(),
+            (date(2026, 9, 26), date(2026, 11, 6))
+        );
+        assert_eq!(saturday_first.cells()[41].kind, CellKind::NextMonth);
+    }
+
+    #[test]
+    fn february_starting_on_sunday_has_no_padding() {
+        // February 2015 fits exactly into four rows.
+        let grid = MonthGrid::new(2015, 2, Weekday::Sun).unwrap();
+        assert_eq!(grid.weeks().count(), 4);
+        assert!(grid.cells().iter().all(|cell| cell.in_month));
+    }
+}
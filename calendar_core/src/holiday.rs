@@ -0,0 +1,650 @@
+/*!
+ * Public holidays computed from rule-based definitions.
+ *
+ * Holidays are defined per country in JSON files, one file per country named
+ * after its lowercase ISO 3166-1 code. Definitions apply to the whole country
+ * unless they name the regions (ISO 3166-2 subdivision codes without the
+ * country prefix) they are limited to. Files for a few countries are built in;
+ * files in the user's holiday directory take precedence over them.
+ *
+ * ```json
+ * {
+ *   "name": "United States",
+ *   "holidays": [
+ *     { "name": "Independence Day", "fixed": { "month": 7, "day": 4 }, "observed": "nearest_weekday" },
+ *     { "name": "Thanksgiving Day", "nth_weekday": { "month": 11, "weekday": "Thu", "nth": 4 } },
+ *     { "name": "Good Friday", "easter": { "offset": -2 } }
+ *   ]
+ * }
+ * ```
+ */
+
+use crate::grid::days_in_month;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/**
+ * The holiday files shipped with the application, by country code.
+ */
+const BUILTIN: &[(&str, &str)] = &[
+    ("de", include_str!("../holidays/de.json")),
+    ("gb", include_str!("../holidays/gb.json")),
+    ("gr", include_str!("../holidays/gr.json")),
+    ("us", include_str!("../holidays/us.json")),
+];
+
+/**
+ * Errors reported while loading holiday definitions.
+ */
+#[derive(Debug)]
+pub enum HolidayError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /** No file defines the holidays of the country. */
+    UnknownCountry(String),
+    /** A definition that can never produce a date, such as February 30th. */
+    Invalid(String),
+}
+
+impl fmt::Display for HolidayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HolidayError::Io(err) => write!(f, "I/O error: {err}"),
+            HolidayError::Format(err) => write!(f, "malformed holiday file: {err}"),
+            HolidayError::UnknownCountry(code) => write!(f, "no holidays defined for {code:?}"),
+            HolidayError::Invalid(reason) => write!(f, "invalid holiday definition: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for HolidayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HolidayError::Io(err) => Some(err),
+            HolidayError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HolidayError {
+    fn from(err: io::Error) -> HolidayError {
+        HolidayError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for HolidayError {
+    fn from(err: serde_json::Error) -> HolidayError {
+        HolidayError::Format(err)
+    }
+}
+
+/**
+ * The method used to compute the date of Easter Sunday.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Computus {
+    /** Western churches, on the Gregorian calendar. */
+    #[default]
+    Gregorian,
+    /** Eastern Orthodox churches, on the Julian calendar; dates are converted to Gregorian ones. */
+    Orthodox,
+}
+
+/**
+ * When a holiday takes place in a given year.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HolidayRule {
+    /** The same date every year. */
+    Fixed { month: u32, day: u32 },
+    /**
+     * The `nth` given weekday of a month, counted from the end of the month
+     * if negative: `nth: -1` is the last one.
+     */
+    NthWeekday {
+        month: u32,
+        weekday: Weekday,
+        nth: i32,
+    },
+    /** A number of days before (negative) or after Easter Sunday. */
+    Easter {
+        offset: i64,
+        #[serde(default)]
+        computus: Computus,
+    },
+}
+
+/**
+ * How a holiday falling on a weekend is made up for.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Observed {
+    /** Not at all. */
+    #[default]
+    None,
+    /** On the Friday before a Saturday or the Monday after a Sunday, as for US federal holidays. */
+    NearestWeekday,
+    /** On the next weekday that is not a holiday itself, as for UK bank holidays. */
+    NextWeekday,
+}
+
+/**
+ * A holiday as defined in a holiday file.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HolidayDefinition {
+    pub name: String,
+    #[serde(flatten)]
+    pub rule: HolidayRule,
+    #[serde(default)]
+    pub observed: Observed,
+    /** The regions the holiday is limited to; the whole country if empty. */
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    /** The first year the holiday takes place. */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_year: Option<i32>,
+    /** The last year the holiday takes place. */
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until_year: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct HolidayFile {
+    name: String,
+    holidays: Vec<HolidayDefinition>,
+}
+
+/**
+ * A holiday on a specific date.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+    /** Whether this is the weekday a holiday falling on a weekend is observed on. */
+    pub observed: bool,
+}
+
+/**
+ * The holidays of a country, or of a region of it.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    /** The name of the country as given in its holiday file. */
+    pub name: String,
+    definitions: Vec<HolidayDefinition>,
+}
+
+impl HolidayCalendar {
+    /**
+     * Returns the directory holiday files are looked up in before the built-in
+     * ones, i.e. `$XDG_CONFIG_HOME/idk2/holidays` on Linux.
+     */
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("idk2").join("holidays"))
+    }
+
+    /**
+     * Function to load the holidays of a country or region.
+     *
+     * Parameters:
+     * - `code`: A country code such as `"US"`, or a country and region code
+     *   such as `"DE-BY"`.
+     * - `dir`: The directory to look for `<country>.json` in before falling
+     *   back to the built-in files.
+     *
+     * Returns:
+     * - The holidays of the country, limited to those of the region if given.
+     */
+    pub fn load(code: &str, dir: Option<&Path>) -> Result<HolidayCalendar, HolidayError> {
+        let (country, region) = match code.split_once('-') {
+            Some((country, region)) => (country, Some(region)),
+            None => (code, None),
+        };
+        let country = country.to_ascii_lowercase();
+
+        if let Some(dir) = dir {
+            match fs::read_to_string(dir.join(format!("{country}.json"))) {
+                Ok(input) => return HolidayCalendar::from_json(&input, region),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let (_, input) = BUILTIN
+            .iter()
+            .find(|(builtin, _)| *builtin == country)
+            .ok_or_else(|| HolidayError::UnknownCountry(code.to_string()))?;
+        HolidayCalendar::from_json(input, region)
+    }
+
+    /**
+     * Function to parse a holiday file.
+     *
+     * Parameters:
+     * - `input`: The content of the file.
+     * - `region`: The region to keep the regional holidays of; regional
+     *   holidays are left out if `None`.
+     *
+     * Returns:
+     * - The calendar, or an error if the file is malformed or defines
+     *   impossible dates.
+     */
+    pub fn from_json(input: &str, region: Option<&str>) -> Result<HolidayCalendar, HolidayError> {
+        let file: HolidayFile = serde_json::from_str(input)?;
+        for definition in &file.holidays {
+            definition.rule.validate().map_err(|reason| {
+                HolidayError::Invalid(format!("{}: {}", definition.name, reason))
+            })?;
+        }
+        let definitions = file
+            .holidays
+            .into_iter()
+            .filter(|definition| {
+                definition.regions.is_empty()
+                    || region.is_some_and(|region| {
+                        definition
+                            .regions
+                            .iter()
+                            .any(|candidate| candidate.eq_ignore_ascii_case(region))
+                    })
+            })
+            .collect();
+        Ok(HolidayCalendar {
+            name: file.name,
+            definitions,
+        })
+    }
+
+    /**
+     * Function to list the holidays of a year.
+     *
+     * Holidays falling on a weekend are listed on their date, followed by the
+     * weekday they are observed on if their definition asks for it. The
+     * observed day of a holiday early in January can fall into the previous
+     * year; it is listed with the year of the holiday.
+     *
+     * Returns:
+     * - The holidays ordered by date.
+     */
+    pub fn holidays_in_year(&self, year: i32) -> Vec<Holiday> {
+        let active: Vec<(&HolidayDefinition, NaiveDate)> = self
+            .definitions
+            .iter()
+            .filter(|definition| {
+                definition.from_year.is_none_or(|from| year >= from)
+                    && definition.until_year.is_none_or(|until| year <= until)
+            })
+            .filter_map(|definition| Some((definition, definition.rule.date(year)?)))
+            .collect();
+
+        let mut taken: BTreeSet<NaiveDate> = active.iter().map(|(_, date)| *date).collect();
+        let mut holidays: Vec<Holiday> = Vec::new();
+        for (definition, date) in active {
+            holidays.push(Holiday {
+                date,
+                name: definition.name.clone(),
+                observed: false,
+            });
+            if let Some(observed) = observed_date(definition.observed, date, &taken) {
+                taken.insert(observed);
+                holidays.push(Holiday {
+                    date: observed,
+                    name: definition.name.clone(),
+                    observed: true,
+                });
+            }
+        }
+        holidays.sort_by_key(|holiday| (holiday.date, holiday.observed));
+        holidays
+    }
+
+    /**
+     * Function to list the holidays between two dates.
+     *
+     * Parameters:
+     * - `start`: The first date, e.g. the first cell of a month grid.
+     * - `end`: The last date (inclusive).
+     *
+     * Returns:
+     * - The holidays grouped by date.
+     */
+    pub fn holidays_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> BTreeMap<NaiveDate, Vec<Holiday>> {
+        let mut by_date: BTreeMap<NaiveDate, Vec<Holiday>> = BTreeMap::new();
+        // Observed days can move into the neighbouring years.
+        for year in start.year() - 1..=end.year() + 1 {
+            for holiday in self.holidays_in_year(year) {
+                if (start..=end).contains(&holiday.date) {
+                    by_date.entry(holiday.date).or_default().push(holiday);
+                }
+            }
+        }
+        by_date
+    }
+}
+
+impl HolidayRule {
+    /**
+     * Returns the date of the holiday in `year`, or `None` if it does not
+     * take place that year, like February 29th outside leap years.
+     */
+    pub fn date(&self, year: i32) -> Option<NaiveDate> {
+        match *self {
+            HolidayRule::Fixed { month, day } => NaiveDate::from_ymd_opt(year, month, day),
+            HolidayRule::NthWeekday {
+                month,
+                weekday,
+                nth,
+            } => {
+                if nth > 0 {
+                    NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth as u8)
+                } else {
+                    let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month)?)?;
+                    let back = (last.weekday().num_days_from_monday() + 7
+                        - weekday.num_days_from_monday())
+                        % 7;
+                    let date = last
+                        .checked_sub_signed(Duration::days(back as i64 + 7 * (-nth as i64 - 1)))?;
+                    (date.month() == month).then_some(date)
+                }
+            }
+            HolidayRule::Easter { offset, computus } => {
+                easter(year, computus)?.checked_add_signed(Duration::days(offset))
+            }
+        }
+    }
+
+    /**
+     * Checks that the rule produces a date in at least some years.
+     */
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            HolidayRule::Fixed { month, day } => {
+                // 2000 is a leap year, so February 29th is accepted.
+                match days_in_month(2000, month) {
+                    Some(days) if (1..=days).contains(&day) => Ok(()),
+                    _ => Err(format!("no day {day} in month {month}")),
+                }
+            }
+            HolidayRule::NthWeekday { month, nth, .. } => {
+                if !(1..=12).contains(&month) {
+                    Err(format!("no month {month}"))
+                } else if nth == 0 || !(-5..=5).contains(&nth) {
+                    Err(format!("no weekday number {nth} in a month"))
+                } else {
+                    Ok(())
+                }
+            }
+            HolidayRule::Easter { .. } => Ok(()),
+        }
+    }
+}
+
+/**
+ * Function to find the day a holiday falling on a weekend is observed on.
+ *
+ * Parameters:
+ * - `observed`: The rule of the holiday.
+ * - `date`: The date of the holiday.
+ * - `taken`: The dates that are holidays already.
+ *
+ * Returns:
+ * - The observed day, or `None` if the holiday is not shifted.
+ */
+fn observed_date(
+    observed: Observed,
+    date: NaiveDate,
+    taken: &BTreeSet<NaiveDate>,
+) -> Option<NaiveDate> {
+    let weekday = date.weekday();
+    if !matches!(weekday, Weekday::Sat | Weekday::Sun) {
+        return None;
+    }
+    match observed {
+        Observed::None => None,
+        Observed::NearestWeekday if weekday == Weekday::Sat => date.pred_opt(),
+        Observed::NearestWeekday => date.succ_opt(),
+        Observed::NextWeekday => {
+            let mut day = date.succ_opt()?;
+            while matches!(day.weekday(), Weekday::Sat | Weekday::Sun) || taken.contains(&day) {
+                day = day.succ_opt()?;
+            }
+            Some(day)
+        }
+    }
+}
+
+/**
+ * Function to compute the date of Easter Sunday.
+ *
+ * Parameters:
+ * - `year`: The year, at least 1583 for the Gregorian computus.
+ * - `computus`: The method to use.
+ *
+ * Returns:
+ * - The date of Easter Sunday on the Gregorian calendar, or `None` if the
+ *   computus does not apply to `year`.
+ */
+pub fn easter(year: i32, computus: Computus) -> Option<NaiveDate> {
+    match computus {
+        Computus::Gregorian => {
+            // Anonymous Gregorian algorithm (Meeus/Jones/Butcher).
+            if year < 1583 {
+                return None;
+            }
+            let a = year % 19;
+            let b = year / 100;
+            let c = year % 100;
+            let d = b / 4;
+            let e = b % 4;
+            let f = (b + 8) / 25;
+            let g = (b - f + 1) / 3;
+            let h = (19 * a + b - d - g + 15) % 30;
+            let i = c / 4;
+            let k = c % 4;
+            let l = (32 + 2 * e + 2 * i - h - k) % 7;
+            let m = (a + 11 * h + 22 * l) / 451;
+            let month = (h + l - 7 * m + 114) / 31;
+            let day = (h + l - 7 * m + 114) % 31 + 1;
+            NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+        }
+        Computus::Orthodox => {
+            // Meeus' Julian algorithm, shifted by the difference between the
+            // calendars in March of the year.
+            if year < 1583 {
+                return None;
+            }
+            let a = year % 4;
+            let b = year % 7;
+            let c = year % 19;
+            let d = (19 * c + 15) % 30;
+            let e = (2 * a + 4 * b - d + 34) % 7;
+            let month = (d + e + 114) / 31;
+            let day = (d + e + 114) % 31 + 1;
+            let julian = NaiveDate::from_ymd_opt(year, month as u32, day as u32)?;
+            let difference = year / 100 - year / 400 - 2;
+            julian.checked_add_signed(Duration::days(difference as i64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /**
+     * Returns date, name and observed flag of the holidays of a year.
+     */
+    fn holidays(calendar: &HolidayCalendar, year: i32) -> Vec<(NaiveDate, String, bool)> {
+        calendar
+            .holidays_in_year(year)
+            .into_iter()
+            .map(|holiday| (holiday.date, holiday.name, holiday.observed))
+            .collect()
+    }
+
+    #[test]
+    fn easter_dates() {
+        assert_eq!(easter(2024, Computus::Gregorian), Some(date(2024, 3, 31)));
+        assert_eq!(easter(2025, Computus::Gregorian), Some(date(2025, 4, 20)));
+        assert_eq!(easter(2026, Computus::Gregorian), Some(date(2026, 4, 5)));
+        assert_eq!(easter(2038, Computus::Gregorian), Some(date(2038, 4, 25)));
+        assert_eq!(easter(2024, Computus::Orthodox), Some(date(2024, 5, 5)));
+        assert_eq!(easter(2025, Computus::Orthodox), Some(date(2025, 4, 20)));
+        assert_eq!(easter(2026, Computus::Orthodox), Some(date(2026, 4, 12)));
+        assert_eq!(easter(1500, Computus::Gregorian), None);
+    }
+
+    #[test]
+    fn nth_weekday_rules() {
+        let rule = |month, weekday, nth| HolidayRule::NthWeekday {
+            month,
+            weekday,
+            nth,
+        };
+        assert_eq!(
+            rule(11, Weekday::Thu, 4).date(2026),
+            Some(date(2026, 11, 26))
+        );
+        assert_eq!(
+            rule(5, Weekday::Mon, -1).date(2026),
+            Some(date(2026, 5, 25))
+        );
+        assert_eq!(
+            rule(8, Weekday::Mon, -1).date(2026),
+            Some(date(2026, 8, 31))
+        );
+        assert_eq!(
+            rule(8, Weekday::Mon, -2).date(2026),
+            Some(date(2026, 8, 24))
+        );
+        // Only some months have a fifth Friday.
+        assert_eq!(
+            rule(10, Weekday::Fri, 5).date(2026),
+            Some(date(2026, 10, 30))
+        );
+        assert_eq!(rule(11, Weekday::Fri, 5).date(2026), None);
+        assert_eq!(rule(11, Weekday::Fri, -5).date(2026), None);
+    }
+
+    #[test]
+    fn us_holidays_are_observed_on_the_nearest_weekday() {
+        let calendar = HolidayCalendar::load("US", None).unwrap();
+        let in_2027 = holidays(&calendar, 2027);
+        // Independence Day 2027 is a Sunday, Christmas 2027 a Saturday.
+        assert!(in_2027.contains(&(date(2027, 7, 4), "Independence Day".to_string(), false)));
+        assert!(in_2027.contains(&(date(2027, 7, 5), "Independence Day".to_string(), true)));
+        assert!(in_2027.contains(&(date(2027, 12, 24), "Christmas Day".to_string(), true)));
+        // New Year's Day 2028 is a Saturday and observed on December 31st.
+        let grid = calendar.holidays_between(date(2027, 12, 26), date(2028, 1, 8));
+        assert_eq!(grid[&date(2027, 12, 31)][0].name, "New Year's Day");
+        assert!(grid[&date(2027, 12, 31)][0].observed);
+        // Juneteenth only exists since 2021.
+        assert!(holidays(&calendar, 2020)
+            .iter()
+            .all(|(_, name, _)| name != "Juneteenth"));
+    }
+
+    #[test]
+    fn uk_substitute_days_skip_other_holidays() {
+        let calendar = HolidayCalendar::load("gb", None).unwrap();
+        // Christmas 2022 is a Sunday and Boxing Day the Monday after it.
+        let christmas: Vec<_> = holidays(&calendar, 2022)
+            .into_iter()
+            .filter(|(date, _, _)| date.month() == 12)
+            .collect();
+        assert_eq!(
+            christmas,
+            [
+                (date(2022, 12, 25), "Christmas Day".to_string(), false),
+                (date(2022, 12, 26), "Boxing Day".to_string(), false),
+                (date(2022, 12, 27), "Christmas Day".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn regional_holidays_need_the_region() {
+        let corpus_christi = date(2026, 6, 4);
+        let national = HolidayCalendar::load("DE", None).unwrap();
+        let bavaria = HolidayCalendar::load("DE-BY", None).unwrap();
+        let berlin = HolidayCalendar::load("de-be", None).unwrap();
+        let between =
+            |calendar: &HolidayCalendar| calendar.holidays_between(corpus_christi, corpus_christi);
+        assert!(between(&national).is_empty());
+        assert_eq!(between(&bavaria)[&corpus_christi][0].name, "Fronleichnam");
+        assert!(between(&berlin).is_empty());
+        assert_eq!(holidays(&national, 2026).len(), 9);
+    }
+
+    #[test]
+    fn orthodox_easter_drives_greek_holidays() {
+        let calendar = HolidayCalendar::load("GR", None).unwrap();
+        let clean_monday = calendar.holidays_between(date(2026, 2, 23), date(2026, 2, 23));
+        assert_eq!(clean_monday.len(), 1);
+    }
+
+    #[test]
+    fn user_files_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("us.json"),
+            r#"{"name": "Company", "holidays": [
+                {"name": "Founders Day", "fixed": {"month": 3, "day": 14}}
+            ]}"#,
+        )
+        .unwrap();
+        let calendar = HolidayCalendar::load("US", Some(dir.path())).unwrap();
+        assert_eq!(calendar.name, "Company");
+        assert_eq!(holidays(&calendar, 2026).len(), 1);
+
+        let fallback = HolidayCalendar::load("GB", Some(dir.path())).unwrap();
+        assert_eq!(fallback.name, "England and Wales");
+    }
+
+    #[test]
+    fn invalid_definitions_are_rejected() {
+        assert!(matches!(
+            HolidayCalendar::load("XX", None),
+            Err(HolidayError::UnknownCountry(_))
+        ));
+        let invalid = |holiday: &str| {
+            let input = format!(r#"{{"name": "Test", "holidays": [{holiday}]}}"#);
+            HolidayCalendar::from_json(&input, None)
+        };
+        assert!(matches!(
+            invalid(r#"{"name": "Nope", "fixed": {"month": 2, "day": 30}}"#),
+            Err(HolidayError::Invalid(_))
+        ));
+        assert!(matches!(
+            invalid(r#"{"name": "Nope", "nth_weekday": {"month": 5, "weekday": "Mon", "nth": 0}}"#),
+            Err(HolidayError::Invalid(_))
+        ));
+        assert!(matches!(
+            invalid(r#"{"name": "Nope", "sometimes": {}}"#),
+            Err(HolidayError::Format(_))
+        ));
+        // February 29th is only a holiday in leap years.
+        let leap = invalid(r#"{"name": "Leap Day", "fixed": {"month": 2, "day": 29}}"#).unwrap();
+        assert_eq!(holidays(&leap, 2028).len(), 1);
+        assert!(holidays(&leap, 2027).is_empty());
+    }
+}
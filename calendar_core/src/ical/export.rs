@@ -0,0 +1,730 @@
+use crate::event::{Event, EventId, EventStore, EventTime};
+use crate::navigation::MonthCursor;
+use crate::reminder::Reminder;
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use std::collections::BTreeSet;
+
+/**
+ * Maximum length of a content line in octets, excluding the line break.
+ */
+const MAX_LINE_LENGTH: usize = 75;
+
+/**
+ * Number of years after its start an event's time zone is described for at
+ * most. Clients keep using the last offset for later occurrences of longer
+ * series.
+ */
+const MAX_TIME_ZONE_YEARS: i32 = 50;
+
+/**
+ * Which events to write to the file.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportRange {
+    /** Events touching the given month. */
+    Month(MonthCursor),
+    /** Events touching at least one date between the two dates (inclusive). */
+    Dates(NaiveDate, NaiveDate),
+    /** Every event in the store. */
+    All,
+}
+
+/**
+ * Function to write the events of the store as an iCalendar object.
+ *
+ * Events keep the `UID` assigned by the store, so exporting the same event
+ * twice yields the same identifier and other clients update rather than
+ * duplicate it. Every time zone used by an exported event is described by a
+ * `VTIMEZONE` component covering the years of the exported events.
+ *
+ * Parameters:
+ * - `store`: The events to export.
+ * - `range`: Which of the events to export.
+ * - `stamp`: The creation time written to `DTSTAMP`.
+ *
+ * Returns:
+ * - The content of the `.ics` file, with CRLF line endings.
+ */
+pub fn export(store: &EventStore, range: ExportRange, stamp: DateTime<Utc>) -> String {
+    let events: Vec<(EventId, &Event)> = match range {
+        ExportRange::Month(cursor) => {
+            let first = cursor.first_day();
+            let last = cursor.next_month().first_day().pred_opt().unwrap_or(first);
+            store.events_between(first, last)
+        }
+        ExportRange::Dates(start, end) => store.events_between(start, end),
+        ExportRange::All => store.iter().collect(),
+    };
+
+    let mut writer = Writer::default();
+    writer.line("BEGIN:VCALENDAR");
+    writer.line("VERSION:2.0");
+    writer.line("PRODID:-//idk2//calendar_core//EN");
+    writer.line("CALSCALE:GREGORIAN");
+
+    let zones: BTreeSet<&str> = events
+        .iter()
+        .filter(|(_, event)| !event.time.is_all_day())
+        .filter_map(|(_, event)| event.time_zone.as_deref())
+        .collect();
+    for name in zones {
+        if let Ok(zone) = name.parse::<Tz>() {
+            let mut years: Vec<(i32, i32)> = events
+                .iter()
+                .filter(|(_, event)| event.time_zone.as_deref() == Some(name))
+                .map(|(_, event)| (event.time.start_date().year(), last_year(event, stamp)))
+                .collect();
+            // Years in which none of the events occur are left out.
+            years.sort();
+            years.dedup_by(|next, kept| {
+                let overlaps = next.0 <= kept.1 + 1;
+                if overlaps {
+                    kept.1 = kept.1.max(next.1);
+                }
+                overlaps
+            });
+            write_time_zone(&mut writer, zone, &years);
+        }
+    }
+
+    for (id, event) in events {
+        write_event(&mut writer, id, event, stamp);
+    }
+
+    writer.line("END:VCALENDAR");
+    writer.output
+}
+
+/**
+ * Writes a single `VEVENT` component.
+ */
+fn write_event(writer: &mut Writer, id: EventId, event: &Event, stamp: DateTime<Utc>) {
+    writer.line("BEGIN:VEVENT");
+    let uid = event
+        .uid
+        .clone()
+        .unwrap_or_else(|| format!("{}@idk2", id.0));
+    writer.line(&format!("UID:{}", escape_text(&uid)));
+    writer.line(&format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+
+    match event.time {
+        EventTime::AllDay { start, end } => {
+            // The end date is exclusive in iCalendar.
+            let end = end.succ_opt().unwrap_or(end);
+            writer.line(&format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+            writer.line(&format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        }
+        EventTime::Timed { start, end } => {
+            let zone = zone_param(event);
+            writer.line(&format!(
+                "DTSTART{}:{}",
+                zone,
+                start.format("%Y%m%dT%H%M%S")
+            ));
+            writer.line(&format!("DTEND{}:{}", zone, end.format("%Y%m%dT%H%M%S")));
+        }
+    }
+
+    if let Some(recurrence) = &event.recurrence {
+        let all_day = event.time.is_all_day();
+        if let Some(rule) = &recurrence.rule {
+            writer.line(&format!("RRULE:{}", rule.to_ical(all_day)));
+        }
+        for (name, dates) in [
+            ("EXDATE", &recurrence.exdates),
+            ("RDATE", &recurrence.rdates),
+        ] {
+            if dates.is_empty() {
+                continue;
+            }
+            let (params, format) = if all_day {
+                (";VALUE=DATE".to_string(), "%Y%m%d")
+            } else {
+                (zone_param(event), "%Y%m%dT%H%M%S")
+            };
+            let values: Vec<String> = dates
+                .iter()
+                .map(|date| date.format(format).to_string())
+                .collect();
+            writer.line(&format!("{}{}:{}", name, params, values.join(",")));
+        }
+    }
+
+    writer.line(&format!("SUMMARY:{}", escape_text(&event.title)));
+    if let Some(location) = &event.location {
+        writer.line(&format!("LOCATION:{}", escape_text(location)));
+    }
+    if let Some(notes) = &event.notes {
+        writer.line(&format!("DESCRIPTION:{}", escape_text(notes)));
+    }
+    for reminder in &event.reminders {
+        write_alarm(writer, event, reminder);
+    }
+    writer.line("END:VEVENT");
+}
+
+/**
+ * Writes a `VALARM` component displaying the title of the event.
+ *
+ * Fixed times are written in UTC for events with a time zone, and as
+ * floating times like the event itself otherwise.
+ */
+fn write_alarm(writer: &mut Writer, event: &Event, reminder: &Reminder) {
+    writer.line("BEGIN:VALARM");
+    writer.line("ACTION:DISPLAY");
+    writer.line(&format!("DESCRIPTION:{}", escape_text(&event.title)));
+    match *reminder {
+        Reminder::Relative(offset) => {
+            writer.line(&format!("TRIGGER:{}", format_duration(offset)));
+        }
+        Reminder::Absolute(time) => {
+            let zone = event
+                .time_zone
+                .as_deref()
+                .and_then(|name| name.parse::<Tz>().ok());
+            let utc = zone.and_then(|zone| zone.from_local_datetime(&time).earliest());
+            let value = match utc {
+                Some(time) => time.naive_utc().format("%Y%m%dT%H%M%SZ"),
+                None => time.format("%Y%m%dT%H%M%S"),
+            };
+            writer.line(&format!("TRIGGER;VALUE=DATE-TIME:{}", value));
+        }
+    }
+    writer.line("END:VALARM");
+}
+
+/**
+ * Returns the `TZID` parameter for the times of a timed event, or nothing for floating times.
+ */
+fn zone_param(event: &Event) -> String {
+    event
+        .time_zone
+        .as_deref()
+        .filter(|name| name.parse::<Tz>().is_ok())
+        .map(|name| format!(";TZID={}", name))
+        .unwrap_or_default()
+}
+
+/**
+ * Returns the last year the time zone of an event needs to be described for.
+ * Recurring events without an end are covered until the year after `stamp`,
+ * and no event is covered for more than `MAX_TIME_ZONE_YEARS` years.
+ */
+fn last_year(event: &Event, stamp: DateTime<Utc>) -> i32 {
+    let end = event.time.end_date().year();
+    let limit = event
+        .time
+        .start_date()
+        .year()
+        .saturating_add(MAX_TIME_ZONE_YEARS);
+    let last = match &event.recurrence {
+        None => end,
+        Some(recurrence) => {
+            let rule_end = match &recurrence.rule {
+                Some(rule) if rule.count.is_some() => {
+                    // Counted series end with their last occurrence, which is
+                    // only looked for up to the limit.
+                    let dtstart = event.time.start();
+                    let horizon = NaiveDate::from_ymd_opt(limit.saturating_add(1), 1, 1)
+                        .map_or(NaiveDateTime::MAX, |date| date.and_time(NaiveTime::MIN));
+                    let last = recurrence
+                        .iter_between(dtstart, dtstart, horizon)
+                        .last()
+                        .unwrap_or(dtstart);
+                    event.time.shifted(last - dtstart).end_date().year()
+                }
+                Some(rule) => match rule.until {
+                    Some(until) => until.year(),
+                    None => stamp.year() + 1,
+                },
+                None => end,
+            };
+            let rdates = recurrence.rdates.iter().map(|date| date.year());
+            rdates.chain([end, rule_end]).max().unwrap_or(end)
+        }
+    };
+    last.min(limit)
+}
+
+/**
+ * Writes a `VTIMEZONE` component listing every offset change of `zone`
+ * during the given ranges of years, which are inclusive and ascending.
+ */
+fn write_time_zone(writer: &mut Writer, zone: Tz, years: &[(i32, i32)]) {
+    writer.line("BEGIN:VTIMEZONE");
+    writer.line(&format!("TZID:{}", zone.name()));
+
+    let mut previous = None;
+    for &(first_year, last_year) in years {
+        let Some(start) = NaiveDate::from_ymd_opt(first_year, 1, 1) else {
+            continue;
+        };
+        let end = NaiveDate::from_ymd_opt(last_year + 1, 1, 1).unwrap_or(start);
+
+        // The observance in effect at the start of the period, unless it
+        // continues from the previous one.
+        let mut instant = start.and_time(NaiveTime::MIN).and_utc();
+        let initial = zone.offset_from_utc_datetime(&instant.naive_utc());
+        let from = previous.unwrap_or(initial);
+        if previous.is_none() || fixed(&from) != fixed(&initial) {
+            write_observance(writer, instant.naive_utc() + fixed(&from), &from, &initial);
+        }
+
+        // Offsets change at most once a day, so stepping by days finds every
+        // change before narrowing it down.
+        let mut current = initial;
+        while instant.date_naive() < end {
+            let next = instant + Duration::days(1);
+            let offset = zone.offset_from_utc_datetime(&next.naive_utc());
+            if fixed(&offset) != fixed(&current) {
+                let change = transition(zone, instant, next, &current);
+                write_observance(
+                    writer,
+                    change.naive_utc() + fixed(&current),
+                    &current,
+                    &offset,
+                );
+                current = offset;
+            }
+            instant = next;
+        }
+        previous = Some(current);
+    }
+
+    writer.line("END:VTIMEZONE");
+}
+
+/**
+ * Returns the first second after `low` and up to `high` at which `zone` no
+ * longer has the offset `before`.
+ */
+fn transition(
+    zone: Tz,
+    mut low: DateTime<Utc>,
+    mut high: DateTime<Utc>,
+    before: &<Tz as TimeZone>::Offset,
+) -> DateTime<Utc> {
+    while high - low > Duration::seconds(1) {
+        let middle = low + Duration::seconds((high - low).num_seconds() / 2);
+        if fixed(&zone.offset_from_utc_datetime(&middle.naive_utc())) == fixed(before) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    high
+}
+
+/**
+ * Writes a `STANDARD` or `DAYLIGHT` observance starting at the local time `start`.
+ */
+fn write_observance<O>(writer: &mut Writer, start: NaiveDateTime, from: &O, to: &O)
+where
+    O: OffsetComponents + OffsetName + Offset,
+{
+    let kind = if to.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    writer.line(&format!("BEGIN:{}", kind));
+    writer.line(&format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+    writer.line(&format!("TZOFFSETFROM:{}", format_offset(fixed(from))));
+    writer.line(&format!("TZOFFSETTO:{}", format_offset(fixed(to))));
+    // Zones without an abbreviation report their numeric offset instead.
+    let name = to.abbreviation();
+    if !name.starts_with(['+', '-']) {
+        writer.line(&format!("TZNAME:{}", escape_text(name)));
+    }
+    writer.line(&format!("END:{}", kind));
+}
+
+fn fixed<O: Offset>(offset: &O) -> Duration {
+    Duration::seconds(offset.fix().local_minus_utc() as i64)
+}
+
+/**
+ * Formats a UTC offset as `+hhmm`, or `+hhmmss` for offsets with seconds.
+ */
+fn format_offset(offset: Duration) -> String {
+    let sign = if offset < Duration::zero() { '-' } else { '+' };
+    let seconds = offset.num_seconds().abs();
+    let (hours, minutes, rest) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if rest == 0 {
+        format!("{}{:02}{:02}", sign, hours, minutes)
+    } else {
+        format!("{}{:02}{:02}{:02}", sign, hours, minutes, rest)
+    }
+}
+
+/**
+ * Formats a DURATION value such as `-PT15M` or `P1DT2H`.
+ */
+fn format_duration(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let seconds = duration.num_seconds().abs();
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    let mut formatted = format!("{}P", sign);
+    if days > 0 {
+        formatted.push_str(&format!("{}D", days));
+    }
+    if rest > 0 || days == 0 {
+        formatted.push('T');
+        let parts = [(rest / 3600, 'H'), (rest / 60 % 60, 'M'), (rest % 60, 'S')];
+        for (amount, unit) in parts {
+            if amount > 0 {
+                formatted.push_str(&format!("{}{}", amount, unit));
+            }
+        }
+        if rest == 0 {
+            formatted.push_str("0S");
+        }
+    }
+    formatted
+}
+
+/**
+ * Escapes the characters with a special meaning in TEXT values.
+ */
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/**
+ * Accumulates content lines, folding them at `MAX_LINE_LENGTH` octets.
+ */
+#[derive(Default)]
+struct Writer {
+    output: String,
+}
+
+impl Writer {
+    fn line(&mut self, line: &str) {
+        let mut length = 0;
+        for c in line.chars() {
+            // Continuation lines start with a space that counts towards the limit.
+            if length + c.len_utf8() > MAX_LINE_LENGTH {
+                self.output.push_str("\r\n ");
+                length = 1;
+            }
+            self.output.push(c);
+            length += c.len_utf8();
+        }
+        self.output.push_str("\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ical::{import, parse_events};
+    use crate::recurrence::Recurrence;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn stamp() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap()
+    }
+
+    fn sample_store() -> EventStore {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let mut standup = Event::new(
+            "Standup; daily, short",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        standup.time_zone = Some("Europe/Berlin".to_string());
+        standup.notes = Some("Agenda:\nblockers".to_string());
+        store.insert(standup);
+        store.insert(Event::new(
+            "Trip",
+            EventTime::all_day(date(2026, 10, 30), date(2026, 11, 2)).unwrap(),
+        ));
+        store.insert(Event::new(
+            "Next year",
+            EventTime::all_day(date(2027, 1, 5), date(2027, 1, 5)).unwrap(),
+        ));
+        store
+    }
+
+    #[test]
+    fn exported_events_import_back_unchanged() {
+        let store = sample_store();
+        let output = export(&store, ExportRange::All, stamp());
+        let events: Vec<Event> = parse_events(&output, &Utc)
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+        let original: Vec<Event> = store.iter().map(|(_, event)| event.clone()).collect();
+        assert_eq!(events, original);
+    }
+
+    #[test]
+    fn zoned_events_round_trip_through_the_local_time() {
+        let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\n\
+                     UID:call@example.com\r\nSUMMARY:Call\r\n\
+                     DTSTART;TZID=America/New_York:20261016T100000\r\n\
+                     DTEND;TZID=America/New_York:20261016T110000\r\n\
+                     END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let mut store = EventStore::new();
+        store.set_zone(Some(chrono_tz::Europe::Berlin));
+        import(&mut store, input, &chrono_tz::Europe::Berlin).unwrap();
+
+        let day = date(2026, 10, 16);
+        let occurrences = store.events_on(day);
+        assert_eq!(
+            occurrences[0].time,
+            EventTime::timed(at(day, 16, 0), at(day, 17, 0)).unwrap()
+        );
+
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains("DTSTART;TZID=America/New_York:20261016T100000\r\n"));
+        assert!(output.contains("DTEND;TZID=America/New_York:20261016T110000\r\n"));
+    }
+
+    #[test]
+    fn recurrences_import_back_unchanged() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 5);
+        let mut standup = Event::new(
+            "Standup",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        standup.time_zone = Some("Europe/Berlin".to_string());
+        standup.recurrence = Some(Recurrence {
+            rule: Some(
+                "FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20271231T235959"
+                    .parse()
+                    .unwrap(),
+            ),
+            exdates: vec![at(date(2026, 10, 7), 9, 0), at(date(2026, 10, 12), 9, 0)],
+            rdates: vec![at(date(2026, 10, 10), 9, 0)],
+        });
+        store.insert(standup);
+        let mut birthday = Event::new("Birthday", EventTime::all_day(day, day).unwrap());
+        birthday.recurrence = Some(Recurrence {
+            rule: Some("FREQ=YEARLY;UNTIL=20300101".parse().unwrap()),
+            exdates: vec![at(date(2027, 10, 5), 0, 0)],
+            rdates: Vec::new(),
+        });
+        store.insert(birthday);
+
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains("RRULE:FREQ=YEARLY;UNTIL=20300101\r\n"));
+        assert!(output.contains("EXDATE;VALUE=DATE:20271005\r\n"));
+        assert!(output.contains("EXDATE;TZID=Europe/Berlin:20261007T090000,20261012T090000\r\n"));
+        // The time zone is described up to the end of the series.
+        assert!(output.contains("BEGIN:DAYLIGHT\r\nDTSTART:20270328T020000\r\n"));
+
+        let events: Vec<Event> = parse_events(&output, &Utc)
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let original: Vec<Event> = store.iter().map(|(_, event)| event.clone()).collect();
+        assert_eq!(events, original);
+    }
+
+    #[test]
+    fn ranges_select_overlapping_events() {
+        let store = sample_store();
+        let count = |range| {
+            export(&store, range, stamp())
+                .matches("BEGIN:VEVENT")
+                .count()
+        };
+
+        let october = MonthCursor::new(2026, 10).unwrap();
+        assert_eq!(count(ExportRange::Month(october)), 2);
+        assert_eq!(count(ExportRange::Month(october.next_month())), 1);
+        assert_eq!(
+            count(ExportRange::Dates(date(2026, 12, 1), date(2027, 1, 31))),
+            1
+        );
+        assert_eq!(count(ExportRange::All), 3);
+    }
+
+    #[test]
+    fn uids_are_stable_across_exports() {
+        let store = sample_store();
+        let uids = |output: String| -> Vec<String> {
+            output
+                .lines()
+                .filter(|line| line.starts_with("UID:"))
+                .map(str::to_string)
+                .collect()
+        };
+        let first = uids(export(&store, ExportRange::All, stamp()));
+        let second = uids(export(&store, ExportRange::All, Utc::now()));
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn time_zones_are_described() {
+        let output = export(&sample_store(), ExportRange::All, stamp());
+        assert!(output.contains("DTSTART;TZID=Europe/Berlin:20261016T090000\r\n"));
+        assert_eq!(output.matches("BEGIN:VTIMEZONE").count(), 1);
+        // Summer time ends on the last Sunday of October at 03:00 local time.
+        assert!(output.contains(
+            "BEGIN:STANDARD\r\nDTSTART:20261025T030000\r\nTZOFFSETFROM:+0200\r\nTZOFFSETTO:+0100\r\nTZNAME:CET\r\n"
+        ));
+        assert!(output.contains(
+            "BEGIN:DAYLIGHT\r\nDTSTART:20260329T020000\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\nTZNAME:CEST\r\n"
+        ));
+    }
+
+    #[test]
+    fn time_zones_are_described_for_the_years_with_events() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 5);
+        let mut standup = Event::new(
+            "Standup",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        standup.time_zone = Some("Europe/Berlin".to_string());
+        standup.recurrence = Some(Recurrence::from_rule(
+            "FREQ=WEEKLY;COUNT=3".parse().unwrap(),
+        ));
+        store.insert(standup);
+        let later = date(2030, 6, 1);
+        let mut meeting = Event::new(
+            "Meeting",
+            EventTime::timed(at(later, 9, 0), at(later, 10, 0)).unwrap(),
+        );
+        meeting.time_zone = Some("Europe/Berlin".to_string());
+        store.insert(meeting);
+
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains("DTSTART:20261025T030000\r\n"));
+        assert!(output.contains("DTSTART:20300331T020000\r\n"));
+        // The counted series ends in 2026 and nothing happens until 2030.
+        assert!(!output.contains("DTSTART:2027"));
+        assert!(!output.contains("DTSTART:2029"));
+
+        let mut store = EventStore::new();
+        let mut forever = Event::new(
+            "Forever",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        forever.time_zone = Some("Europe/Berlin".to_string());
+        forever.recurrence = Some(Recurrence::from_rule(
+            "FREQ=DAILY;UNTIL=99991231T235959".parse().unwrap(),
+        ));
+        store.insert(forever);
+        let mut counted = Event::new(
+            "Counted",
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap(),
+        );
+        counted.time_zone = Some("Europe/Berlin".to_string());
+        counted.recurrence = Some(Recurrence::from_rule(
+            "FREQ=DAILY;COUNT=4000000000".parse().unwrap(),
+        ));
+        store.insert(counted);
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains("DTSTART:20270328T020000\r\n"));
+        // Long series are only described for their first fifty years.
+        assert!(output.contains("DTSTART:20761025T030000\r\n"));
+        assert!(!output.contains("DTSTART:2077"));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let output = export(&sample_store(), ExportRange::All, stamp());
+        assert!(output.contains("SUMMARY:Standup\\; daily\\, short\r\n"));
+        assert!(output.contains("DESCRIPTION:Agenda:\\nblockers\r\n"));
+    }
+
+    #[test]
+    fn long_lines_are_folded_without_splitting_characters() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let title = "Überlange Besprechung ".repeat(10);
+        store.insert(Event::new(
+            title.trim_end(),
+            EventTime::all_day(day, day).unwrap(),
+        ));
+
+        let output = export(&store, ExportRange::All, stamp());
+        for line in output.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_LENGTH, "{line:?}");
+        }
+        let event = parse_events(&output, &Utc).unwrap().remove(0).unwrap();
+        assert_eq!(event.title, title.trim_end());
+    }
+
+    #[test]
+    fn formats_offsets() {
+        assert_eq!(format_offset(Duration::hours(2)), "+0200");
+        assert_eq!(format_offset(Duration::minutes(-330)), "-0530");
+        assert_eq!(format_offset(Duration::seconds(3 * 3600 + 61)), "+030101");
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(-Duration::minutes(15)), "-PT15M");
+        assert_eq!(format_duration(-Duration::hours(29)), "-P1DT5H");
+        assert_eq!(format_duration(Duration::days(2)), "P2D");
+        assert_eq!(format_duration(Duration::seconds(3661)), "PT1H1M1S");
+        assert_eq!(format_duration(Duration::zero()), "PT0S");
+    }
+
+    #[test]
+    fn reminders_import_back_unchanged() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let mut dentist = Event::new(
+            "Dentist",
+            EventTime::timed(at(day, 14, 0), at(day, 15, 0)).unwrap(),
+        );
+        dentist.reminders = vec![
+            Reminder::minutes_before(15),
+            Reminder::days_before_at(1, NaiveTime::from_hms_opt(9, 0, 0).unwrap(), at(day, 14, 0))
+                .unwrap(),
+            Reminder::Absolute(at(day, 8, 0)),
+        ];
+        store.insert(dentist.clone());
+
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains(
+            "BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:Dentist\r\nTRIGGER:-PT15M\r\n"
+        ));
+        assert!(output.contains("TRIGGER:-P1DT5H\r\n"));
+        assert!(output.contains("TRIGGER;VALUE=DATE-TIME:20261016T080000\r\n"));
+        let events: Vec<Event> = parse_events(&output, &Utc)
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let original: Vec<Event> = store.iter().map(|(_, event)| event.clone()).collect();
+        assert_eq!(events, original);
+
+        // Fixed times of events with a time zone are written in UTC.
+        dentist.time_zone = Some("Europe/Berlin".to_string());
+        dentist.reminders = vec![Reminder::Absolute(at(day, 8, 0))];
+        store.insert(dentist);
+        let output = export(&store, ExportRange::All, stamp());
+        assert!(output.contains("TRIGGER;VALUE=DATE-TIME:20261016T060000Z\r\n"));
+    }
+}
@@ -0,0 +1,885 @@
+use super::{IcalError, IcalErrorKind};
+use crate::event::{Event, EventId, EventStore, EventTime};
+use crate::recurrence::{Recurrence, RecurrenceRule};
+use crate::reminder::Reminder;
+use chrono::prelude::*;
+use chrono::Duration;
+
+/**
+ * A property of a component after unfolding, e.g. `DTSTART;TZID=Europe/Berlin:20261016T090000`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ContentLine {
+    line: usize,
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn invalid(&self) -> IcalError {
+        IcalError {
+            line: self.line,
+            kind: IcalErrorKind::InvalidValue {
+                property: self.name.clone(),
+                value: self.value.clone(),
+            },
+        }
+    }
+}
+
+/**
+ * A `BEGIN:<name>` ... `END:<name>` block.
+ */
+#[derive(Debug, Clone, Default)]
+struct Component {
+    line: usize,
+    name: String,
+    properties: Vec<ContentLine>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    fn properties_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a ContentLine> {
+        self.properties
+            .iter()
+            .filter(move |property| property.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|property| unescape_text(&property.value))
+            .filter(|text| !text.is_empty())
+    }
+}
+
+/**
+ * Either end of an event as written in the file.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Moment {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Moment {
+    /**
+     * Returns the moment as a point in time, dates starting at midnight.
+     */
+    fn start(self) -> NaiveDateTime {
+        match self {
+            Moment::Date(date) => date.and_time(NaiveTime::MIN),
+            Moment::DateTime(time) => time,
+        }
+    }
+}
+
+/**
+ * Outcome of importing a file into an `EventStore`.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /** Events that were not in the store before. */
+    pub added: Vec<EventId>,
+    /** Existing events replaced because they share their `UID` with an imported one. */
+    pub updated: Vec<EventId>,
+    /** Components that were left out, and why. */
+    pub skipped: Vec<IcalError>,
+}
+
+/**
+ * Function to import the events of an iCalendar file into the store.
+ *
+ * Events whose `UID` is already known replace the stored version, so importing
+ * the same file twice does not duplicate anything.
+ *
+ * Parameters:
+ * - `store`: The store receiving the events.
+ * - `input`: The content of the `.ics` file.
+ * - `zone`: The time zone UTC timestamps are converted to.
+ *
+ * Returns:
+ * - What was imported and which components were skipped, or an error if the
+ *   file is not a well-formed iCalendar object at all.
+ */
+pub fn import<Tz: TimeZone>(
+    store: &mut EventStore,
+    input: &str,
+    zone: &Tz,
+) -> Result<ImportReport, IcalError> {
+    let mut report = ImportReport::default();
+    for event in parse_events(input, zone)? {
+        match event {
+            Ok(event) => match event.uid.as_deref().and_then(|uid| store.find_by_uid(uid)) {
+                Some(id) => {
+                    store.update(id, event);
+                    report.updated.push(id);
+                }
+                None => report.added.push(store.insert(event)),
+            },
+            Err(err) => report.skipped.push(err),
+        }
+    }
+    Ok(report)
+}
+
+/**
+ * Function to parse the `VEVENT` components of an iCalendar file.
+ *
+ * A `VEVENT` with a `RECURRENCE-ID` replaces one occurrence of a recurring
+ * event. As the store keeps such occurrences as events of their own, the
+ * occurrence is excluded from the series and the replacement gets a UID
+ * derived from the series and the occurrence it replaces.
+ *
+ * Parameters:
+ * - `input`: The content of the `.ics` file.
+ * - `zone`: The time zone UTC timestamps are converted to.
+ *
+ * Returns:
+ * - One entry per component of the calendar: the event, or the reason it
+ *   cannot be represented. Structural errors that make the whole file
+ *   unreadable are returned as the outer error.
+ */
+pub fn parse_events<Tz: TimeZone>(
+    input: &str,
+    zone: &Tz,
+) -> Result<Vec<Result<Event, IcalError>>, IcalError> {
+    let lines = unfold(input)
+        .into_iter()
+        .map(|(line, text)| parse_content_line(line, &text))
+        .collect::<Result<Vec<_>, _>>()?;
+    let components = build_components(lines)?;
+
+    let calendars: Vec<_> = components
+        .iter()
+        .filter(|component| component.name == "VCALENDAR")
+        .collect();
+    if calendars.is_empty() {
+        return Err(IcalError {
+            line: 1,
+            kind: IcalErrorKind::NotACalendar,
+        });
+    }
+
+    let mut events = Vec::new();
+    let mut detached = Vec::new();
+    for component in calendars.iter().flat_map(|calendar| &calendar.children) {
+        match component.name.as_str() {
+            "VEVENT" => match to_event(component, zone) {
+                Ok((event, Some(recurrence_id))) => {
+                    detached.push((events.len(), recurrence_id));
+                    events.push(Ok(event));
+                }
+                Ok((event, None)) => events.push(Ok(event)),
+                Err(err) => events.push(Err(err)),
+            },
+            // Time zone definitions are only referenced through TZID parameters.
+            "VTIMEZONE" => {}
+            name => events.push(Err(IcalError {
+                line: component.line,
+                kind: IcalErrorKind::UnsupportedComponent(name.to_string()),
+            })),
+        }
+    }
+
+    for (index, recurrence_id) in detached {
+        let Ok(event) = &mut events[index] else {
+            continue;
+        };
+        let Some(uid) = event.uid.clone() else {
+            continue;
+        };
+        event.uid = Some(format!("{uid}/{}", recurrence_id.format("%Y%m%dT%H%M%S")));
+        let series = events
+            .iter_mut()
+            .flatten()
+            .find(|other| other.uid.as_deref() == Some(uid.as_str()) && other.recurrence.is_some());
+        if let Some(recurrence) = series.and_then(|series| series.recurrence.as_mut()) {
+            recurrence.exdates.push(recurrence_id);
+        }
+    }
+    Ok(events)
+}
+
+/**
+ * Joins folded lines, i.e. lines continued by a leading space or tab.
+ *
+ * Returns:
+ * - The logical lines together with the physical line they start on.
+ */
+fn unfold(input: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, raw) in input.lines().enumerate() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, previous))) => previous.push_str(continuation),
+            _ if raw.is_empty() => {}
+            _ => lines.push((index + 1, raw.to_string())),
+        }
+    }
+    lines
+}
+
+/**
+ * Splits a logical line into name, parameters and value.
+ */
+fn parse_content_line(line: usize, text: &str) -> Result<ContentLine, IcalError> {
+    let malformed = || IcalError {
+        line,
+        kind: IcalErrorKind::MalformedLine(text.to_string()),
+    };
+
+    let name_end = text.find([';', ':']).ok_or_else(malformed)?;
+    let name = text[..name_end].to_ascii_uppercase();
+    if name.is_empty() {
+        return Err(malformed());
+    }
+
+    let mut params = Vec::new();
+    let mut rest = &text[name_end..];
+    while let Some(param) = rest.strip_prefix(';') {
+        let eq = param.find('=').ok_or_else(malformed)?;
+        let key = param[..eq].to_ascii_uppercase();
+        let after = &param[eq + 1..];
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let close = quoted.find('"').ok_or_else(malformed)?;
+            (&quoted[..close], &quoted[close + 1..])
+        } else {
+            let end = after.find([';', ':']).ok_or_else(malformed)?;
+            (&after[..end], &after[end..])
+        };
+        params.push((key, value.to_string()));
+        rest = remaining;
+    }
+
+    let value = rest.strip_prefix(':').ok_or_else(malformed)?;
+    Ok(ContentLine {
+        line,
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+/**
+ * Nests the content lines into components according to their BEGIN/END lines.
+ */
+fn build_components(lines: Vec<ContentLine>) -> Result<Vec<Component>, IcalError> {
+    let mut root = Component::default();
+    let mut stack: Vec<Component> = Vec::new();
+
+    for line in lines {
+        match line.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                line: line.line,
+                name: line.value.to_ascii_uppercase(),
+                ..Component::default()
+            }),
+            "END" => {
+                let name = line.value.to_ascii_uppercase();
+                let component = match stack.pop() {
+                    Some(component) if component.name == name => component,
+                    open => {
+                        return Err(IcalError {
+                            line: line.line,
+                            kind: IcalErrorKind::UnbalancedComponent {
+                                expected: open.map(|c| c.name).unwrap_or_default(),
+                                found: format!("END:{name}"),
+                            },
+                        })
+                    }
+                };
+                stack
+                    .last_mut()
+                    .unwrap_or(&mut root)
+                    .children
+                    .push(component);
+            }
+            _ => match stack.last_mut() {
+                Some(component) => component.properties.push(line),
+                None => {
+                    return Err(IcalError {
+                        line: line.line,
+                        kind: IcalErrorKind::MalformedLine(line.name),
+                    })
+                }
+            },
+        }
+    }
+
+    if let Some(open) = stack.pop() {
+        return Err(IcalError {
+            line: open.line,
+            kind: IcalErrorKind::UnbalancedComponent {
+                expected: open.name,
+                found: "end of file".to_string(),
+            },
+        });
+    }
+    Ok(root.children)
+}
+
+/**
+ * Maps a `VEVENT` component to an `Event`.
+ *
+ * Returns:
+ * - The event, and the start of the occurrence it replaces if it has a
+ *   `RECURRENCE-ID`.
+ */
+fn to_event<Tz: TimeZone>(
+    component: &Component,
+    zone: &Tz,
+) -> Result<(Event, Option<NaiveDateTime>), IcalError> {
+    let missing = |name| IcalError {
+        line: component.line,
+        kind: IcalErrorKind::MissingProperty(name),
+    };
+
+    let dtstart = component
+        .property("DTSTART")
+        .ok_or_else(|| missing("DTSTART"))?;
+    let start = parse_moment(dtstart, zone)?;
+
+    let end = if let Some(dtend) = component.property("DTEND") {
+        Some((parse_moment(dtend, zone)?, dtend))
+    } else if let Some(duration) = component.property("DURATION") {
+        let length = parse_duration(&duration.value).ok_or_else(|| duration.invalid())?;
+        let end = match start {
+            Moment::Date(date) => date.checked_add_signed(length).map(Moment::Date),
+            Moment::DateTime(time) => time.checked_add_signed(length).map(Moment::DateTime),
+        };
+        Some((end.ok_or_else(|| duration.invalid())?, duration))
+    } else {
+        None
+    };
+
+    let time = match (start, end) {
+        (Moment::Date(start), None) => EventTime::all_day(start, start),
+        // The end date of all-day events is exclusive in iCalendar.
+        (Moment::Date(start), Some((Moment::Date(end), _))) => {
+            EventTime::all_day(start, end.pred_opt().unwrap_or(end).max(start))
+        }
+        (Moment::DateTime(start), None) => EventTime::timed(start, start),
+        (Moment::DateTime(start), Some((Moment::DateTime(end), property))) => {
+            Some(EventTime::timed(start, end).ok_or_else(|| property.invalid())?)
+        }
+        (_, Some((_, property))) => return Err(property.invalid()),
+    }
+    .ok_or_else(|| dtstart.invalid())?;
+
+    let mut event = Event::new(
+        component
+            .text("SUMMARY")
+            .unwrap_or_else(|| "(No title)".to_string()),
+        time,
+    );
+    event.location = component.text("LOCATION");
+    event.notes = component.text("DESCRIPTION");
+    event.uid = component.text("UID");
+    event.time_zone = dtstart
+        .param("TZID")
+        .filter(|_| !event.time.is_all_day())
+        .map(|tzid| tzid.trim_start_matches('/').to_string());
+    event.recurrence = parse_recurrence(component, zone)?;
+    event.reminders = parse_reminders(component, &event.time, zone)?;
+
+    let recurrence_id = component
+        .property("RECURRENCE-ID")
+        .map(|property| parse_moment(property, zone).map(Moment::start))
+        .transpose()?;
+    Ok((event, recurrence_id))
+}
+
+/**
+ * Collects the RRULE, EXDATE and RDATE properties of a component.
+ *
+ * Returns:
+ * - The recurrence, or `None` if the component does not recur.
+ */
+fn parse_recurrence<Tz: TimeZone>(
+    component: &Component,
+    zone: &Tz,
+) -> Result<Option<Recurrence>, IcalError> {
+    let rule = component
+        .property("RRULE")
+        .map(|property| {
+            property
+                .value
+                .parse::<RecurrenceRule>()
+                .map_err(|_| property.invalid())
+        })
+        .transpose()?;
+    let exdates = parse_date_list(component, "EXDATE", zone)?;
+    let rdates = parse_date_list(component, "RDATE", zone)?;
+
+    if rule.is_none() && rdates.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Recurrence {
+        rule,
+        exdates,
+        rdates,
+    }))
+}
+
+/**
+ * Maps the `VALARM` components of an event to reminders, whatever their
+ * `ACTION`. Triggers relative to the end are converted to offsets from the
+ * start.
+ */
+fn parse_reminders<Tz: TimeZone>(
+    component: &Component,
+    time: &EventTime,
+    zone: &Tz,
+) -> Result<Vec<Reminder>, IcalError> {
+    let mut reminders = Vec::new();
+    for alarm in component
+        .children
+        .iter()
+        .filter(|child| child.name == "VALARM")
+    {
+        let trigger = alarm.property("TRIGGER").ok_or(IcalError {
+            line: alarm.line,
+            kind: IcalErrorKind::MissingProperty("TRIGGER"),
+        })?;
+        if trigger.param("VALUE") == Some("DATE-TIME") {
+            let moment = parse_moment(trigger, zone)?;
+            reminders.push(Reminder::Absolute(moment.start()));
+            continue;
+        }
+        let value = trigger.value.trim();
+        let mut offset = match value.strip_prefix('-') {
+            Some(duration) => parse_duration(duration).map(|duration| -duration),
+            None => parse_duration(value),
+        }
+        .ok_or_else(|| trigger.invalid())?;
+        if trigger.param("RELATED") == Some("END") {
+            // All-day events end at midnight after their last date.
+            let end = match *time {
+                EventTime::AllDay { end, .. } => end.and_time(NaiveTime::MIN) + Duration::days(1),
+                EventTime::Timed { end, .. } => end,
+            };
+            offset += end - time.start();
+        }
+        reminders.push(Reminder::Relative(offset));
+    }
+    Ok(reminders)
+}
+
+/**
+ * Parses every value of the (possibly repeated) comma separated date list property `name`.
+ */
+fn parse_date_list<Tz: TimeZone>(
+    component: &Component,
+    name: &str,
+    zone: &Tz,
+) -> Result<Vec<NaiveDateTime>, IcalError> {
+    let mut dates = Vec::new();
+    for property in component.properties_named(name) {
+        if property.param("VALUE") == Some("PERIOD") {
+            return Err(property.invalid());
+        }
+        for value in property.value.split(',') {
+            let single = ContentLine {
+                value: value.to_string(),
+                ..property.clone()
+            };
+            let moment = parse_moment(&single, zone).map_err(|_| property.invalid())?;
+            dates.push(moment.start());
+        }
+    }
+    Ok(dates)
+}
+
+/**
+ * Parses a DATE or DATE-TIME value. UTC times are converted to `zone`, other
+ * times are kept as wall-clock times.
+ */
+fn parse_moment<Tz: TimeZone>(property: &ContentLine, zone: &Tz) -> Result<Moment, IcalError> {
+    let value = property.value.trim();
+    let is_date = property.param("VALUE") == Some("DATE") || value.len() == 8;
+
+    let moment = if is_date {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(Moment::Date)
+    } else if let Some(utc) = value.strip_suffix('Z') {
+        NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|time| {
+                Moment::DateTime(
+                    Utc.from_utc_datetime(&time)
+                        .with_timezone(zone)
+                        .naive_local(),
+                )
+            })
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(Moment::DateTime)
+    };
+    moment.ok_or_else(|| property.invalid())
+}
+
+/**
+ * Parses a non-negative DURATION value such as `PT1H30M` or `P1W`.
+ */
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let mut rest = value.strip_prefix('P')?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    while !rest.is_empty() {
+        if let Some(time) = rest.strip_prefix('T') {
+            if in_time || time.is_empty() {
+                return None;
+            }
+            in_time = true;
+            rest = time;
+            continue;
+        }
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let part = match (in_time, unit) {
+            (false, 'W') => Duration::try_weeks(amount)?,
+            (false, 'D') => Duration::try_days(amount)?,
+            (true, 'H') => Duration::try_hours(amount)?,
+            (true, 'M') => Duration::try_minutes(amount)?,
+            (true, 'S') => Duration::try_seconds(amount)?,
+            _ => return None,
+        };
+        total = total.checked_add(&part)?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    Some(total)
+}
+
+/**
+ * Resolves the backslash escapes of a TEXT value.
+ */
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn calendar(body: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//test//EN\r\n{body}END:VCALENDAR\r\n")
+    }
+
+    fn single_event(body: &str) -> Result<Event, IcalError> {
+        let input = calendar(&format!("BEGIN:VEVENT\r\n{body}END:VEVENT\r\n"));
+        let mut events = parse_events(&input, &Utc).unwrap();
+        assert_eq!(events.len(), 1);
+        events.remove(0)
+    }
+
+    #[test]
+    fn maps_recurrences() {
+        let event = single_event(
+            "SUMMARY:Standup\r\n\
+             DTSTART:20261005T090000\r\n\
+             DTEND:20261005T091500\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE\r\n\
+             EXDATE:20261007T090000,20261012T090000\r\n\
+             EXDATE:20261014T090000\r\n\
+             RDATE;VALUE=DATE:20261010\r\n",
+        )
+        .unwrap();
+
+        let recurrence = event.recurrence.unwrap();
+        assert_eq!(
+            recurrence.rule,
+            Some("FREQ=WEEKLY;BYDAY=MO,WE".parse().unwrap())
+        );
+        assert_eq!(recurrence.exdates.len(), 3);
+        assert_eq!(recurrence.rdates, [at(date(2026, 10, 10), 0, 0)]);
+
+        let invalid = single_event("DTSTART:20261005T090000\r\nRRULE:FREQ=SOMETIMES\r\n");
+        assert!(
+            matches!(invalid.unwrap_err().kind, IcalErrorKind::InvalidValue { property, .. } if property == "RRULE")
+        );
+    }
+
+    #[test]
+    fn detached_occurrences_are_excluded_from_their_series() {
+        let input = calendar(
+            "BEGIN:VEVENT\r\nUID:standup@example.com\r\nSUMMARY:Standup\r\n\
+             RECURRENCE-ID:20261007T090000\r\nDTSTART:20261007T100000\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:standup@example.com\r\nSUMMARY:Standup\r\n\
+             DTSTART:20261005T090000\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n",
+        );
+        let events: Vec<Event> = parse_events(&input, &Utc)
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(
+            events[0].uid.as_deref(),
+            Some("standup@example.com/20261007T090000")
+        );
+        assert_eq!(events[0].recurrence, None);
+        assert_eq!(
+            events[1].recurrence.as_ref().unwrap().exdates,
+            [at(date(2026, 10, 7), 9, 0)]
+        );
+    }
+
+    #[test]
+    fn maps_alarms_to_reminders() {
+        let event = single_event(
+            "DTSTART:20261016T140000\r\nDTEND:20261016T150000\r\n\
+             BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\n\
+             BEGIN:VALARM\r\nACTION:AUDIO\r\nTRIGGER;RELATED=END:PT5M\r\nEND:VALARM\r\n\
+             BEGIN:VALARM\r\nTRIGGER;VALUE=DATE-TIME:20261016T060000Z\r\nEND:VALARM\r\n",
+        )
+        .unwrap();
+        let day = date(2026, 10, 16);
+        assert_eq!(
+            event.reminders,
+            [
+                Reminder::minutes_before(15),
+                Reminder::Relative(Duration::minutes(65)),
+                Reminder::Absolute(at(day, 6, 0)),
+            ]
+        );
+
+        let broken = single_event(
+            "DTSTART:20261016T140000\r\nBEGIN:VALARM\r\nTRIGGER:soon\r\nEND:VALARM\r\n",
+        );
+        assert!(broken.is_err());
+        let missing = single_event(
+            "DTSTART:20261016T140000\r\nBEGIN:VALARM\r\nACTION:DISPLAY\r\nEND:VALARM\r\n",
+        );
+        assert!(matches!(
+            missing.unwrap_err().kind,
+            IcalErrorKind::MissingProperty("TRIGGER")
+        ));
+    }
+
+    #[test]
+    fn maps_timed_events() {
+        let event = single_event(
+            "UID:standup-1@example.com\r\n\
+             SUMMARY:Standup\\, daily\r\n\
+             LOCATION:Room 4\r\n\
+             DESCRIPTION:Line one\\nLine two\r\n\
+             DTSTART;TZID=Europe/Berlin:20261016T090000\r\n\
+             DTEND;TZID=Europe/Berlin:20261016T091500\r\n",
+        )
+        .unwrap();
+
+        let day = date(2026, 10, 16);
+        assert_eq!(event.title, "Standup, daily");
+        assert_eq!(event.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.notes.as_deref(), Some("Line one\nLine two"));
+        assert_eq!(event.uid.as_deref(), Some("standup-1@example.com"));
+        assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(
+            event.time,
+            EventTime::timed(at(day, 9, 0), at(day, 9, 15)).unwrap()
+        );
+    }
+
+    #[test]
+    fn all_day_end_dates_are_exclusive() {
+        let event = single_event(
+            "SUMMARY:Trip\r\nDTSTART;VALUE=DATE:20261030\r\nDTEND;VALUE=DATE:20261102\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            event.time,
+            EventTime::all_day(date(2026, 10, 30), date(2026, 11, 1)).unwrap()
+        );
+
+        let single = single_event("SUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20261003\r\n").unwrap();
+        let day = date(2026, 10, 3);
+        assert_eq!(single.time, EventTime::all_day(day, day).unwrap());
+    }
+
+    #[test]
+    fn durations_define_the_end() {
+        let event = single_event("DTSTART:20261016T120000\r\nDURATION:PT1H30M\r\n").unwrap();
+        let day = date(2026, 10, 16);
+        assert_eq!(
+            event.time,
+            EventTime::timed(at(day, 12, 0), at(day, 13, 30)).unwrap()
+        );
+
+        let week = single_event("DTSTART;VALUE=DATE:20261012\r\nDURATION:P1W\r\n").unwrap();
+        assert_eq!(
+            week.time,
+            EventTime::all_day(date(2026, 10, 12), date(2026, 10, 18)).unwrap()
+        );
+    }
+
+    #[test]
+    fn utc_times_are_converted_to_the_target_zone() {
+        let input = calendar(
+            "BEGIN:VEVENT\r\nDTSTART:20261016T230000Z\r\nDTEND:20261017T000000Z\r\nEND:VEVENT\r\n",
+        );
+        let zone = FixedOffset::east_opt(2 * 3600).unwrap();
+        let event = parse_events(&input, &zone).unwrap().remove(0).unwrap();
+
+        let day = date(2026, 10, 17);
+        assert_eq!(
+            event.time,
+            EventTime::timed(at(day, 1, 0), at(day, 2, 0)).unwrap()
+        );
+        assert_eq!(event.time_zone, None);
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let event =
+            single_event("SUMMARY:A very long\r\n  title\r\nDTSTART;VALUE=DATE:\r\n 20261016\r\n")
+                .unwrap();
+        assert_eq!(event.title, "A very long title");
+    }
+
+    #[test]
+    fn broken_events_are_reported_without_affecting_others() {
+        let input = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:No start\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:2026-10-16\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:20261016T100000\r\nDTEND:20261016T090000\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20261016\r\nDTEND:20261017T090000\r\nEND:VEVENT\r\n\
+             BEGIN:VTODO\r\nSUMMARY:Chores\r\nEND:VTODO\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Fine\r\nDTSTART;VALUE=DATE:20261016\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:20261016T100000\r\nDURATION:PT1é\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:20261016T100000\r\n\
+             BEGIN:VALARM\r\nTRIGGER:-PT1é\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+        );
+        let events = parse_events(&input, &Utc).unwrap();
+
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|event| event.as_ref().map_err(|err| err.kind.clone()))
+            .collect();
+        assert_eq!(kinds[0], Err(IcalErrorKind::MissingProperty("DTSTART")));
+        assert!(
+            matches!(&kinds[1], Err(IcalErrorKind::InvalidValue { property, .. }) if property == "DTSTART")
+        );
+        assert!(
+            matches!(&kinds[2], Err(IcalErrorKind::InvalidValue { property, .. }) if property == "DTEND")
+        );
+        assert!(
+            matches!(&kinds[3], Err(IcalErrorKind::InvalidValue { property, .. }) if property == "DTEND")
+        );
+        assert_eq!(
+            kinds[4],
+            Err(IcalErrorKind::UnsupportedComponent("VTODO".to_string()))
+        );
+        assert_eq!(kinds[5].as_ref().unwrap().title, "Fine");
+        // Units that are not ASCII are rejected like any other unknown unit.
+        assert!(
+            matches!(&kinds[6], Err(IcalErrorKind::InvalidValue { property, .. }) if property == "DURATION")
+        );
+        assert!(kinds[7].is_err());
+        assert_eq!(events[4].as_ref().unwrap_err().line, 18);
+    }
+
+    #[test]
+    fn structural_errors_fail_the_whole_file() {
+        let unbalanced = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert!(matches!(
+            parse_events(unbalanced, &Utc).unwrap_err().kind,
+            IcalErrorKind::UnbalancedComponent { .. }
+        ));
+
+        let unterminated = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\n";
+        assert_eq!(parse_events(unterminated, &Utc).unwrap_err().line, 2);
+
+        let garbage = "BEGIN:VCALENDAR\r\nthis is not ical\r\nEND:VCALENDAR\r\n";
+        assert!(matches!(
+            parse_events(garbage, &Utc).unwrap_err().kind,
+            IcalErrorKind::MalformedLine(_)
+        ));
+
+        assert_eq!(
+            parse_events("", &Utc).unwrap_err().kind,
+            IcalErrorKind::NotACalendar
+        );
+    }
+
+    #[test]
+    fn quoted_parameters_may_contain_delimiters() {
+        let line = parse_content_line(
+            1,
+            "ATTENDEE;CN=\"Doe; John:Jr\";ROLE=CHAIR:mailto:j@d.example",
+        )
+        .unwrap();
+        assert_eq!(line.param("CN"), Some("Doe; John:Jr"));
+        assert_eq!(line.param("ROLE"), Some("CHAIR"));
+        assert_eq!(line.value, "mailto:j@d.example");
+    }
+
+    #[test]
+    fn reimporting_updates_events_by_uid() {
+        let mut store = EventStore::new();
+        let first = calendar("BEGIN:VEVENT\r\nUID:a@example.com\r\nSUMMARY:Old\r\nDTSTART;VALUE=DATE:20261016\r\nEND:VEVENT\r\n");
+        let second = calendar("BEGIN:VEVENT\r\nUID:a@example.com\r\nSUMMARY:New\r\nDTSTART;VALUE=DATE:20261017\r\nEND:VEVENT\r\n");
+
+        let report = import(&mut store, &first, &Utc).unwrap();
+        assert_eq!(report.added.len(), 1);
+        let report = import(&mut store, &second, &Utc).unwrap();
+        assert_eq!(report.updated, vec![EventId(0)]);
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.events_on(date(2026, 10, 17))[0].event.title, "New");
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT15M"), Some(Duration::minutes(15)));
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("P2W"), Some(Duration::days(14)));
+        assert_eq!(parse_duration("-PT15M"), None);
+        assert_eq!(parse_duration("P"), None);
+        assert_eq!(parse_duration("PT"), None);
+        assert_eq!(parse_duration("P1H"), None);
+    }
+}
@@ -0,0 +1,60 @@
+/*!
+ * Exchange of events with other calendar applications through iCalendar
+ * (RFC 5545) files.
+ */
+
+pub mod export;
+pub mod import;
+
+pub use export::{export, ExportRange};
+pub use import::{import, parse_events, ImportReport};
+
+use std::fmt;
+
+/**
+ * Something that went wrong while reading an iCalendar file.
+ *
+ * `line` is the (1-based) physical line on which the offending content line
+ * or component starts.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcalError {
+    pub line: usize,
+    pub kind: IcalErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcalErrorKind {
+    /** A content line without a `name:value` structure. */
+    MalformedLine(String),
+    /** An `END` that does not match the innermost open `BEGIN`, or a missing `END`. */
+    UnbalancedComponent { expected: String, found: String },
+    /** The input does not contain a `VCALENDAR` object. */
+    NotACalendar,
+    /** A component this application cannot represent, such as `VTODO`. */
+    UnsupportedComponent(String),
+    /** A `VEVENT` without a property that is needed to place it in the calendar. */
+    MissingProperty(&'static str),
+    /** A property whose value cannot be parsed. */
+    InvalidValue { property: String, value: String },
+}
+
+impl fmt::Display for IcalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            IcalErrorKind::MalformedLine(line) => write!(f, "malformed content line {line:?}"),
+            IcalErrorKind::UnbalancedComponent { expected, found } => {
+                write!(f, "expected END:{expected}, found {found}")
+            }
+            IcalErrorKind::NotACalendar => write!(f, "no VCALENDAR object found"),
+            IcalErrorKind::UnsupportedComponent(name) => write!(f, "unsupported component {name}"),
+            IcalErrorKind::MissingProperty(name) => write!(f, "missing property {name}"),
+            IcalErrorKind::InvalidValue { property, value } => {
+                write!(f, "invalid value {value:?} for {property}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IcalError {}
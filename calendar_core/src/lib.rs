@@ -0,0 +1,43 @@
+/*!
+ * Front-end independent calendar logic.
+ *
+ * Everything in this crate is plain Rust on top of `chrono`, so it can be
+ * exercised from unit tests or reused by front-ends other than the Slint
+ * window in the `idk2` binary.
+ */
+
+pub mod agenda;
+pub mod business;
+pub mod clock;
+pub mod day;
+pub mod draft;
+pub mod event;
+pub mod grid;
+pub mod holiday;
+pub mod ical;
+pub mod locale;
+pub mod navigation;
+pub mod quick_add;
+pub mod recurrence;
+pub mod reminder;
+pub mod settings;
+pub mod storage;
+pub mod text;
+pub mod week;
+
+pub use agenda::{Agenda, AgendaDay};
+pub use business::BusinessCalendar;
+pub use clock::{Clock, DayWatch, FixedClock, SystemClock};
+pub use day::{DayLayout, SlotSize};
+pub use draft::{DraftError, DraftField, EventDraft};
+pub use event::{Color, EditScope, Event, EventId, EventStore, EventTime, Occurrence};
+pub use grid::{CellKind, DayCell, MonthGrid, WeekNumbering};
+pub use holiday::{Holiday, HolidayCalendar, HolidayError};
+pub use locale::{Locale, NameStyle};
+pub use navigation::MonthCursor;
+pub use recurrence::{ByDay, Frequency, Recurrence, RecurrenceRule};
+pub use reminder::{Alert, Reminder, ReminderState, Scheduler};
+pub use settings::Settings;
+pub use storage::{Storage, StorageError};
+pub use text::TextCalendar;
+pub use week::WeekLayout;
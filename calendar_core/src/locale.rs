@@ -0,0 +1,377 @@
+/*!
+ * Month and weekday names, date formats and the labels of the languages the
+ * calendar is displayed in.
+ *
+ * Every language is described by a JSON catalog named after its ISO 639-1
+ * code. Dates are formatted with patterns in which `{year}`, `{month}`,
+ * `{day}` and `{weekday}` are replaced by the parts of the date, so that
+ * each language can order them its own way:
+ *
+ * ```json
+ * {
+ *   "name": "日本語",
+ *   "months": ["1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月"],
+ *   "month_title": "{year}年{month}",
+ *   ...
+ * }
+ * ```
+ */
+
+use chrono::prelude::*;
+use serde::Deserialize;
+
+/**
+ * The catalogs shipped with the application, by language code.
+ */
+const BUILTIN: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("de", include_str!("../locales/de.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("ja", include_str!("../locales/ja.json")),
+];
+
+/**
+ * Whether names are written out or abbreviated, e.g. for narrow windows.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameStyle {
+    #[default]
+    Full,
+    Short,
+}
+
+/**
+ * A translation catalog as stored in a locale file.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+struct Catalog {
+    name: String,
+    months: [String; 12],
+    months_short: [String; 12],
+    // Starting with Monday.
+    weekdays: [String; 7],
+    weekdays_short: [String; 7],
+    month_title: String,
+    date: String,
+    day: String,
+    day_month: String,
+    all_day: String,
+    // `{name}` is replaced by the name of the holiday.
+    observed: String,
+    // `{weekend}` and `{holidays}` are replaced by the rules in effect.
+    business_rules: String,
+    none: String,
+    new_event: String,
+    edit_event: String,
+}
+
+/**
+ * The names and date formats of a language.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    /** The ISO 639-1 code of the language, e.g. `"de"`. */
+    pub code: String,
+    catalog: Catalog,
+}
+
+impl Default for Locale {
+    fn default() -> Locale {
+        Locale::find("en").expect("the English catalog is built in")
+    }
+}
+
+impl Locale {
+    /**
+     * Returns the built-in languages, ordered by code with English first.
+     */
+    pub fn available() -> Vec<Locale> {
+        BUILTIN
+            .iter()
+            .map(|(code, input)| {
+                Locale::from_json(code, input).expect("built-in catalogs are valid")
+            })
+            .collect()
+    }
+
+    /**
+     * Function to look up the language of a locale name.
+     *
+     * Parameters:
+     * - `name`: A language code, optionally with a territory, encoding or
+     *   modifier as in the `LANG` environment variable, e.g. `"de"`,
+     *   `"de-AT"` or `"de_DE.UTF-8"`.
+     *
+     * Returns:
+     * - The locale, or `None` if the language is not available.
+     */
+    pub fn find(name: &str) -> Option<Locale> {
+        let language = name
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (code, input) = BUILTIN.iter().find(|(code, _)| *code == language)?;
+        Locale::from_json(code, input).ok()
+    }
+
+    /**
+     * Function to parse a catalog.
+     *
+     * Parameters:
+     * - `code`: The language code of the catalog.
+     * - `input`: The content of the catalog file.
+     *
+     * Returns:
+     * - The locale, or an error if the catalog is malformed.
+     */
+    pub fn from_json(code: &str, input: &str) -> Result<Locale, serde_json::Error> {
+        Ok(Locale {
+            code: code.to_string(),
+            catalog: serde_json::from_str(input)?,
+        })
+    }
+
+    /**
+     * Returns the name of the language in the language itself, e.g. `"Deutsch"`.
+     */
+    pub fn name(&self) -> &str {
+        &self.catalog.name
+    }
+
+    /**
+     * Returns the name of `month` (1 to 12), or `None` for other numbers.
+     */
+    pub fn month_name(&self, month: u32, style: NameStyle) -> Option<&str> {
+        let months = match style {
+            NameStyle::Full => &self.catalog.months,
+            NameStyle::Short => &self.catalog.months_short,
+        };
+        months
+            .get(month.checked_sub(1)? as usize)
+            .map(String::as_str)
+    }
+
+    pub fn weekday_name(&self, weekday: Weekday, style: NameStyle) -> &str {
+        let weekdays = match style {
+            NameStyle::Full => &self.catalog.weekdays,
+            NameStyle::Short => &self.catalog.weekdays_short,
+        };
+        &weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /**
+     * Formats the month and year of `date` as a heading, e.g. "October 2026"
+     * or "2026年10月".
+     */
+    pub fn month_title(&self, date: NaiveDate, style: NameStyle) -> String {
+        self.format(date, &self.catalog.month_title, style)
+    }
+
+    /**
+     * Formats a complete date, e.g. "Friday 16 October 2026".
+     */
+    pub fn date(&self, date: NaiveDate, style: NameStyle) -> String {
+        self.format(date, &self.catalog.date, style)
+    }
+
+    /**
+     * Formats the weekday and day of the month, e.g. "Fri 16".
+     */
+    pub fn day(&self, date: NaiveDate, style: NameStyle) -> String {
+        self.format(date, &self.catalog.day, style)
+    }
+
+    /**
+     * Formats the day and month without the year, e.g. "Oct 16".
+     */
+    pub fn day_month(&self, date: NaiveDate, style: NameStyle) -> String {
+        self.format(date, &self.catalog.day_month, style)
+    }
+
+    /**
+     * Returns the label of events lasting the whole day, e.g. "all day".
+     */
+    pub fn all_day(&self) -> &str {
+        &self.catalog.all_day
+    }
+
+    /**
+     * Describes a holiday observed on another day than it falls on, e.g.
+     * "Christmas Day (observed)".
+     */
+    pub fn observed(&self, name: &str) -> String {
+        self.catalog.observed.replace("{name}", name)
+    }
+
+    /**
+     * Function to describe the rules business days are computed with.
+     *
+     * Parameters:
+     * - `weekend`: The weekend days, or `None` if there are none.
+     * - `holidays`: The name of the holiday region, or `None` if there is none.
+     *
+     * Returns:
+     * - The description, e.g. "Weekend: Saturday, Sunday · Holidays: none".
+     */
+    pub fn business_rules(&self, weekend: Option<&str>, holidays: Option<&str>) -> String {
+        self.catalog
+            .business_rules
+            .replace("{weekend}", weekend.unwrap_or(&self.catalog.none))
+            .replace("{holidays}", holidays.unwrap_or(&self.catalog.none))
+    }
+
+    /**
+     * Returns the heading of the dialog creating an event.
+     */
+    pub fn new_event(&self) -> &str {
+        &self.catalog.new_event
+    }
+
+    /**
+     * Returns the heading of the dialog editing an event.
+     */
+    pub fn edit_event(&self) -> &str {
+        &self.catalog.edit_event
+    }
+
+    /**
+     * Function to format a date with a pattern.
+     *
+     * Parameters:
+     * - `date`: The date to format.
+     * - `pattern`: The text to fill in; `{year}`, `{month}`, `{day}` and
+     *   `{weekday}` are replaced, anything else is kept as it is.
+     * - `style`: Whether month and weekday names are abbreviated.
+     *
+     * Returns:
+     * - The formatted date.
+     */
+    pub fn format(&self, date: NaiveDate, pattern: &str, style: NameStyle) -> String {
+        let mut formatted = String::with_capacity(pattern.len());
+        let mut rest = pattern;
+        while let Some(open) = rest.find('{') {
+            formatted.push_str(&rest[..open]);
+            rest = &rest[open..];
+            let Some(close) = rest.find('}') else {
+                break;
+            };
+            match &rest[1..close] {
+                "year" => formatted.push_str(&date.year().to_string()),
+                "month" => formatted.push_str(
+                    self.month_name(date.month(), style)
+                        .expect("chrono months are 1 to 12"),
+                ),
+                "day" => formatted.push_str(&date.day().to_string()),
+                "weekday" => formatted.push_str(self.weekday_name(date.weekday(), style)),
+                _ => formatted.push_str(&rest[..=close]),
+            }
+            rest = &rest[close + 1..];
+        }
+        formatted.push_str(rest);
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn locale(code: &str) -> Locale {
+        Locale::find(code).unwrap()
+    }
+
+    #[test]
+    fn every_builtin_catalog_parses() {
+        let codes: Vec<String> = Locale::available()
+            .into_iter()
+            .map(|locale| locale.code)
+            .collect();
+        assert_eq!(codes, ["en", "de", "es", "fr", "ja"]);
+    }
+
+    #[test]
+    fn locale_names_are_matched_by_language() {
+        assert_eq!(locale("de_DE.UTF-8").code, "de");
+        assert_eq!(locale("de-AT").code, "de");
+        assert_eq!(locale("FR").code, "fr");
+        assert!(Locale::find("xx").is_none());
+        assert!(Locale::find("").is_none());
+        assert_eq!(Locale::default().code, "en");
+    }
+
+    #[test]
+    fn names_come_in_full_and_short() {
+        let german = locale("de");
+        assert_eq!(german.month_name(3, NameStyle::Full), Some("März"));
+        assert_eq!(german.month_name(10, NameStyle::Short), Some("Okt."));
+        assert_eq!(german.month_name(0, NameStyle::Full), None);
+        assert_eq!(german.month_name(13, NameStyle::Full), None);
+        assert_eq!(
+            german.weekday_name(Weekday::Thu, NameStyle::Full),
+            "Donnerstag"
+        );
+        assert_eq!(german.weekday_name(Weekday::Sun, NameStyle::Short), "So");
+    }
+
+    #[test]
+    fn each_language_orders_the_parts_of_a_date() {
+        let day = date(2026, 10, 16);
+        assert_eq!(
+            locale("en").month_title(day, NameStyle::Full),
+            "October 2026"
+        );
+        assert_eq!(locale("ja").month_title(day, NameStyle::Full), "2026年10月");
+        assert_eq!(
+            locale("es").month_title(day, NameStyle::Full),
+            "octubre de 2026"
+        );
+        assert_eq!(locale("en").date(day, NameStyle::Short), "Fri 16 Oct 2026");
+        assert_eq!(
+            locale("de").date(day, NameStyle::Full),
+            "Freitag, 16. Oktober 2026"
+        );
+        assert_eq!(
+            locale("ja").date(day, NameStyle::Short),
+            "2026年10月16日(金)"
+        );
+        assert_eq!(locale("fr").day(day, NameStyle::Full), "vendredi 16");
+        assert_eq!(locale("en").day_month(day, NameStyle::Short), "Oct 16");
+    }
+
+    #[test]
+    fn labels_are_translated() {
+        assert_eq!(locale("en").all_day(), "all day");
+        assert_eq!(locale("ja").all_day(), "終日");
+        assert_eq!(
+            locale("en").observed("Christmas Day"),
+            "Christmas Day (observed)"
+        );
+        assert_eq!(
+            locale("de").business_rules(Some("Samstag, Sonntag"), None),
+            "Wochenende: Samstag, Sonntag · Feiertage: keine"
+        );
+        assert_eq!(
+            locale("en").business_rules(None, Some("Germany")),
+            "Weekend: none · Holidays: Germany"
+        );
+        assert_eq!(locale("fr").new_event(), "Nouvel événement");
+        assert_eq!(locale("es").edit_event(), "Editar evento");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        let english = locale("en");
+        let day = date(2026, 10, 16);
+        assert_eq!(
+            english.format(day, "{day}/{hour} {month", NameStyle::Full),
+            "16/{hour} {month"
+        );
+        assert_eq!(english.format(day, "", NameStyle::Full), "");
+    }
+}
@@ -0,0 +1,184 @@
+use crate::grid::MonthGrid;
+use chrono::prelude::*;
+
+/**
+ * The earliest year the cursor can reach. One year of margin is kept to the
+ * limits of `NaiveDate` so that the padding days of the grid always exist.
+ */
+pub const MIN_YEAR: i32 = -262_142;
+
+/**
+ * The latest year the cursor can reach, see `MIN_YEAR`.
+ */
+pub const MAX_YEAR: i32 = 262_141;
+
+/**
+ * The month currently shown by a front-end.
+ *
+ * A cursor always refers to an existing month: every navigation step rolls
+ * over years in both directions and saturates at `MIN_YEAR` / `MAX_YEAR`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonthCursor {
+    year: i32,
+    month: u32,
+}
+
+impl MonthCursor {
+    /**
+     * Creates a cursor pointing to the given month.
+     *
+     * Returns:
+     * - The cursor, or `None` if the month or year is out of range.
+     */
+    pub fn new(year: i32, month: u32) -> Option<MonthCursor> {
+        if (1..=12).contains(&month) && (MIN_YEAR..=MAX_YEAR).contains(&year) {
+            Some(MonthCursor { year, month })
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Creates a cursor pointing to the month containing `date`.
+     */
+    pub fn from_date(date: NaiveDate) -> MonthCursor {
+        MonthCursor {
+            year: date.year().clamp(MIN_YEAR, MAX_YEAR),
+            month: date.month(),
+        }
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /**
+     * Returns the first day of the month the cursor points to.
+     */
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("cursor is always valid")
+    }
+
+    /**
+     * Returns the month grid for the month the cursor points to, with weeks
+     * starting on `week_start`.
+     */
+    pub fn grid(&self, week_start: Weekday) -> MonthGrid {
+        MonthGrid::new(self.year, self.month, week_start).expect("cursor is always valid")
+    }
+
+    /**
+     * Moves the cursor by the given number of months, rolling over years.
+     */
+    pub fn add_months(self, months: i32) -> MonthCursor {
+        let index = self.year as i64 * 12 + (self.month as i64 - 1) + months as i64;
+        let min = MIN_YEAR as i64 * 12;
+        let max = MAX_YEAR as i64 * 12 + 11;
+        let index = index.clamp(min, max);
+        MonthCursor {
+            year: index.div_euclid(12) as i32,
+            month: index.rem_euclid(12) as u32 + 1,
+        }
+    }
+
+    /**
+     * Returns the cursors of the twelve months of the year the cursor points to.
+     */
+    pub fn months_of_year(&self) -> [MonthCursor; 12] {
+        let year = self.year;
+        std::array::from_fn(|index| MonthCursor {
+            year,
+            month: index as u32 + 1,
+        })
+    }
+
+    pub fn next_month(self) -> MonthCursor {
+        self.add_months(1)
+    }
+
+    pub fn previous_month(self) -> MonthCursor {
+        self.add_months(-1)
+    }
+
+    pub fn next_year(self) -> MonthCursor {
+        self.add_months(12)
+    }
+
+    pub fn previous_year(self) -> MonthCursor {
+        self.add_months(-12)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(year: i32, month: u32) -> MonthCursor {
+        MonthCursor::new(year, month).unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_months() {
+        assert_eq!(MonthCursor::new(2026, 0), None);
+        assert_eq!(MonthCursor::new(2026, 13), None);
+        assert_eq!(MonthCursor::new(MAX_YEAR + 1, 1), None);
+    }
+
+    #[test]
+    fn month_navigation_rolls_over_years() {
+        assert_eq!(cursor(2026, 12).next_month(), cursor(2027, 1));
+        assert_eq!(cursor(2027, 1).previous_month(), cursor(2026, 12));
+        assert_eq!(cursor(2026, 10).add_months(-22), cursor(2024, 12));
+        assert_eq!(cursor(1, 1).previous_month(), cursor(0, 12));
+        assert_eq!(cursor(0, 1).previous_month(), cursor(-1, 12));
+    }
+
+    #[test]
+    fn year_navigation_keeps_the_month() {
+        assert_eq!(cursor(2024, 2).next_year(), cursor(2025, 2));
+        assert_eq!(cursor(2024, 2).previous_year(), cursor(2023, 2));
+    }
+
+    #[test]
+    fn months_of_year_run_from_january_to_december() {
+        let months = cursor(2026, 10).months_of_year();
+        assert_eq!(months[0], cursor(2026, 1));
+        assert_eq!(months[11], cursor(2026, 12));
+        assert!(months
+            .windows(2)
+            .all(|pair| pair[0].next_month() == pair[1]));
+    }
+
+    #[test]
+    fn navigation_saturates_at_the_limits() {
+        let last = cursor(MAX_YEAR, 12);
+        assert_eq!(last.next_month(), last);
+        assert_eq!(cursor(MAX_YEAR, 3).next_year(), cursor(MAX_YEAR, 12));
+        let first = cursor(MIN_YEAR, 1);
+        assert_eq!(first.previous_year(), first);
+        for week_start in [Weekday::Sun, Weekday::Mon, Weekday::Sat] {
+            assert_eq!(last.grid(week_start).month(), 12);
+            assert_eq!(first.grid(week_start).month(), 1);
+        }
+    }
+
+    #[test]
+    fn a_full_round_trip_returns_to_the_start() {
+        let start = cursor(2026, 10);
+        let mut current = start;
+        for _ in 0..1200 {
+            current = current.next_month();
+            assert!(MonthGrid::new(current.year(), current.month(), Weekday::Sun).is_some());
+        }
+        assert_eq!(current, cursor(2126, 10));
+        for _ in 0..1200 {
+            current = current.previous_month();
+        }
+        assert_eq!(current, start);
+    }
+}
@@ -0,0 +1,612 @@
+/*!
+ * Natural-language quick add, turning a line such as
+ * "Lunch with Sam tomorrow 12:30 for 1h at Cafe Luna" into an event draft.
+ *
+ * The parser picks the phrases it understands out of the text, matching
+ * words case-insensitively, and keeps the remaining words as the title:
+ *
+ * - dates: `today`, `tomorrow`, a weekday such as `friday` or `fri` (the
+ *   next such day, today included), `next friday` (the next one after
+ *   today), `next week`, `in 3 days`, `in 2 weeks` and YYYY-MM-DD, each
+ *   optionally preceded by `on`;
+ * - times: `12:30`, `9am`, `9:15 pm`, `noon` and `midnight`, optionally
+ *   preceded by `at` or `from`, which also allow a bare hour such as
+ *   `at 9`, and ranges such as `9-10am` or `12:30 to 14:00`;
+ * - durations: `for 1h`, `for 1h30m`, `for 90 min` or `for 2 hours`, and
+ *   `for 3 days` for events spanning several days;
+ * - recurrences: `daily`, `weekly`, `monthly`, `yearly`, `every day`,
+ *   `every 2 weeks`, `every weekday` and `every monday`;
+ * - a location: `@Cafe Luna` or `at Cafe Luna`, running up to the next
+ *   phrase that is understood.
+ *
+ * Only the first phrase of each kind counts; later ones stay in the title.
+ * Events without a time are all-day events, and their durations only count
+ * in whole days. Relative dates are resolved against the date passed in, so
+ * the result does not depend on the clock.
+ */
+
+use crate::draft::EventDraft;
+use crate::recurrence::{ByDay, Frequency, RecurrenceRule};
+use chrono::prelude::*;
+use chrono::{Days, Duration};
+
+/**
+ * The weekdays `every weekday` repeats on.
+ */
+const WORKING_DAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+/**
+ * A phrase understood by the parser.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Phrase {
+    Date(NaiveDate),
+    /** A start time with an optional end time. */
+    Time(NaiveTime, Option<NaiveTime>),
+    Duration(Duration),
+    /** A recurrence, with the weekday it starts on if it names one. */
+    Recurrence(RecurrenceRule, Option<Weekday>),
+}
+
+/**
+ * The phrases found so far.
+ */
+#[derive(Debug, Default)]
+struct Found {
+    date: Option<NaiveDate>,
+    time: Option<(NaiveTime, Option<NaiveTime>)>,
+    duration: Option<Duration>,
+    recurrence: Option<(RecurrenceRule, Option<Weekday>)>,
+}
+
+impl Found {
+    /**
+     * Function to take a phrase unless one of its kind was found before.
+     *
+     * Returns:
+     * - Whether the phrase was taken.
+     */
+    fn take(&mut self, phrase: Phrase) -> bool {
+        fn fill<T>(slot: &mut Option<T>, value: T) -> bool {
+            let empty = slot.is_none();
+            if empty {
+                *slot = Some(value);
+            }
+            empty
+        }
+        match phrase {
+            Phrase::Date(date) => fill(&mut self.date, date),
+            Phrase::Time(start, end) => fill(&mut self.time, (start, end)),
+            Phrase::Duration(duration) => fill(&mut self.duration, duration),
+            Phrase::Recurrence(rule, weekday) => fill(&mut self.recurrence, (rule, weekday)),
+        }
+    }
+}
+
+/**
+ * Function to parse a quick-add text.
+ *
+ * Parameters:
+ * - `text`: The text as typed.
+ * - `today`: The date relative dates are resolved against.
+ *
+ * Returns:
+ * - The draft of the event; validating it reports problems such as an
+ *   empty title.
+ */
+pub fn parse(text: &str, today: NaiveDate) -> EventDraft {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let lower: Vec<String> = words
+        .iter()
+        .map(|word| word.trim_end_matches([',', '.', ';']).to_lowercase())
+        .collect();
+
+    let mut found = Found::default();
+    let mut title: Vec<&str> = Vec::new();
+    let mut location: Vec<&str> = Vec::new();
+    let mut in_location = false;
+    let mut i = 0;
+    while i < words.len() {
+        if let Some((count, phrase)) = phrase(&lower[i..], today) {
+            if found.take(phrase) {
+                in_location = false;
+                i += count;
+                continue;
+            }
+        }
+        let word = words[i];
+        i += 1;
+        if in_location {
+            location.push(word);
+        } else if location.is_empty() && word.starts_with('@') {
+            in_location = true;
+            location.extend(Some(&word[1..]).filter(|rest| !rest.is_empty()));
+        } else if location.is_empty() && lower[i - 1] == "at" && i < words.len() {
+            in_location = true;
+        } else {
+            title.push(word);
+        }
+    }
+    draft(found, title.join(" "), location.join(" "), today)
+}
+
+/**
+ * Function to build the draft from the phrases found.
+ */
+fn draft(found: Found, title: String, location: String, today: NaiveDate) -> EventDraft {
+    let weekday = found.recurrence.as_ref().and_then(|(_, weekday)| *weekday);
+    let date = match (found.date, weekday) {
+        (Some(date), _) => date,
+        // "every monday" starts on the next Monday.
+        (None, Some(weekday)) => upcoming(today, weekday, true),
+        (None, None) => today,
+    };
+    let mut draft = EventDraft {
+        title,
+        location,
+        start_date: format_date(date),
+        ..EventDraft::default()
+    };
+    match found.time {
+        Some((start, end)) => {
+            let start = date.and_time(start);
+            let end = match (end, found.duration) {
+                (Some(end), _) => {
+                    let end = date.and_time(end);
+                    // "22:00 to 1:00" ends on the next day.
+                    Some(if end < start { end + Days::new(1) } else { end })
+                }
+                (None, Some(duration)) => start.checked_add_signed(duration),
+                (None, None) => None,
+            };
+            draft.start_time = start.format("%H:%M").to_string();
+            if let Some(end) = end {
+                draft.end_date = format_date(end.date());
+                draft.end_time = end.format("%H:%M").to_string();
+            }
+        }
+        None => {
+            let days = found
+                .duration
+                .map_or(1, |duration| duration.num_days().max(1));
+            let last = date.checked_add_signed(Duration::days(days - 1));
+            draft.end_date = format_date(last.unwrap_or(date));
+        }
+    }
+    if let Some((rule, _)) = found.recurrence {
+        draft.recurrence = rule.to_ical(found.time.is_none());
+    }
+    draft
+}
+
+/**
+ * Function to match a phrase at the start of `words`.
+ *
+ * Returns:
+ * - The number of words the phrase spans and the phrase.
+ */
+fn phrase(words: &[String], today: NaiveDate) -> Option<(usize, Phrase)> {
+    if let Some((count, date)) = date(words, today) {
+        return Some((count, Phrase::Date(date)));
+    }
+    if let Some((count, start, end)) = time(words) {
+        return Some((count, Phrase::Time(start, end)));
+    }
+    if let Some((count, duration)) = duration(words) {
+        return Some((count, Phrase::Duration(duration)));
+    }
+    recurrence(words).map(|(count, rule, weekday)| (count, Phrase::Recurrence(rule, weekday)))
+}
+
+fn date(words: &[String], today: NaiveDate) -> Option<(usize, NaiveDate)> {
+    let word = |i: usize| words.get(i).map(String::as_str);
+    match (word(0)?, word(1), word(2)) {
+        ("on", _, _) => date(&words[1..], today).map(|(count, date)| (count + 1, date)),
+        ("today", _, _) => Some((1, today)),
+        ("tomorrow", _, _) => Some((1, today.succ_opt()?)),
+        ("next", Some("week"), _) => Some((2, today.checked_add_days(Days::new(7))?)),
+        ("next", Some(day), _) => Some((2, upcoming(today, day.parse().ok()?, false))),
+        ("in", Some(amount), Some(unit)) => {
+            let amount: u64 = amount.parse().ok()?;
+            let days = match unit {
+                "day" | "days" => amount,
+                "week" | "weeks" => amount.checked_mul(7)?,
+                _ => return None,
+            };
+            Some((3, today.checked_add_days(Days::new(days))?))
+        }
+        (day, _, _) => match day.parse::<Weekday>() {
+            Ok(weekday) => Some((1, upcoming(today, weekday, true))),
+            Err(_) => NaiveDate::parse_from_str(day, "%Y-%m-%d")
+                .ok()
+                .map(|date| (1, date)),
+        },
+    }
+}
+
+/**
+ * Function to match a time or a time range, e.g. `at 9`, `12:30pm` or
+ * `9-10am`.
+ *
+ * Returns:
+ * - The number of words, the start time and the end time, if any.
+ */
+fn time(words: &[String]) -> Option<(usize, NaiveTime, Option<NaiveTime>)> {
+    // A bare hour is only taken for a time after "at" or "from".
+    let prefixed = matches!(words.first()?.as_str(), "at" | "from");
+    let skip = usize::from(prefixed);
+    // A range written as one word, e.g. "9-10am" or "9-10 am".
+    if let Some((first, second)) = words.get(skip)?.split_once('-') {
+        let mut rest = vec![second.to_string()];
+        rest.extend(words[skip + 1..].iter().cloned());
+        let (count, end) = clock(&rest, prefixed)?;
+        let (_, start) = clock(&[first.to_string()], true)?;
+        let (start, end) = meridiem_range(start, end);
+        return Some((skip + count, start.time()?, Some(end.time()?)));
+    }
+    let (mut count, start) = clock(&words[skip..], prefixed)?;
+    count += skip;
+    let end = match words.get(count).map(String::as_str) {
+        Some("-" | "to" | "until" | "till") => clock(&words[count + 1..], true),
+        _ => None,
+    };
+    match end {
+        Some((length, end)) => {
+            let (start, end) = meridiem_range(start, end);
+            Some((count + 1 + length, start.time()?, Some(end.time()?)))
+        }
+        None => Some((count, start.time()?, None)),
+    }
+}
+
+/**
+ * A time of day as written, before `am` and `pm` are applied.
+ */
+#[derive(Debug, Clone, Copy)]
+struct Clock {
+    hour: u32,
+    minute: u32,
+    /** `Some(true)` for pm, `Some(false)` for am. */
+    pm: Option<bool>,
+}
+
+impl Clock {
+    fn time(&self) -> Option<NaiveTime> {
+        let hour = match self.pm {
+            None if self.hour < 24 => self.hour,
+            Some(pm) if (1..=12).contains(&self.hour) => self.hour % 12 + if pm { 12 } else { 0 },
+            _ => return None,
+        };
+        NaiveTime::from_hms_opt(hour, self.minute, 0)
+    }
+}
+
+/**
+ * Function to apply the `am` or `pm` of the end of a range to its start,
+ * e.g. "9-11am", unless that would make the range end before it starts,
+ * e.g. "11-1pm".
+ */
+fn meridiem_range(mut start: Clock, end: Clock) -> (Clock, Clock) {
+    if start.pm.is_none() && start.hour <= 12 {
+        if let Some(pm) = end.pm {
+            start.pm = Some(pm);
+            if start.time() > end.time() {
+                start.pm = Some(!pm);
+            }
+        }
+    }
+    (start, end)
+}
+
+/**
+ * Function to match a single time of day, e.g. `12:30`, `9am`, `9 pm` or
+ * `noon`.
+ *
+ * Parameters:
+ * - `bare`: Whether an hour without minutes or `am`/`pm` is a time.
+ *
+ * Returns:
+ * - The number of words and the time.
+ */
+fn clock(words: &[String], bare: bool) -> Option<(usize, Clock)> {
+    let word = words.first()?.as_str();
+    let at = |hour| Clock {
+        hour,
+        minute: 0,
+        pm: None,
+    };
+    match word {
+        "noon" => return Some((1, at(12))),
+        "midnight" => return Some((1, at(0))),
+        _ => {}
+    }
+    let (digits, suffix) = match word.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(index) => word.split_at(index),
+        None => (word, ""),
+    };
+    let (hour, minute) = match digits.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, Some(minute)),
+        Some(_) => return None,
+        None => (digits, None),
+    };
+    let mut clock = at(hour.parse().ok()?);
+    clock.minute = minute.map_or(Some(0), |minute| minute.parse().ok())?;
+    let (count, suffix) = match (suffix, words.get(1).map(String::as_str)) {
+        ("", Some(next @ ("am" | "pm"))) => (2, next),
+        _ => (1, suffix),
+    };
+    clock.pm = match suffix {
+        "am" => Some(false),
+        "pm" => Some(true),
+        "" if minute.is_some() || bare => None,
+        _ => return None,
+    };
+    clock.time()?;
+    Some((count, clock))
+}
+
+/**
+ * Function to match a duration, e.g. `for 1h30m`, `for 90 min` or
+ * `for 3 days`.
+ */
+fn duration(words: &[String]) -> Option<(usize, Duration)> {
+    if words.first()? != "for" {
+        return None;
+    }
+    let amount = words.get(1)?;
+    if let Some(duration) = parse_amount(amount) {
+        return Some((2, duration));
+    }
+    let unit = words.get(2)?;
+    parse_amount(&format!("{amount}{unit}")).map(|duration| (3, duration))
+}
+
+/**
+ * Function to parse an amount of time written as numbers followed by units,
+ * e.g. `1h30m`, `90min` or `2hours`.
+ */
+fn parse_amount(text: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let letters = rest[digits..]
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .map_or(rest.len(), |end| digits + end);
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = match &rest[digits..letters] {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount)?,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(amount)?,
+            "d" | "day" | "days" => Duration::try_days(amount)?,
+            _ => return None,
+        };
+        total = total.checked_add(&unit)?;
+        rest = &rest[letters..];
+    }
+    (total > Duration::zero()).then_some(total)
+}
+
+/**
+ * Function to match a recurrence, e.g. `weekly`, `every 2 weeks` or
+ * `every monday`.
+ *
+ * Returns:
+ * - The number of words, the rule and the weekday it names, if any.
+ */
+fn recurrence(words: &[String]) -> Option<(usize, RecurrenceRule, Option<Weekday>)> {
+    let frequency = |unit: &str| match unit {
+        "day" | "days" | "daily" => Some(Frequency::Daily),
+        "week" | "weeks" | "weekly" => Some(Frequency::Weekly),
+        "month" | "months" | "monthly" => Some(Frequency::Monthly),
+        "year" | "years" | "yearly" | "annually" => Some(Frequency::Yearly),
+        _ => None,
+    };
+    let word = words.first()?.as_str();
+    if word != "every" {
+        let frequency = frequency(word).filter(|_| word.ends_with("ly"))?;
+        return Some((1, RecurrenceRule::new(frequency), None));
+    }
+    let next = words.get(1)?.as_str();
+    if next == "weekday" {
+        let mut rule = RecurrenceRule::new(Frequency::Weekly);
+        rule.by_day = WORKING_DAYS.into_iter().map(ByDay::every).collect();
+        return Some((2, rule, None));
+    }
+    if let Ok(weekday) = next.parse::<Weekday>() {
+        let mut rule = RecurrenceRule::new(Frequency::Weekly);
+        rule.by_day = vec![ByDay::every(weekday)];
+        return Some((2, rule, Some(weekday)));
+    }
+    if let Some(frequency) = frequency(next).filter(|_| !next.ends_with("ly")) {
+        return Some((2, RecurrenceRule::new(frequency), None));
+    }
+    let interval: u32 = next.parse().ok().filter(|interval| *interval > 0)?;
+    let mut rule = RecurrenceRule::new(frequency(words.get(2)?)?);
+    rule.interval = interval;
+    Some((3, rule, None))
+}
+
+/**
+ * Function to find the next date falling on a weekday.
+ *
+ * Parameters:
+ * - `today`: The date to start from.
+ * - `weekday`: The weekday to find.
+ * - `inclusive`: Whether `today` itself counts.
+ */
+fn upcoming(today: NaiveDate, weekday: Weekday, inclusive: bool) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    let days = if days == 0 && !inclusive { 7 } else { days };
+    today + Days::new(u64::from(days))
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventTime;
+    use crate::recurrence::Recurrence;
+
+    /** A Friday. */
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn times(text: &str) -> (String, EventTime) {
+        let event = parse(text, today()).to_event().unwrap();
+        (event.title, event.time)
+    }
+
+    fn all_day(day: u32) -> EventTime {
+        EventTime::all_day(at(day, 0, 0).date(), at(day, 0, 0).date()).unwrap()
+    }
+
+    fn timed(start: NaiveDateTime, end: NaiveDateTime) -> EventTime {
+        EventTime::timed(start, end).unwrap()
+    }
+
+    #[test]
+    fn parses_the_example() {
+        let event = parse("Lunch with Sam tomorrow 12:30 for 1h at Cafe Luna", today())
+            .to_event()
+            .unwrap();
+        assert_eq!(event.title, "Lunch with Sam");
+        assert_eq!(event.time, timed(at(17, 12, 30), at(17, 13, 30)));
+        assert_eq!(event.location.as_deref(), Some("Cafe Luna"));
+        assert_eq!(event.recurrence, None);
+    }
+
+    #[test]
+    fn resolves_relative_dates() {
+        assert_eq!(
+            times("Call mom today"),
+            ("Call mom".to_string(), all_day(16))
+        );
+        assert_eq!(times("Pay rent tomorrow").1, all_day(17));
+        // Today is a Friday.
+        assert_eq!(times("Drinks friday").1, all_day(16));
+        assert_eq!(times("Drinks next Friday").1, all_day(23));
+        assert_eq!(times("Dentist on Tue").1, all_day(20));
+        assert_eq!(times("Taxes in 3 days").1, all_day(19));
+        assert_eq!(times("Review in 2 weeks").1, all_day(30));
+        assert_eq!(times("Retro next week").1, all_day(23));
+        assert_eq!(times("Trip on 2026-10-25").1, all_day(25));
+    }
+
+    #[test]
+    fn reads_times_and_ranges() {
+        assert_eq!(
+            times("Standup 9am"),
+            ("Standup".to_string(), timed(at(16, 9, 0), at(16, 10, 0)))
+        );
+        assert_eq!(times("Call at 9").1, timed(at(16, 9, 0), at(16, 10, 0)));
+        assert_eq!(
+            times("Call 9:15 pm").1,
+            timed(at(16, 21, 15), at(16, 22, 15))
+        );
+        assert_eq!(times("Lunch noon").1, timed(at(16, 12, 0), at(16, 13, 0)));
+        assert_eq!(times("Launch 12am").1, timed(at(16, 0, 0), at(16, 1, 0)));
+        assert_eq!(times("Class 9-11am").1, timed(at(16, 9, 0), at(16, 11, 0)));
+        assert_eq!(times("Class 11-1pm").1, timed(at(16, 11, 0), at(16, 13, 0)));
+        assert_eq!(
+            times("Party from 22:00 to 1:00").1,
+            timed(at(16, 22, 0), at(17, 1, 0))
+        );
+        // Bare numbers are not times.
+        assert_eq!(times("Buy 3 apples").0, "Buy 3 apples");
+    }
+
+    #[test]
+    fn reads_durations() {
+        assert_eq!(
+            times("Workshop 10:00 for 1h30m").1,
+            timed(at(16, 10, 0), at(16, 11, 30))
+        );
+        assert_eq!(
+            times("Workshop 10:00 for 90 min").1,
+            timed(at(16, 10, 0), at(16, 11, 30))
+        );
+        assert_eq!(
+            times("Hike 8am for 2 hours").1,
+            timed(at(16, 8, 0), at(16, 10, 0))
+        );
+        assert_eq!(
+            times("Conference tomorrow for 3 days").1,
+            EventTime::all_day(at(17, 0, 0).date(), at(19, 0, 0).date()).unwrap()
+        );
+        assert_eq!(times("Waiting for Godot").0, "Waiting for Godot");
+    }
+
+    #[test]
+    fn reads_recurrences() {
+        let event = parse("Gym every Monday 7am", today()).to_event().unwrap();
+        assert_eq!(event.title, "Gym");
+        assert_eq!(event.time, timed(at(19, 7, 0), at(19, 8, 0)));
+        let mut rule = RecurrenceRule::new(Frequency::Weekly);
+        rule.by_day = vec![ByDay::every(Weekday::Mon)];
+        assert_eq!(event.recurrence, Some(Recurrence::from_rule(rule)));
+
+        let rule = |text: &str| {
+            parse(text, today())
+                .to_event()
+                .unwrap()
+                .recurrence
+                .and_then(|recurrence| recurrence.rule)
+                .unwrap()
+        };
+        assert_eq!(rule("Standup every weekday 9:00").by_day.len(), 5);
+        assert_eq!(rule("Backup daily").frequency, Frequency::Daily);
+        assert_eq!(rule("Rent every month").frequency, Frequency::Monthly);
+        let biweekly = rule("Sprint review every 2 weeks");
+        assert_eq!(
+            (biweekly.frequency, biweekly.interval),
+            (Frequency::Weekly, 2)
+        );
+    }
+
+    #[test]
+    fn reads_locations() {
+        let draft = parse("Coffee @Cafe Luna tomorrow with Sam", today());
+        assert_eq!(draft.title, "Coffee with Sam");
+        assert_eq!(draft.location, "Cafe Luna");
+        assert_eq!(draft.start_date, "2026-10-17");
+
+        let draft = parse("Dinner at 7pm at Sam's place", today());
+        assert_eq!(draft.title, "Dinner");
+        assert_eq!(draft.location, "Sam's place");
+        assert_eq!(draft.start_time, "19:00");
+    }
+
+    #[test]
+    fn keeps_what_it_does_not_understand_in_the_title() {
+        let draft = parse("Plan monday meeting tomorrow", today());
+        assert_eq!(draft.title, "Plan meeting tomorrow");
+        assert_eq!(draft.start_date, "2026-10-19");
+
+        // A trailing "at" or "from" is part of the title.
+        assert_eq!(parse("Lunch at", today()).title, "Lunch at");
+        assert_eq!(parse("Party from", today()).title, "Party from");
+        assert_eq!(parse("at", today()).title, "at");
+
+        assert_eq!(
+            parse("tomorrow 9am", today()).to_event(),
+            Err(crate::draft::DraftError::EmptyTitle)
+        );
+    }
+}
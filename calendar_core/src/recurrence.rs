@@ -0,0 +1,1065 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/**
+ * How often a recurrence rule repeats (the `FREQ` rule part).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    /**
+     * Returns the number of periods after which the calendar repeats itself:
+     * 400 Gregorian years are exactly 146097 days, 20871 weeks or 4800
+     * months, whatever the interval. A rule without an occurrence for that
+     * many periods never has one again.
+     */
+    fn cycle_periods(self) -> u32 {
+        match self {
+            Frequency::Daily => 146_097,
+            Frequency::Weekly => 20_871,
+            Frequency::Monthly => 4_800,
+            Frequency::Yearly => 400,
+        }
+    }
+}
+
+/**
+ * A `BYDAY` entry such as `MO` (every Monday), `2TU` (second Tuesday) or
+ * `-1FR` (last Friday). `nth` is zero for entries without an ordinal.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: i32,
+    pub weekday: Weekday,
+}
+
+impl ByDay {
+    pub fn every(weekday: Weekday) -> ByDay {
+        ByDay { nth: 0, weekday }
+    }
+
+    pub fn nth(nth: i32, weekday: Weekday) -> ByDay {
+        ByDay { nth, weekday }
+    }
+}
+
+/**
+ * A recurrence rule as described by the RRULE property of RFC 5545.
+ *
+ * Supported rule parts are FREQ, INTERVAL, COUNT, UNTIL, BYDAY, BYMONTHDAY,
+ * BYMONTH, BYSETPOS and WKST. `until` is compared with the wall-clock start
+ * of the occurrences and is inclusive.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+/**
+ * Error returned when an RRULE value cannot be parsed.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError(pub String);
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid recurrence rule: {}", self.0)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl RecurrenceRule {
+    /**
+     * Creates a rule repeating every period forever.
+     */
+    pub fn new(frequency: Frequency) -> RecurrenceRule {
+        RecurrenceRule {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        }
+    }
+
+    /**
+     * Formats the rule as an RRULE value.
+     *
+     * Parameters:
+     * - `all_day`: Whether the event is an all-day event, in which case
+     *   `UNTIL` is written as a DATE rather than a DATE-TIME.
+     */
+    pub fn to_ical(&self, all_day: bool) -> String {
+        let mut parts = vec![format!(
+            "FREQ={}",
+            match self.frequency {
+                Frequency::Daily => "DAILY",
+                Frequency::Weekly => "WEEKLY",
+                Frequency::Monthly => "MONTHLY",
+                Frequency::Yearly => "YEARLY",
+            }
+        )];
+        if self.interval > 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            if all_day {
+                parts.push(format!("UNTIL={}", until.format("%Y%m%d")));
+            } else {
+                parts.push(format!("UNTIL={}", until.format("%Y%m%dT%H%M%S")));
+            }
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|day| {
+                    let name = weekday_code(day.weekday);
+                    if day.nth == 0 {
+                        name.to_string()
+                    } else {
+                        format!("{}{}", day.nth, name)
+                    }
+                })
+                .collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        let join = |values: &[i32]| {
+            values
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        if !self.by_month_day.is_empty() {
+            parts.push(format!("BYMONTHDAY={}", join(&self.by_month_day)));
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<i32> = self.by_month.iter().map(|m| *m as i32).collect();
+            parts.push(format!("BYMONTH={}", join(&months)));
+        }
+        if !self.by_set_pos.is_empty() {
+            parts.push(format!("BYSETPOS={}", join(&self.by_set_pos)));
+        }
+        if self.week_start != Weekday::Mon {
+            parts.push(format!("WKST={}", weekday_code(self.week_start)));
+        }
+        parts.join(";")
+    }
+
+    /**
+     * Returns the occurrence starts generated by the rule, in order, beginning
+     * with `dtstart` itself. Generation stops after `horizon` even if the rule
+     * is unbounded.
+     */
+    pub fn iter(&self, dtstart: NaiveDateTime, horizon: NaiveDateTime) -> RuleIter<'_> {
+        RuleIter {
+            rule: self,
+            dtstart,
+            horizon,
+            period: 0,
+            pending: Vec::new(),
+            emitted: 0,
+            done: false,
+        }
+    }
+
+    /**
+     * Returns the occurrence starts generated by the rule from `from` on, in
+     * order, up to `horizon`.
+     *
+     * Unless the rule has a COUNT or BYSETPOS, the periods ending before
+     * `from` are skipped without being expanded, so a window far from
+     * `dtstart` costs as much as one close to it. The iterator may still
+     * return a few starts before `from` from the period containing it.
+     */
+    pub fn iter_from(
+        &self,
+        dtstart: NaiveDateTime,
+        from: NaiveDateTime,
+        horizon: NaiveDateTime,
+    ) -> RuleIter<'_> {
+        let mut iter = self.iter(dtstart, horizon);
+        if self.count.is_none() && self.by_set_pos.is_empty() && from > dtstart {
+            iter.period = self.period_of(dtstart.date(), from.date());
+            // DTSTART lies in a skipped period unless `from` is in the first.
+            iter.emitted = u32::from(iter.period > 0);
+        }
+        iter
+    }
+
+    /**
+     * Returns the index of the period containing `date`, counting the one
+     * containing `dtstart` as zero.
+     */
+    fn period_of(&self, dtstart: NaiveDate, date: NaiveDate) -> i64 {
+        let months = |date: NaiveDate| date.year() as i64 * 12 + date.month0() as i64;
+        let periods = match self.frequency {
+            Frequency::Daily => (date - dtstart).num_days(),
+            Frequency::Weekly => {
+                let offset = days_since(dtstart.weekday(), self.week_start);
+                let week = dtstart
+                    .checked_sub_signed(Duration::days(offset))
+                    .unwrap_or(dtstart);
+                (date - week).num_days().div_euclid(7)
+            }
+            Frequency::Monthly => months(date) - months(dtstart),
+            Frequency::Yearly => date.year() as i64 - dtstart.year() as i64,
+        };
+        periods.div_euclid(self.interval.max(1) as i64).max(0)
+    }
+
+    /**
+     * Returns whether `date` passes the BYMONTH, BYMONTHDAY and BYDAY filters
+     * that limit daily and weekly rules.
+     */
+    fn matches_limits(&self, date: NaiveDate) -> bool {
+        (self.by_month.is_empty() || self.by_month.contains(&date.month()))
+            && (self.by_month_day.is_empty()
+                || self
+                    .by_month_day
+                    .iter()
+                    .any(|day| resolve_month_day(date.year(), date.month(), *day) == Some(date)))
+            && (self.by_day.is_empty()
+                || self.by_day.iter().any(|day| day.weekday == date.weekday()))
+    }
+
+    /**
+     * Returns the candidate dates of the `index`-th period, unsorted and
+     * before BYSETPOS is applied, or `None` once the periods leave the range
+     * of representable dates.
+     */
+    fn period_dates(&self, dtstart: NaiveDate, index: i64) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = index.checked_mul(self.interval.max(1) as i64)?;
+        match self.frequency {
+            Frequency::Daily => {
+                let date = dtstart.checked_add_signed(Duration::try_days(step)?)?;
+                let dates = if self.matches_limits(date) {
+                    vec![date]
+                } else {
+                    vec![]
+                };
+                Some((date, dates))
+            }
+            Frequency::Weekly => {
+                let offset = days_since(dtstart.weekday(), self.week_start);
+                let week = dtstart
+                    .checked_sub_signed(Duration::days(offset))?
+                    .checked_add_signed(Duration::try_weeks(step)?)?;
+                let dates = week
+                    .iter_days()
+                    .take(7)
+                    .filter(|date| {
+                        if self.by_day.is_empty() {
+                            date.weekday() == dtstart.weekday()
+                        } else {
+                            self.by_day.iter().any(|day| day.weekday == date.weekday())
+                        }
+                    })
+                    .filter(|date| {
+                        (self.by_month.is_empty() || self.by_month.contains(&date.month()))
+                            && (self.by_month_day.is_empty()
+                                || self.by_month_day.iter().any(|day| {
+                                    resolve_month_day(date.year(), date.month(), *day)
+                                        == Some(*date)
+                                }))
+                    })
+                    .collect();
+                Some((week, dates))
+            }
+            Frequency::Monthly => {
+                let months =
+                    (dtstart.year() as i64 * 12 + dtstart.month0() as i64).checked_add(step)?;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = months.rem_euclid(12) as u32 + 1;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let dates = if self.by_month.is_empty() || self.by_month.contains(&month) {
+                    self.month_dates(year, month, dtstart.day())
+                } else {
+                    Vec::new()
+                };
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(dtstart.year() as i64 + step).ok()?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let dates = if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    let last = NaiveDate::from_ymd_opt(year, 12, 31)?;
+                    weekdays_in(first, last, &self.by_day)
+                } else {
+                    let months: Vec<u32> = if !self.by_month.is_empty() {
+                        self.by_month.clone()
+                    } else if !self.by_month_day.is_empty() {
+                        (1..=12).collect()
+                    } else {
+                        vec![dtstart.month()]
+                    };
+                    months
+                        .into_iter()
+                        .flat_map(|month| self.month_dates(year, month, dtstart.day()))
+                        .collect()
+                };
+                Some((first, dates))
+            }
+        }
+    }
+
+    /**
+     * Returns the days of a month selected by BYMONTHDAY and BYDAY, or the
+     * day of the month of DTSTART if neither is given.
+     */
+    fn month_dates(&self, year: i32, month: u32, default_day: u32) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return Vec::new();
+        };
+        let last = NaiveDate::from_ymd_opt(
+            year,
+            month,
+            crate::grid::days_in_month(year, month).unwrap_or(28),
+        )
+        .unwrap_or(first);
+
+        let by_month_day: Vec<NaiveDate> = self
+            .by_month_day
+            .iter()
+            .filter_map(|day| resolve_month_day(year, month, *day))
+            .collect();
+        let by_day = weekdays_in(first, last, &self.by_day);
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => NaiveDate::from_ymd_opt(year, month, default_day)
+                .into_iter()
+                .collect(),
+            (false, true) => by_month_day,
+            (true, false) => by_day,
+            (false, false) => by_month_day
+                .into_iter()
+                .filter(|date| by_day.contains(date))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_ical(false))
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RuleError;
+
+    /**
+     * Parses an RRULE value such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`.
+     */
+    fn from_str(value: &str) -> Result<RecurrenceRule, RuleError> {
+        let mut frequency = None;
+        let mut rule = RecurrenceRule::new(Frequency::Daily);
+
+        for part in value.trim().split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| RuleError(format!("expected NAME=VALUE, found {part:?}")))?;
+            let invalid = || RuleError(format!("invalid {name} value {value:?}"));
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(RuleError(format!("unsupported frequency {value:?}"))),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?
+                }
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| invalid())?),
+                "UNTIL" => rule.until = Some(parse_until(value).ok_or_else(invalid)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(parse_by_day)
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day =
+                        parse_list(value, |n| n != 0 && n.abs() <= 31).ok_or_else(invalid)?
+                }
+                "BYMONTH" => {
+                    rule.by_month = parse_list(value, |n| (1..=12).contains(&n))
+                        .ok_or_else(invalid)?
+                        .into_iter()
+                        .map(|n| n as u32)
+                        .collect()
+                }
+                "BYSETPOS" => {
+                    rule.by_set_pos =
+                        parse_list(value, |n| n != 0 && n.abs() <= 366).ok_or_else(invalid)?
+                }
+                "WKST" => rule.week_start = parse_weekday(value).ok_or_else(invalid)?,
+                other => return Err(RuleError(format!("unsupported rule part {other}"))),
+            }
+        }
+
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(RuleError(
+                "COUNT and UNTIL are mutually exclusive".to_string(),
+            ));
+        }
+        rule.frequency = frequency.ok_or_else(|| RuleError("missing FREQ".to_string()))?;
+        Ok(rule)
+    }
+}
+
+/**
+ * Iterator over the occurrence starts of a rule, see `RecurrenceRule::iter`.
+ */
+pub struct RuleIter<'a> {
+    rule: &'a RecurrenceRule,
+    dtstart: NaiveDateTime,
+    horizon: NaiveDateTime,
+    period: i64,
+    pending: Vec<NaiveDateTime>,
+    emitted: u32,
+    done: bool,
+}
+
+impl Iterator for RuleIter<'_> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        loop {
+            if self.done || self.rule.count.is_some_and(|count| self.emitted >= count) {
+                return None;
+            }
+
+            // DTSTART is always the first occurrence.
+            let next = if self.emitted == 0 {
+                Some(self.dtstart)
+            } else if !self.pending.is_empty() {
+                Some(self.pending.remove(0))
+            } else {
+                self.fill();
+                continue;
+            };
+
+            let next = next?;
+            if self.rule.until.is_some_and(|until| next > until) || next > self.horizon {
+                self.done = true;
+                return None;
+            }
+            self.emitted += 1;
+            return Some(next);
+        }
+    }
+}
+
+impl RuleIter<'_> {
+    /**
+     * Computes the occurrences of the next period that yields any, giving up
+     * once the calendar has gone through a whole cycle without one.
+     */
+    fn fill(&mut self) {
+        let mut empty_periods = 0;
+        while self.pending.is_empty() && !self.done {
+            if empty_periods == self.rule.frequency.cycle_periods() {
+                self.done = true;
+                return;
+            }
+            empty_periods += 1;
+            let Some((period_start, mut dates)) =
+                self.rule.period_dates(self.dtstart.date(), self.period)
+            else {
+                self.done = true;
+                return;
+            };
+            self.period += 1;
+
+            if period_start > self.horizon.date()
+                || self
+                    .rule
+                    .until
+                    .is_some_and(|until| period_start > until.date())
+            {
+                self.done = true;
+                return;
+            }
+
+            dates.sort();
+            dates.dedup();
+            if !self.rule.by_set_pos.is_empty() {
+                dates = select_positions(&dates, &self.rule.by_set_pos);
+            }
+            self.pending = dates
+                .into_iter()
+                .map(|date| date.and_time(self.dtstart.time()))
+                .filter(|start| *start > self.dtstart)
+                .collect();
+        }
+    }
+}
+
+/**
+ * The recurrence of an event: a rule plus explicit additions (RDATE) and
+ * exclusions (EXDATE), both identified by the start of the occurrence.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Recurrence {
+    pub rule: Option<RecurrenceRule>,
+    pub exdates: Vec<NaiveDateTime>,
+    pub rdates: Vec<NaiveDateTime>,
+}
+
+impl Recurrence {
+    pub fn from_rule(rule: RecurrenceRule) -> Recurrence {
+        Recurrence {
+            rule: Some(rule),
+            ..Recurrence::default()
+        }
+    }
+
+    /**
+     * Function to expand the recurrence within a window.
+     *
+     * Only the occurrences starting between `from` and `to` (inclusive) are
+     * materialized, so unbounded rules can be displayed month by month.
+     *
+     * Parameters:
+     * - `dtstart`: The start of the first occurrence.
+     * - `from`: The earliest occurrence start to return.
+     * - `to`: The latest occurrence start to return.
+     *
+     * Returns:
+     * - The occurrence starts, sorted and without excluded dates.
+     */
+    pub fn occurrences(
+        &self,
+        dtstart: NaiveDateTime,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        self.iter_between(dtstart, from, to).collect()
+    }
+
+    /**
+     * Returns the same occurrence starts as `occurrences`, expanding the
+     * rule only as far as they are consumed, e.g. to find the first one.
+     */
+    pub fn iter_between(
+        &self,
+        dtstart: NaiveDateTime,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        let generated: Box<dyn Iterator<Item = NaiveDateTime> + '_> = match &self.rule {
+            Some(rule) => Box::new(rule.iter_from(dtstart, from, to)),
+            None => Box::new(std::iter::once(dtstart)),
+        };
+        let mut generated = generated
+            .skip_while(move |start| *start < from)
+            .take_while(move |start| *start <= to)
+            .peekable();
+        let added: BTreeSet<NaiveDateTime> = self
+            .rdates
+            .iter()
+            .copied()
+            .filter(|start| (from..=to).contains(start))
+            .collect();
+        let mut added = added.into_iter().peekable();
+
+        // Merges both sorted sequences, dropping duplicates and exclusions.
+        std::iter::from_fn(move || loop {
+            let next = match (generated.peek(), added.peek()) {
+                (Some(rule), Some(date)) if date < rule => added.next(),
+                (Some(rule), Some(date)) if date == rule => {
+                    added.next();
+                    generated.next()
+                }
+                (Some(_), _) => generated.next(),
+                (None, _) => added.next(),
+            }?;
+            if !self.exdates.contains(&next) {
+                return Some(next);
+            }
+        })
+    }
+
+    /**
+     * Counts the occurrences generated by the rule before `start`, ignoring
+     * RDATE and EXDATE as COUNT does.
+     */
+    pub fn count_before(&self, dtstart: NaiveDateTime, start: NaiveDateTime) -> u32 {
+        match &self.rule {
+            Some(rule) => rule
+                .iter(dtstart, start)
+                .filter(|other| *other < start)
+                .count() as u32,
+            None => 0,
+        }
+    }
+}
+
+/**
+ * Returns the ordinal number of days from `start` to `weekday` going forward.
+ */
+fn days_since(weekday: Weekday, start: Weekday) -> i64 {
+    (weekday.num_days_from_monday() as i64 - start.num_days_from_monday() as i64).rem_euclid(7)
+}
+
+/**
+ * Resolves a BYMONTHDAY value, negative values counting from the end of the month.
+ */
+fn resolve_month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let length = crate::grid::days_in_month(year, month)? as i32;
+    let day = if day < 0 { length + day + 1 } else { day };
+    if (1..=length).contains(&day) {
+        NaiveDate::from_ymd_opt(year, month, day as u32)
+    } else {
+        None
+    }
+}
+
+/**
+ * Returns the days between `first` and `last` matching any of the BYDAY
+ * entries, ordinals counting within that span.
+ */
+fn weekdays_in(first: NaiveDate, last: NaiveDate, by_day: &[ByDay]) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    for entry in by_day {
+        let matching: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .filter(|date| date.weekday() == entry.weekday)
+            .collect();
+        match entry.nth {
+            0 => dates.extend(matching),
+            nth => {
+                if let Some(date) = select_positions(&matching, &[nth]).first() {
+                    dates.push(*date);
+                }
+            }
+        }
+    }
+    dates
+}
+
+/**
+ * Picks the 1-based positions from a sorted set, negative positions counting from the end.
+ */
+fn select_positions(dates: &[NaiveDate], positions: &[i32]) -> Vec<NaiveDate> {
+    let len = dates.len() as i32;
+    let mut selected: Vec<NaiveDate> = positions
+        .iter()
+        .filter_map(|pos| {
+            let index = if *pos < 0 { len + pos } else { pos - 1 };
+            (0..len).contains(&index).then(|| dates[index as usize])
+        })
+        .collect();
+    selected.sort();
+    selected.dedup();
+    selected
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn parse_by_day(value: &str) -> Option<ByDay> {
+    let split = value.len().checked_sub(2)?;
+    let weekday = parse_weekday(value.get(split..)?)?;
+    let nth = match &value[..split] {
+        "" => 0,
+        ordinal => {
+            let nth: i32 = ordinal.strip_prefix('+').unwrap_or(ordinal).parse().ok()?;
+            if nth == 0 || nth.abs() > 53 {
+                return None;
+            }
+            nth
+        }
+    };
+    Some(ByDay { nth, weekday })
+}
+
+fn parse_list(value: &str, valid: impl Fn(i32) -> bool) -> Option<Vec<i32>> {
+    value
+        .split(',')
+        .map(|item| item.trim().parse().ok().filter(|n| valid(*n)))
+        .collect()
+}
+
+/**
+ * Parses an UNTIL value. UTC values are taken as wall-clock times.
+ */
+fn parse_until(value: &str) -> Option<NaiveDateTime> {
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    if value.len() == 8 {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(|date| date.and_time(NaiveTime::MIN))
+    } else {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn rule(value: &str) -> RecurrenceRule {
+        value.parse().unwrap()
+    }
+
+    /**
+     * Returns the dates of the occurrences of `rule` starting at `dtstart`, up to the end of 2030.
+     */
+    fn dates(value: &str, dtstart: NaiveDate) -> Vec<NaiveDate> {
+        let horizon = at(date(2030, 12, 31), 23, 59);
+        rule(value)
+            .iter(at(dtstart, 9, 0), horizon)
+            .map(|start| start.date())
+            .collect()
+    }
+
+    #[test]
+    fn parses_and_formats_rules() {
+        let text = "FREQ=MONTHLY;INTERVAL=2;COUNT=10;BYDAY=1MO,-1FR,WE;BYMONTHDAY=-1;BYMONTH=1,7;BYSETPOS=-1;WKST=SU";
+        let parsed = rule(text);
+        assert_eq!(parsed.frequency, Frequency::Monthly);
+        assert_eq!(parsed.by_day[1], ByDay::nth(-1, Weekday::Fri));
+        assert_eq!(parsed.to_string(), text);
+
+        let until = rule("FREQ=DAILY;UNTIL=20261031");
+        assert_eq!(until.until, Some(at(date(2026, 10, 31), 0, 0)));
+        assert_eq!(until.to_ical(true), "FREQ=DAILY;UNTIL=20261031");
+        assert_eq!(until.to_ical(false), "FREQ=DAILY;UNTIL=20261031T000000");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for value in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261031",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=MONTHLY;BYDAY=0MO",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=YEARLY;BYMONTH=13",
+            "FREQ=DAILY;BYHOUR=9",
+        ] {
+            assert!(value.parse::<RecurrenceRule>().is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn daily_with_interval_and_count() {
+        assert_eq!(
+            dates("FREQ=DAILY;INTERVAL=2;COUNT=4", date(2026, 10, 30)),
+            [
+                date(2026, 10, 30),
+                date(2026, 11, 1),
+                date(2026, 11, 3),
+                date(2026, 11, 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_on_several_days_until() {
+        // Weekly standup on Monday and Wednesday, starting on a Wednesday.
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20261021T090000",
+                date(2026, 10, 7)
+            ),
+            [
+                date(2026, 10, 7),
+                date(2026, 10, 12),
+                date(2026, 10, 14),
+                date(2026, 10, 19),
+                date(2026, 10, 21)
+            ]
+        );
+    }
+
+    #[test]
+    fn biweekly_respects_week_start() {
+        let monday_start = dates(
+            "FREQ=WEEKLY;INTERVAL=2;COUNT=4;BYDAY=TU,SU",
+            date(1997, 8, 5),
+        );
+        let sunday_start = dates(
+            "FREQ=WEEKLY;INTERVAL=2;COUNT=4;BYDAY=TU,SU;WKST=SU",
+            date(1997, 8, 5),
+        );
+        // The examples from RFC 5545, section 3.8.5.3.
+        assert_eq!(
+            monday_start,
+            [
+                date(1997, 8, 5),
+                date(1997, 8, 10),
+                date(1997, 8, 19),
+                date(1997, 8, 24)
+            ]
+        );
+        assert_eq!(
+            sunday_start,
+            [
+                date(1997, 8, 5),
+                date(1997, 8, 17),
+                date(1997, 8, 19),
+                date(1997, 8, 31)
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_by_month_day_skips_short_months() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;COUNT=4", date(2026, 1, 31)),
+            [
+                date(2026, 1, 31),
+                date(2026, 3, 31),
+                date(2026, 5, 31),
+                date(2026, 7, 31)
+            ]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1;COUNT=3", date(2026, 1, 31)),
+            [date(2026, 1, 31), date(2026, 2, 28), date(2026, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn monthly_by_nth_weekday() {
+        // Monthly review on the last Friday.
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR;COUNT=3", date(2026, 10, 30)),
+            [date(2026, 10, 30), date(2026, 11, 27), date(2026, 12, 25)]
+        );
+        // Friday the 13th.
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13;COUNT=3",
+                date(2026, 2, 13)
+            ),
+            [date(2026, 2, 13), date(2026, 3, 13), date(2026, 11, 13)]
+        );
+    }
+
+    #[test]
+    fn monthly_by_set_pos() {
+        // Last working day of the month.
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3",
+                date(2026, 10, 30)
+            ),
+            [date(2026, 10, 30), date(2026, 11, 30), date(2026, 12, 31)]
+        );
+    }
+
+    #[test]
+    fn yearly_rules() {
+        // Thanksgiving.
+        assert_eq!(
+            dates(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH;COUNT=3",
+                date(2026, 11, 26)
+            ),
+            [date(2026, 11, 26), date(2027, 11, 25), date(2028, 11, 23)]
+        );
+        // Leap days only exist every four years.
+        assert_eq!(
+            dates("FREQ=YEARLY;COUNT=2", date(2024, 2, 29)),
+            [date(2024, 2, 29), date(2028, 2, 29)]
+        );
+        // The 20th Monday of the year.
+        assert_eq!(
+            dates("FREQ=YEARLY;BYDAY=20MO;COUNT=2", date(2026, 5, 18)),
+            [date(2026, 5, 18), date(2027, 5, 17)]
+        );
+    }
+
+    #[test]
+    fn unbounded_rules_stop_at_the_horizon() {
+        let starts: Vec<_> = rule("FREQ=DAILY")
+            .iter(at(date(2026, 10, 1), 9, 0), at(date(2026, 10, 31), 0, 0))
+            .collect();
+        assert_eq!(starts.len(), 30);
+
+        let never: Vec<_> = rule("FREQ=MONTHLY;BYMONTH=2;BYMONTHDAY=30")
+            .iter(at(date(2026, 1, 30), 9, 0), at(date(2100, 1, 1), 0, 0))
+            .collect();
+        assert_eq!(never, [at(date(2026, 1, 30), 9, 0)]);
+    }
+
+    #[test]
+    fn windows_far_from_dtstart_skip_the_periods_before_them() {
+        let dtstart = at(date(2026, 1, 31), 9, 0);
+        for value in [
+            "FREQ=DAILY;INTERVAL=3",
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,SU;WKST=SU",
+            "FREQ=MONTHLY;BYMONTHDAY=-1",
+            "FREQ=MONTHLY;INTERVAL=5",
+            "FREQ=YEARLY;INTERVAL=2;BYMONTH=2,11;BYDAY=1MO",
+            "FREQ=DAILY;UNTIL=20290301T090000",
+        ] {
+            let rule = rule(value);
+            let from = at(date(2028, 2, 12), 0, 0);
+            let to = at(date(2029, 6, 30), 0, 0);
+            let skipped: Vec<_> = rule
+                .iter_from(dtstart, from, to)
+                .filter(|start| *start >= from)
+                .collect();
+            let all: Vec<_> = rule
+                .iter(dtstart, to)
+                .filter(|start| *start >= from)
+                .collect();
+            assert_eq!(skipped, all, "{value}");
+        }
+
+        // Expanding every day of more than 200,000 years would take a while.
+        let recurrence = Recurrence::from_rule(rule("FREQ=DAILY"));
+        let from = at(date(250_000, 3, 1), 0, 0);
+        let starts = recurrence.occurrences(dtstart, from, at(date(250_000, 3, 3), 23, 59));
+        assert_eq!(starts.len(), 3);
+        let first = recurrence
+            .iter_between(dtstart, from, NaiveDateTime::MAX)
+            .next();
+        assert_eq!(first, Some(at(date(250_000, 3, 1), 9, 0)));
+    }
+
+    #[test]
+    fn rules_without_further_occurrences_stop_searching() {
+        let dtstart = at(date(2026, 1, 31), 9, 0);
+        let never = rule("FREQ=DAILY;BYMONTH=2;BYMONTHDAY=30");
+        assert_eq!(never.iter(dtstart, NaiveDateTime::MAX).count(), 1);
+        let recurrence = Recurrence::from_rule(never);
+        let from = at(date(2026, 11, 1), 0, 0);
+        let next = recurrence
+            .iter_between(dtstart, from, NaiveDateTime::MAX)
+            .next();
+        assert_eq!(next, None);
+
+        // Leap days skip 2100, eight years apart.
+        let leap_day = rule("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29");
+        let starts: Vec<_> = leap_day
+            .iter(at(date(2096, 2, 29), 9, 0), NaiveDateTime::MAX)
+            .take(2)
+            .collect();
+        assert_eq!(starts[1], at(date(2104, 2, 29), 9, 0));
+        let daily = rule("FREQ=DAILY;BYMONTH=2;BYMONTHDAY=29");
+        let starts: Vec<_> = daily
+            .iter(at(date(2096, 2, 29), 9, 0), NaiveDateTime::MAX)
+            .take(2)
+            .collect();
+        assert_eq!(starts[1], at(date(2104, 2, 29), 9, 0));
+    }
+
+    #[test]
+    fn recurrence_applies_exdates_and_rdates_within_the_window() {
+        let dtstart = at(date(2026, 10, 5), 9, 0);
+        let recurrence = Recurrence {
+            rule: Some(rule("FREQ=WEEKLY")),
+            exdates: vec![at(date(2026, 10, 12), 9, 0)],
+            rdates: vec![at(date(2026, 10, 14), 15, 0), at(date(2026, 12, 1), 9, 0)],
+        };
+
+        let starts = recurrence.occurrences(
+            dtstart,
+            at(date(2026, 10, 6), 0, 0),
+            at(date(2026, 10, 31), 23, 59),
+        );
+        assert_eq!(
+            starts,
+            [
+                at(date(2026, 10, 14), 15, 0),
+                at(date(2026, 10, 19), 9, 0),
+                at(date(2026, 10, 26), 9, 0)
+            ]
+        );
+        assert_eq!(
+            recurrence.count_before(dtstart, at(date(2026, 10, 19), 9, 0)),
+            2
+        );
+        // The first occurrence skips the EXDATE and picks up the RDATE.
+        assert_eq!(
+            recurrence
+                .iter_between(dtstart, at(date(2026, 10, 6), 0, 0), NaiveDateTime::MAX)
+                .next(),
+            Some(at(date(2026, 10, 14), 15, 0))
+        );
+    }
+
+    #[test]
+    fn rdate_only_recurrences_keep_dtstart() {
+        let dtstart = at(date(2026, 10, 5), 9, 0);
+        let recurrence = Recurrence {
+            rdates: vec![at(date(2026, 10, 7), 9, 0)],
+            ..Recurrence::default()
+        };
+        let starts = recurrence.occurrences(dtstart, dtstart, at(date(2026, 10, 31), 0, 0));
+        assert_eq!(starts, [dtstart, at(date(2026, 10, 7), 9, 0)]);
+    }
+}
@@ -0,0 +1,521 @@
+/*!
+ * Reminders of events and the scheduler deciding when they are due.
+ *
+ * Reminders are modeled on the `TRIGGER` of iCalendar `VALARM` components:
+ * either an offset from the start of every occurrence, such as
+ * `TRIGGER:-PT15M` for "15 minutes before", or a fixed point in time. A
+ * reminder "1 day before at 9:00" is stored as the offset from the start of
+ * the event to 9:00 on the previous day, like other calendar applications do.
+ *
+ * The scheduler never reads the clock itself; every call is given the current
+ * time, so that the caller decides where "now" comes from.
+ */
+
+use crate::event::{EventId, EventStore};
+use crate::storage::{write_atomically, StorageError};
+use chrono::prelude::*;
+use chrono::{Days, Duration};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/**
+ * Number of days reminders missed while the application was closed are still
+ * shown for when it starts again.
+ */
+pub const CATCH_UP_DAYS: i64 = 7;
+
+/**
+ * When a reminder of an event goes off.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reminder {
+    /** An offset from the start of each occurrence, negative before it. */
+    Relative(Duration),
+    /** A fixed date and time, shown once even for recurring events. */
+    Absolute(NaiveDateTime),
+}
+
+impl Reminder {
+    /**
+     * Creates a reminder the given number of minutes before the start.
+     */
+    pub fn minutes_before(minutes: i64) -> Reminder {
+        Reminder::Relative(-Duration::minutes(minutes))
+    }
+
+    /**
+     * Function to create a reminder at a time of day some days before an
+     * event, e.g. "1 day before at 9:00".
+     *
+     * Parameters:
+     * - `days`: The number of days before the start date.
+     * - `time`: The time of day of the reminder.
+     * - `start`: The start of the event.
+     *
+     * Returns:
+     * - The reminder, or `None` if the date is out of range.
+     */
+    pub fn days_before_at(days: u64, time: NaiveTime, start: NaiveDateTime) -> Option<Reminder> {
+        let due = start
+            .date()
+            .checked_sub_days(Days::new(days))?
+            .and_time(time);
+        Some(Reminder::Relative(due - start))
+    }
+
+    /**
+     * Returns when the reminder of an occurrence starting at `start` is due.
+     */
+    pub fn due(&self, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        match *self {
+            Reminder::Relative(offset) => start.checked_add_signed(offset),
+            Reminder::Absolute(time) => Some(time),
+        }
+    }
+}
+
+/**
+ * A reminder that went off and has not been dismissed yet.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: EventId,
+    /** The start of the occurrence the reminder is for. */
+    pub start: NaiveDateTime,
+    /** When the reminder went off, or is to go off again after snoozing. */
+    pub due: NaiveDateTime,
+}
+
+/**
+ * What the scheduler keeps between runs of the application, see
+ * `Scheduler::new`.
+ *
+ * The state is written whenever reminders fire, so it lives in a file of its
+ * own in the data directory rather than with the settings the user edits.
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderState {
+    /** Local time up to which reminders were shown; missed ones after it are shown on start. */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked: Option<NaiveDateTime>,
+    /** Reminders that went off or were snoozed and have not been dismissed yet. */
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<Alert>,
+}
+
+impl ReminderState {
+    /**
+     * Returns the path of the state file in the user's data directory, i.e.
+     * `$XDG_DATA_HOME/idk2/reminders.json` on Linux.
+     */
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("idk2").join("reminders.json"))
+    }
+
+    /**
+     * Loads the state stored at `path`.
+     *
+     * Returns:
+     * - The stored state, or an empty one if nothing has been saved yet.
+     */
+    pub fn load(path: &Path) -> Result<ReminderState, StorageError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(ReminderState::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /**
+     * Atomically replaces the state stored at `path`.
+     */
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        let content = serde_json::to_vec_pretty(self)?;
+        write_atomically(path, &content, None)?;
+        Ok(())
+    }
+}
+
+/**
+ * Keeps track of which reminders have gone off.
+ *
+ * The scheduler is polled regularly with the current time and fires every
+ * reminder that became due since the previous poll. Fired reminders stay
+ * active until they are dismissed or snoozed; snoozed ones fire again later.
+ */
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    // Reminders due up to this time have been fired.
+    checked: NaiveDateTime,
+    active: Vec<Alert>,
+    snoozed: Vec<Alert>,
+}
+
+impl Scheduler {
+    /**
+     * Function to create a scheduler.
+     *
+     * Parameters:
+     * - `since`: The time reminders were last checked, e.g. when the
+     *   application was closed. Reminders missed since then are fired by the
+     *   first poll, going back at most `CATCH_UP_DAYS`. `None` fires only
+     *   reminders due from `now` on.
+     * - `pending`: The alerts that were active or snoozed when the application
+     *   was closed, see `pending`. They fire again once they are due.
+     * - `now`: The current time.
+     */
+    pub fn new(since: Option<NaiveDateTime>, pending: Vec<Alert>, now: NaiveDateTime) -> Scheduler {
+        let earliest = now
+            .checked_sub_signed(Duration::days(CATCH_UP_DAYS))
+            .unwrap_or(now);
+        Scheduler {
+            checked: since.map_or(now, |since| since.clamp(earliest, now)),
+            active: Vec::new(),
+            snoozed: pending,
+        }
+    }
+
+    /**
+     * Returns the time up to which reminders have been fired.
+     */
+    pub fn checked(&self) -> NaiveDateTime {
+        self.checked
+    }
+
+    /**
+     * Returns the reminders that went off and were neither dismissed nor
+     * snoozed, oldest first.
+     */
+    pub fn active(&self) -> &[Alert] {
+        &self.active
+    }
+
+    /**
+     * Returns the alerts that are active or snoozed, to be kept until the
+     * scheduler is created again.
+     */
+    pub fn pending(&self) -> Vec<Alert> {
+        self.active.iter().chain(&self.snoozed).copied().collect()
+    }
+
+    /**
+     * Returns the state to create the scheduler again from when the
+     * application starts the next time.
+     */
+    pub fn state(&self) -> ReminderState {
+        ReminderState {
+            checked: Some(self.checked),
+            alerts: self.pending(),
+        }
+    }
+
+    /**
+     * Function to fire the reminders that became due since the last poll.
+     *
+     * Parameters:
+     * - `events`: The events whose reminders are checked. Alerts of events
+     *   that no longer exist are dropped.
+     * - `now`: The current time.
+     *
+     * Returns:
+     * - The alerts that went off, oldest first. They are also active until
+     *   they are dismissed or snoozed.
+     */
+    pub fn poll(&mut self, events: &EventStore, now: NaiveDateTime) -> Vec<Alert> {
+        let exists = |alert: &Alert| events.get(alert.id).is_some();
+        self.active.retain(exists);
+        self.snoozed.retain(exists);
+
+        // A clock set back does not fire the same reminders twice.
+        let mut fired = match now > self.checked {
+            true => due_between(events, self.checked, now),
+            false => Vec::new(),
+        };
+        self.checked = self.checked.max(now);
+
+        let (woken, snoozed): (Vec<Alert>, Vec<Alert>) =
+            self.snoozed.iter().partition(|alert| alert.due <= now);
+        self.snoozed = snoozed;
+        fired.extend(woken);
+        fired.sort_by_key(|alert| (alert.due, alert.start, alert.id));
+
+        self.active.extend(&fired);
+        fired
+    }
+
+    /**
+     * Function to hide an active alert until `until`, when the next poll
+     * fires it again.
+     *
+     * Returns:
+     * - Whether the alert was active.
+     */
+    pub fn snooze(&mut self, alert: &Alert, until: NaiveDateTime) -> bool {
+        if !self.dismiss(alert) {
+            return false;
+        }
+        self.snoozed.push(Alert {
+            due: until,
+            ..*alert
+        });
+        true
+    }
+
+    /**
+     * Function to remove an active alert for good.
+     *
+     * Returns:
+     * - Whether the alert was active.
+     */
+    pub fn dismiss(&mut self, alert: &Alert) -> bool {
+        let count = self.active.len();
+        self.active.retain(|active| active != alert);
+        self.active.len() < count
+    }
+}
+
+/**
+ * Returns the reminders due after `after` and up to `until`, in no
+ * particular order.
+ */
+fn due_between(events: &EventStore, after: NaiveDateTime, until: NaiveDateTime) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    let mut offsets = Vec::new();
+    for (id, event) in events.iter() {
+        for reminder in &event.reminders {
+            match *reminder {
+                Reminder::Relative(offset) => offsets.push(offset),
+                Reminder::Absolute(due) if after < due && due <= until => alerts.push(Alert {
+                    id,
+                    start: events.local_time(event, event.time).start(),
+                    due,
+                }),
+                Reminder::Absolute(_) => {}
+            }
+        }
+    }
+    let (Some(earliest), Some(latest)) = (offsets.iter().min(), offsets.iter().max()) else {
+        return alerts;
+    };
+
+    // Occurrences starting in this range may have a reminder in the window.
+    let first = after
+        .checked_sub_signed(*latest)
+        .map_or(NaiveDate::MIN, |time| time.date());
+    let last = until
+        .checked_sub_signed(*earliest)
+        .map_or(NaiveDate::MAX, |time| time.date());
+    for occurrence in events.occurrences_between(first, last) {
+        let start = occurrence.start();
+        for reminder in &occurrence.event.reminders {
+            let Reminder::Relative(_) = reminder else {
+                continue;
+            };
+            match reminder.due(start) {
+                Some(due) if after < due && due <= until => alerts.push(Alert {
+                    id: occurrence.id,
+                    start,
+                    due,
+                }),
+                _ => {}
+            }
+        }
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, EventTime};
+    use crate::recurrence::{Frequency, Recurrence, RecurrenceRule};
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn event(start: NaiveDateTime, reminders: Vec<Reminder>) -> Event {
+        let mut event = Event::new(
+            "Dentist",
+            EventTime::timed(start, start + Duration::hours(1)).unwrap(),
+        );
+        event.reminders = reminders;
+        event
+    }
+
+    #[test]
+    fn reminders_are_due_relative_to_the_start() {
+        let start = at(16, 14, 0);
+        assert_eq!(
+            Reminder::minutes_before(15).due(start),
+            Some(at(16, 13, 45))
+        );
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let day_before = Reminder::days_before_at(1, nine, start).unwrap();
+        assert_eq!(day_before, Reminder::Relative(-Duration::hours(29)));
+        assert_eq!(day_before.due(start), Some(at(15, 9, 0)));
+        assert_eq!(
+            Reminder::Absolute(at(1, 8, 0)).due(start),
+            Some(at(1, 8, 0))
+        );
+    }
+
+    #[test]
+    fn polls_fire_each_reminder_once() {
+        let mut events = EventStore::new();
+        let id = events.insert(event(
+            at(16, 14, 0),
+            vec![
+                Reminder::minutes_before(15),
+                Reminder::Absolute(at(16, 8, 0)),
+            ],
+        ));
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(16, 7, 0));
+
+        let fired = scheduler.poll(&events, at(16, 8, 0));
+        assert_eq!(
+            fired,
+            [Alert {
+                id,
+                start: at(16, 14, 0),
+                due: at(16, 8, 0)
+            }]
+        );
+        assert!(scheduler.poll(&events, at(16, 13, 44)).is_empty());
+        assert_eq!(scheduler.poll(&events, at(16, 13, 45)).len(), 1);
+        assert!(scheduler.poll(&events, at(16, 13, 50)).is_empty());
+        assert_eq!(scheduler.active().len(), 2);
+
+        // Setting the clock back does not fire anything again.
+        assert!(scheduler.poll(&events, at(16, 7, 0)).is_empty());
+        assert_eq!(scheduler.checked(), at(16, 13, 50));
+    }
+
+    #[test]
+    fn every_occurrence_of_a_recurring_event_is_reminded() {
+        let mut events = EventStore::new();
+        let mut standup = event(at(12, 9, 0), vec![Reminder::minutes_before(5)]);
+        standup.recurrence = Some(Recurrence::from_rule(RecurrenceRule::new(Frequency::Daily)));
+        events.insert(standup);
+
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(14, 12, 0));
+        let fired = scheduler.poll(&events, at(16, 12, 0));
+        let due: Vec<NaiveDateTime> = fired.iter().map(|alert| alert.due).collect();
+        assert_eq!(due, [at(15, 8, 55), at(16, 8, 55)]);
+        assert_eq!(fired[1].start, at(16, 9, 0));
+    }
+
+    #[test]
+    fn events_in_other_zones_are_reminded_at_the_local_time() {
+        let mut events = EventStore::new();
+        events.set_zone(Some(chrono_tz::Europe::Berlin));
+        let mut call = event(at(16, 10, 0), vec![Reminder::minutes_before(15)]);
+        call.time_zone = Some("America/New_York".to_owned());
+        events.insert(call);
+
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(16, 9, 0));
+        assert!(scheduler.poll(&events, at(16, 15, 0)).is_empty());
+        let fired = scheduler.poll(&events, at(16, 15, 45));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].start, at(16, 16, 0));
+    }
+
+    #[test]
+    fn missed_reminders_are_caught_up_within_limits() {
+        let mut events = EventStore::new();
+        events.insert(event(at(2, 10, 0), vec![Reminder::minutes_before(10)]));
+        events.insert(event(at(14, 10, 0), vec![Reminder::minutes_before(10)]));
+
+        // Closed on the 1st, started again on the 16th.
+        let mut scheduler = Scheduler::new(Some(at(1, 12, 0)), Vec::new(), at(16, 12, 0));
+        assert_eq!(scheduler.checked(), at(9, 12, 0));
+        let fired = scheduler.poll(&events, at(16, 12, 0));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].due, at(14, 9, 50));
+
+        // Without a previous check, nothing from the past is fired.
+        let mut fresh = Scheduler::new(None, Vec::new(), at(16, 12, 0));
+        assert!(fresh.poll(&events, at(16, 12, 0)).is_empty());
+    }
+
+    #[test]
+    fn alerts_can_be_snoozed_and_dismissed() {
+        let mut events = EventStore::new();
+        events.insert(event(at(16, 14, 0), vec![Reminder::minutes_before(15)]));
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(16, 13, 0));
+        let alert = scheduler.poll(&events, at(16, 13, 45))[0];
+
+        assert!(scheduler.snooze(&alert, at(16, 13, 55)));
+        assert!(scheduler.active().is_empty());
+        assert!(scheduler.poll(&events, at(16, 13, 50)).is_empty());
+        let again = scheduler.poll(&events, at(16, 13, 55));
+        assert_eq!(
+            again,
+            [Alert {
+                due: at(16, 13, 55),
+                ..alert
+            }]
+        );
+
+        assert!(scheduler.dismiss(&again[0]));
+        assert!(!scheduler.dismiss(&again[0]));
+        assert!(!scheduler.snooze(&again[0], at(16, 14, 0)));
+        assert!(scheduler.active().is_empty());
+
+        // Alerts of deleted events disappear.
+        let other = events.insert(event(at(16, 15, 0), vec![Reminder::minutes_before(0)]));
+        scheduler.poll(&events, at(16, 15, 0));
+        assert_eq!(scheduler.active().len(), 1);
+        events.remove(other);
+        assert!(scheduler.poll(&events, at(16, 15, 1)).is_empty());
+        assert!(scheduler.active().is_empty());
+    }
+
+    #[test]
+    fn pending_alerts_survive_a_restart() {
+        let mut events = EventStore::new();
+        events.insert(event(at(16, 14, 0), vec![Reminder::minutes_before(15)]));
+        events.insert(event(at(16, 15, 0), vec![Reminder::minutes_before(15)]));
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(16, 13, 0));
+        let fired = scheduler.poll(&events, at(16, 14, 45));
+        assert!(scheduler.snooze(&fired[0], at(16, 15, 0)));
+
+        // The application is closed and started again.
+        let pending = scheduler.pending();
+        assert_eq!(pending.len(), 2);
+        let mut restarted = Scheduler::new(Some(scheduler.checked()), pending, at(16, 14, 50));
+        let shown = restarted.poll(&events, at(16, 14, 50));
+        assert_eq!(shown, [fired[1]]);
+        let woken = restarted.poll(&events, at(16, 15, 0));
+        assert_eq!(woken.len(), 1);
+        assert_eq!(woken[0].start, at(16, 14, 0));
+        assert_eq!(restarted.active().len(), 2);
+    }
+
+    #[test]
+    fn state_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("reminders.json");
+        assert_eq!(
+            ReminderState::load(&path).unwrap(),
+            ReminderState::default()
+        );
+
+        let mut events = EventStore::new();
+        events.insert(event(at(16, 14, 0), vec![Reminder::minutes_before(15)]));
+        let mut scheduler = Scheduler::new(None, Vec::new(), at(16, 13, 0));
+        scheduler.poll(&events, at(16, 14, 45));
+        let state = scheduler.state();
+        assert_eq!(state.checked, Some(at(16, 14, 45)));
+        assert_eq!(state.alerts.len(), 1);
+
+        state.save(&path).unwrap();
+        assert_eq!(ReminderState::load(&path).unwrap(), state);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::business::BusinessCalendar;
+use crate::day::SlotSize;
+use crate::grid::WeekNumbering;
+use crate::holiday::HolidayCalendar;
+use crate::locale::Locale;
+use crate::storage::{write_atomically, StorageError};
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/**
+ * User preferences that are not part of the calendar itself.
+ *
+ * Settings missing from the file take their default value, so new settings
+ * can be added without a migration.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /** The day the weeks of the month view start with. */
+    pub week_start: Weekday,
+    /** The scheme of the week numbers shown next to the month grid; hidden if `None`. */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_numbers: Option<WeekNumbering>,
+    /** IANA name of the zone "today" is determined in; the system zone if `None`. */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    /** Country or region code of the holidays to show, e.g. `"US"` or `"DE-BY"`. */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holidays: Option<String>,
+    /** The days of the week that are not business days. */
+    pub weekend: Vec<Weekday>,
+    /** The length of the slots of the day view's time axis. */
+    pub slot_size: SlotSize,
+    /** Language of month and weekday names, e.g. `"de"`; taken from the environment if `None`. */
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            week_start: Weekday::Sun,
+            week_numbers: None,
+            time_zone: None,
+            holidays: None,
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            slot_size: SlotSize::default(),
+            locale: None,
+        }
+    }
+}
+
+impl Settings {
+    /**
+     * Returns the path of the settings file in the user's configuration
+     * directory, i.e. `$XDG_CONFIG_HOME/idk2/settings.json` on Linux.
+     */
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("idk2").join("settings.json"))
+    }
+
+    /**
+     * Loads the settings stored at `path`.
+     *
+     * Returns:
+     * - The stored settings, or the defaults if nothing has been saved yet.
+     */
+    pub fn load(path: &Path) -> Result<Settings, StorageError> {
+        let settings: Settings = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(name) = &settings.time_zone {
+            if name.parse::<Tz>().is_err() {
+                return Err(StorageError::Invalid(format!("unknown time zone {name:?}")));
+            }
+        }
+        Ok(settings)
+    }
+
+    /**
+     * Returns the configured time zone, or `None` to use the system one.
+     */
+    pub fn zone(&self) -> Option<Tz> {
+        self.time_zone.as_deref().and_then(|name| name.parse().ok())
+    }
+
+    /**
+     * Function to determine the current wall-clock time.
+     *
+     * Parameters:
+     * - `now`: The current point in time.
+     *
+     * Returns:
+     * - The local time at `now` in the configured time zone, or in the
+     *   system's local time zone if none is configured.
+     */
+    pub fn local_time(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        match self.zone() {
+            Some(zone) => now.with_timezone(&zone).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
+        }
+    }
+
+    /**
+     * Function to determine the current date.
+     *
+     * Returns:
+     * - The date of `local_time(now)`.
+     */
+    pub fn today(&self, now: DateTime<Utc>) -> NaiveDate {
+        self.local_time(now).date()
+    }
+
+    /**
+     * Function to determine the language dates are displayed in.
+     *
+     * Returns:
+     * - The configured language, or else the language of the `LC_ALL`,
+     *   `LC_TIME` or `LANG` environment variable, falling back to English
+     *   if none of them names an available language.
+     */
+    pub fn locale(&self) -> Locale {
+        let from_env = ["LC_ALL", "LC_TIME", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        self.locale
+            .iter()
+            .cloned()
+            .chain(from_env)
+            .find_map(|name| Locale::find(&name))
+            .unwrap_or_default()
+    }
+
+    /**
+     * Function to build the business-day calendar described by the settings.
+     *
+     * Parameters:
+     * - `holidays`: The holidays of the configured region, if loaded.
+     *
+     * Returns:
+     * - The calendar, or `None` if every day of the week is a weekend day.
+     */
+    pub fn business_calendar(
+        &self,
+        holidays: Option<&HolidayCalendar>,
+    ) -> Option<BusinessCalendar> {
+        let calendar = BusinessCalendar::new(self.weekend.iter().copied())?;
+        Some(match holidays {
+            Some(holidays) => calendar.with_holidays(holidays.clone()),
+            None => calendar,
+        })
+    }
+
+    /**
+     * Atomically replaces the settings stored at `path`.
+     */
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        let content = serde_json::to_vec_pretty(self)?;
+        write_atomically(path, &content, None)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_loads_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = Settings::load(&dir.path().join("settings.json")).unwrap();
+        assert_eq!(settings, Settings::default());
+        assert_eq!(settings.week_start, Weekday::Sun);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("settings.json");
+        let settings = Settings {
+            week_start: Weekday::Mon,
+            week_numbers: Some(WeekNumbering::Iso),
+            time_zone: Some("Europe/Berlin".to_string()),
+            holidays: Some("DE-BE".to_string()),
+            weekend: vec![Weekday::Fri, Weekday::Sat],
+            slot_size: SlotSize::Quarter,
+            locale: Some("ja".to_string()),
+        };
+
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+    }
+
+    #[test]
+    fn unknown_and_missing_fields_are_tolerated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+
+        fs::write(&path, r#"{"theme": "dark"}"#).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+
+        fs::write(&path, r#"{"week_start": "Sat"}"#).unwrap();
+        assert_eq!(Settings::load(&path).unwrap().week_start, Weekday::Sat);
+
+        fs::write(&path, r#"{"week_start": "Someday"}"#).unwrap();
+        assert!(matches!(
+            Settings::load(&path),
+            Err(StorageError::Format(_))
+        ));
+
+        fs::write(&path, r#"{"week_numbers": "us"}"#).unwrap();
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.week_numbers, Some(WeekNumbering::Us));
+
+        fs::write(&path, r#"{"slot_size": 60}"#).unwrap();
+        assert_eq!(Settings::load(&path).unwrap().slot_size, SlotSize::Hour);
+
+        fs::write(&path, r#"{"slot_size": 45}"#).unwrap();
+        assert!(matches!(
+            Settings::load(&path),
+            Err(StorageError::Format(_))
+        ));
+
+        fs::write(&path, r#"{"time_zone": "Mars/Olympus_Mons"}"#).unwrap();
+        assert!(matches!(
+            Settings::load(&path),
+            Err(StorageError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn today_depends_on_the_time_zone() {
+        // 02:00 UTC is still the previous evening in California.
+        let now = Utc.with_ymd_and_hms(2026, 10, 17, 2, 0, 0).unwrap();
+        let in_zone = |name: &str| Settings {
+            time_zone: Some(name.to_string()),
+            ..Settings::default()
+        };
+
+        assert_eq!(
+            in_zone("America/Los_Angeles").today(now),
+            NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+        );
+        assert_eq!(
+            in_zone("Asia/Tokyo").today(now),
+            NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()
+        );
+        assert_eq!(
+            Settings::default().today(now),
+            now.with_timezone(&Local).date_naive()
+        );
+    }
+
+    #[test]
+    fn the_configured_locale_is_used() {
+        let with_locale = |name: &str| Settings {
+            locale: Some(name.to_string()),
+            ..Settings::default()
+        };
+        assert_eq!(with_locale("de_CH").locale().code, "de");
+        assert_eq!(with_locale("ja").locale().code, "ja");
+    }
+}
@@ -0,0 +1,704 @@
+use crate::event::{Color, Event, EventId, EventStore, EventTime};
+use crate::recurrence::Recurrence;
+use crate::reminder::Reminder;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/**
+ * Version of the on-disk format written by `Storage::save`.
+ */
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+/**
+ * A migration rewrites a document of one schema version into the next one.
+ */
+type Migration = fn(&mut Value) -> Result<(), StorageError>;
+
+/**
+ * Migration hooks, indexed by the version they migrate from minus one: the
+ * first entry upgrades version 1 documents to version 2 and so on. Adding a
+ * schema change means appending a migration here, which also bumps
+ * `SCHEMA_VERSION`.
+ */
+const MIGRATIONS: &[Migration] = &[];
+
+/**
+ * Errors reported while loading or saving the calendar.
+ */
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /** The file was written by a newer version of the application. */
+    UnsupportedVersion(u64),
+    /** The file is well-formed but describes an impossible calendar. */
+    Invalid(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io(err) => write!(f, "I/O error: {err}"),
+            StorageError::Format(err) => write!(f, "malformed calendar file: {err}"),
+            StorageError::UnsupportedVersion(version) => write!(
+                f,
+                "calendar file has schema version {version}, this build supports up to {SCHEMA_VERSION}"
+            ),
+            StorageError::Invalid(reason) => write!(f, "invalid calendar file: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::Io(err) => Some(err),
+            StorageError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(err: io::Error) -> StorageError {
+        StorageError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(err: serde_json::Error) -> StorageError {
+        StorageError::Format(err)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredCalendar {
+    version: u64,
+    events: Vec<StoredEvent>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEvent {
+    id: u64,
+    title: String,
+    time: StoredTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    color: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurrence: Option<StoredRecurrence>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reminders: Vec<StoredReminder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    calendar: Option<String>,
+}
+
+/**
+ * The recurrence of an event, the rule being kept as an RRULE value.
+ */
+#[derive(Serialize, Deserialize)]
+struct StoredRecurrence {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rule: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exdates: Vec<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rdates: Vec<NaiveDateTime>,
+}
+
+/**
+ * A reminder of an event, relative offsets being kept in seconds.
+ */
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StoredReminder {
+    Relative { seconds: i64 },
+    Absolute { at: NaiveDateTime },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StoredTime {
+    AllDay {
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+    Timed {
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    },
+}
+
+impl StoredEvent {
+    fn from_event(id: EventId, event: &Event) -> StoredEvent {
+        let time = match event.time {
+            EventTime::AllDay { start, end } => StoredTime::AllDay { start, end },
+            EventTime::Timed { start, end } => StoredTime::Timed { start, end },
+        };
+        StoredEvent {
+            id: id.0,
+            title: event.title.clone(),
+            time,
+            location: event.location.clone(),
+            notes: event.notes.clone(),
+            color: event.color.to_hex(),
+            uid: event.uid.clone(),
+            time_zone: event.time_zone.clone(),
+            recurrence: event
+                .recurrence
+                .as_ref()
+                .map(|recurrence| StoredRecurrence {
+                    rule: recurrence.rule.as_ref().map(ToString::to_string),
+                    exdates: recurrence.exdates.clone(),
+                    rdates: recurrence.rdates.clone(),
+                }),
+            reminders: event
+                .reminders
+                .iter()
+                .map(|reminder| match *reminder {
+                    Reminder::Relative(offset) => StoredReminder::Relative {
+                        seconds: offset.num_seconds(),
+                    },
+                    Reminder::Absolute(at) => StoredReminder::Absolute { at },
+                })
+                .collect(),
+            calendar: event.calendar.clone(),
+        }
+    }
+
+    fn into_event(self) -> Result<(EventId, Event), StorageError> {
+        let time = match self.time {
+            StoredTime::AllDay { start, end } => EventTime::all_day(start, end),
+            StoredTime::Timed { start, end } => EventTime::timed(start, end),
+        }
+        .ok_or_else(|| StorageError::Invalid(format!("event {} ends before it starts", self.id)))?;
+        let color = Color::from_hex(&self.color).ok_or_else(|| {
+            StorageError::Invalid(format!(
+                "event {} has invalid color {:?}",
+                self.id, self.color
+            ))
+        })?;
+        let recurrence = match self.recurrence {
+            Some(stored) => Some(Recurrence {
+                rule: stored
+                    .rule
+                    .map(|rule| rule.parse())
+                    .transpose()
+                    .map_err(|err| StorageError::Invalid(format!("event {}: {}", self.id, err)))?,
+                exdates: stored.exdates,
+                rdates: stored.rdates,
+            }),
+            None => None,
+        };
+        let reminders = self
+            .reminders
+            .into_iter()
+            .map(|stored| match stored {
+                StoredReminder::Relative { seconds } => Duration::try_seconds(seconds)
+                    .map(Reminder::Relative)
+                    .ok_or_else(|| {
+                        StorageError::Invalid(format!(
+                            "event {} has a reminder out of range",
+                            self.id
+                        ))
+                    }),
+                StoredReminder::Absolute { at } => Ok(Reminder::Absolute(at)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let event = Event {
+            title: self.title,
+            time,
+            location: self.location,
+            notes: self.notes,
+            color,
+            uid: self.uid,
+            time_zone: self.time_zone,
+            recurrence,
+            reminders,
+            calendar: self.calendar,
+        };
+        Ok((EventId(self.id), event))
+    }
+}
+
+/**
+ * Saves and loads an `EventStore` as a versioned JSON document.
+ *
+ * Saving never leaves a half-written file behind: the new content is written
+ * to a temporary file next to the calendar and renamed over it, after the
+ * previous version has been copied to a backup file. Saving content that is
+ * already in the file leaves both files untouched.
+ *
+ * The window and the command-line tools share the file, so the storage
+ * remembers what it last read or wrote. Changes another process made in the
+ * meantime are merged into the store being saved instead of being
+ * overwritten.
+ */
+#[derive(Debug, Clone)]
+pub struct Storage {
+    path: PathBuf,
+    /** The file as last loaded or saved, `None` before either. */
+    synced: RefCell<Option<String>>,
+}
+
+impl Storage {
+    pub fn new(path: impl Into<PathBuf>) -> Storage {
+        Storage {
+            path: path.into(),
+            synced: RefCell::new(None),
+        }
+    }
+
+    /**
+     * Returns the storage in the user's data directory, i.e.
+     * `$XDG_DATA_HOME/idk2/calendar.json` on Linux.
+     */
+    pub fn default_location() -> Option<Storage> {
+        dirs::data_dir().map(|dir| Storage::new(dir.join("idk2").join("calendar.json")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+     * Returns the path of the copy of the previously saved calendar.
+     */
+    pub fn backup_path(&self) -> PathBuf {
+        self.sibling("bak")
+    }
+
+    /**
+     * Loads the calendar, upgrading older schema versions on the fly.
+     *
+     * Returns:
+     * - The stored events, or an empty store if nothing has been saved yet.
+     */
+    pub fn load(&self) -> Result<EventStore, StorageError> {
+        let content = self.read()?;
+        let store = parse(&content)?;
+        *self.synced.borrow_mut() = Some(content);
+        Ok(store)
+    }
+
+    /**
+     * Atomically replaces the saved calendar with the content of `store`.
+     *
+     * If another process changed the file since it was last loaded or saved
+     * here, its changes are merged into `store` first, see `merge`.
+     */
+    pub fn save(&self, store: &mut EventStore) -> Result<(), StorageError> {
+        let current = self.read()?;
+        let synced = self.synced.borrow().clone();
+        if let Some(synced) = synced.filter(|synced| *synced != current) {
+            *store = merge(&parse(&synced)?, store, &parse(&current)?);
+        }
+
+        let calendar = StoredCalendar {
+            version: SCHEMA_VERSION,
+            events: store
+                .iter()
+                .map(|(id, event)| StoredEvent::from_event(id, event))
+                .collect(),
+        };
+        let content = serde_json::to_string_pretty(&calendar)?;
+        if content == current {
+            *self.synced.borrow_mut() = Some(content);
+            return Ok(());
+        }
+
+        write_atomically(&self.path, content.as_bytes(), Some(&self.backup_path()))?;
+        *self.synced.borrow_mut() = Some(content);
+        Ok(())
+    }
+
+    /**
+     * Returns the content of the file, or an empty string if it is missing.
+     */
+    fn read(&self) -> Result<String, StorageError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn sibling(&self, extension: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(extension);
+        self.path.with_file_name(name)
+    }
+}
+
+/**
+ * Function to replace a file without ever leaving a half-written one behind.
+ *
+ * The content is written to a temporary file next to `path`, flushed to disk
+ * and renamed over the file.
+ *
+ * Parameters:
+ * - `path`: The file to replace, created along with its directory if missing.
+ * - `content`: The new content of the file.
+ * - `backup`: Where to copy the previous version of the file to, if anywhere.
+ */
+pub(crate) fn write_atomically(
+    path: &Path,
+    content: &[u8],
+    backup: Option<&Path>,
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp_path = path.with_file_name(name);
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    drop(file);
+
+    if let Some(backup) = backup.filter(|_| path.exists()) {
+        fs::copy(path, backup)?;
+    }
+    fs::rename(&temp_path, path)
+}
+
+/**
+ * Function to describe an event the way the calendar file stores it, e.g.
+ * for machine-readable output.
+ *
+ * Returns:
+ * - The JSON object of the event, including its identifier.
+ */
+pub fn event_json(id: EventId, event: &Event) -> Value {
+    serde_json::to_value(StoredEvent::from_event(id, event))
+        .expect("events are always serializable")
+}
+
+/**
+ * Function to read a calendar document, an empty one standing for a
+ * calendar that has not been saved yet.
+ */
+fn parse(content: &str) -> Result<EventStore, StorageError> {
+    let mut store = EventStore::new();
+    if content.is_empty() {
+        return Ok(store);
+    }
+    let document = migrate(serde_json::from_str(content)?)?;
+    let calendar: StoredCalendar = serde_json::from_value(document)?;
+    for stored in calendar.events {
+        let (id, event) = stored.into_event()?;
+        if store.insert_with_id(id, event).is_some() {
+            return Err(StorageError::Invalid(format!(
+                "duplicate event id {}",
+                id.0
+            )));
+        }
+    }
+    Ok(store)
+}
+
+/**
+ * Function to combine the changes two processes made to a calendar.
+ *
+ * Events changed on one side only take the version of that side. Where both
+ * sides changed an event, `ours` wins, and events both sides added under the
+ * same identifier are all kept, the one of `ours` under a new identifier.
+ *
+ * Parameters:
+ * - `base`: The calendar both sides started from.
+ * - `ours`: The calendar of this process.
+ * - `theirs`: The calendar another process saved.
+ *
+ * Returns:
+ * - The merged calendar.
+ */
+fn merge(base: &EventStore, ours: &EventStore, theirs: &EventStore) -> EventStore {
+    let ids: BTreeSet<EventId> = base
+        .iter()
+        .chain(ours.iter())
+        .chain(theirs.iter())
+        .map(|(id, _)| id)
+        .collect();
+    let mut merged = EventStore::new();
+    merged.set_zone(ours.zone());
+    let mut renumbered = Vec::new();
+    for id in ids {
+        let (base, ours, theirs) = (base.get(id), ours.get(id), theirs.get(id));
+        let event = if ours == base {
+            theirs
+        } else if theirs == base || theirs == ours {
+            ours
+        } else if base.is_none() {
+            renumbered.extend(ours);
+            theirs
+        } else {
+            ours
+        };
+        if let Some(event) = event {
+            merged.insert_with_id(id, event.clone());
+        }
+    }
+    for event in renumbered {
+        merged.insert(event.clone());
+    }
+    merged
+}
+
+/**
+ * Runs the migrations needed to bring `document` to `SCHEMA_VERSION`.
+ */
+fn migrate(mut document: Value) -> Result<Value, StorageError> {
+    let version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| StorageError::Invalid("missing schema version".to_string()))?;
+    if version == 0 {
+        return Err(StorageError::Invalid("schema version 0".to_string()));
+    }
+    if version > SCHEMA_VERSION {
+        return Err(StorageError::UnsupportedVersion(version));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migration(&mut document)?;
+        document["version"] = Value::from(from as u64 + 2);
+    }
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn sample_store() -> EventStore {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let mut lunch = Event::new(
+            "Lunch",
+            EventTime::timed(
+                day.and_hms_opt(12, 30, 0).unwrap(),
+                day.and_hms_opt(13, 30, 0).unwrap(),
+            )
+            .unwrap(),
+        );
+        lunch.location = Some("Cafe Luna".to_string());
+        lunch.color = Color::rgb(0xff, 0x80, 0x00);
+        lunch.uid = Some("lunch@example.com".to_string());
+        lunch.time_zone = Some("Europe/Berlin".to_string());
+        lunch.calendar = Some("Personal".to_string());
+        lunch.reminders = vec![
+            Reminder::minutes_before(15),
+            Reminder::Absolute(day.and_hms_opt(8, 0, 0).unwrap()),
+        ];
+        store.insert(lunch);
+        store.insert(Event::new("Holiday", EventTime::all_day(day, day).unwrap()));
+        store
+    }
+
+    fn recurring_store() -> EventStore {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 16);
+        let mut standup = Event::new(
+            "Standup",
+            EventTime::timed(
+                day.and_hms_opt(9, 0, 0).unwrap(),
+                day.and_hms_opt(9, 15, 0).unwrap(),
+            )
+            .unwrap(),
+        );
+        standup.recurrence = Some(Recurrence {
+            rule: Some("FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=30".parse().unwrap()),
+            exdates: vec![date(2026, 10, 19).and_hms_opt(9, 0, 0).unwrap()],
+            rdates: Vec::new(),
+        });
+        store.insert(standup);
+        store
+    }
+
+    #[test]
+    fn missing_file_loads_as_empty_calendar() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        assert!(storage.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("nested").join("calendar.json"));
+        let mut store = sample_store();
+
+        storage.save(&mut store).unwrap();
+        let loaded = storage.load().unwrap();
+
+        let original: Vec<_> = store.iter().collect();
+        let restored: Vec<_> = loaded.iter().collect();
+        assert_eq!(original, restored);
+        assert!(!storage.sibling("tmp").exists());
+    }
+
+    #[test]
+    fn recurring_events_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        let mut store = recurring_store();
+
+        storage.save(&mut store).unwrap();
+        let loaded = storage.load().unwrap();
+
+        let original: Vec<_> = store.iter().collect();
+        let restored: Vec<_> = loaded.iter().collect();
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn events_are_described_like_in_the_file() {
+        let store = sample_store();
+        let json = event_json(EventId(0), store.get(EventId(0)).unwrap());
+        assert_eq!(json["id"], 0);
+        assert_eq!(json["title"], "Lunch");
+        assert_eq!(json["time"]["kind"], "timed");
+        assert_eq!(json["time"]["start"], "2026-10-16T12:30:00");
+        assert_eq!(json["color"], "#ff8000");
+        assert!(json.get("notes").is_none());
+        assert_eq!(json["reminders"][0]["kind"], "relative");
+        assert_eq!(json["reminders"][0]["seconds"], -900);
+        assert_eq!(json["calendar"], "Personal");
+    }
+
+    #[test]
+    fn saving_backs_up_the_previous_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        let mut store = sample_store();
+
+        storage.save(&mut store).unwrap();
+        assert!(!storage.backup_path().exists());
+
+        store.remove(EventId(0));
+        storage.save(&mut store).unwrap();
+
+        assert_eq!(storage.load().unwrap().len(), 1);
+        let backup = Storage::new(storage.backup_path()).load().unwrap();
+        assert_eq!(backup.len(), 2);
+
+        // Saving without changes keeps the backup of the previous version.
+        storage.save(&mut store).unwrap();
+        let backup = Storage::new(storage.backup_path()).load().unwrap();
+        assert_eq!(backup.len(), 2);
+    }
+
+    #[test]
+    fn changes_of_other_processes_are_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calendar.json");
+        let window = Storage::new(&path);
+        let mut ours = sample_store();
+        window.save(&mut ours).unwrap();
+
+        // Another process renames the holiday and adds an event.
+        let command = Storage::new(&path);
+        let mut theirs = command.load().unwrap();
+        let mut holiday = theirs.get(EventId(1)).unwrap().clone();
+        holiday.title = "Bank holiday".to_string();
+        theirs.update(EventId(1), holiday);
+        let day = date(2026, 10, 20);
+        theirs.insert(Event::new("Dentist", EventTime::all_day(day, day).unwrap()));
+        command.save(&mut theirs).unwrap();
+
+        // Meanwhile the window moved lunch and added an event too.
+        let mut lunch = ours.get(EventId(0)).unwrap().clone();
+        lunch.location = Some("Canteen".to_string());
+        ours.update(EventId(0), lunch);
+        ours.insert(Event::new("Gym", EventTime::all_day(day, day).unwrap()));
+        window.save(&mut ours).unwrap();
+
+        let titles = |store: &EventStore| -> Vec<String> {
+            store.iter().map(|(_, event)| event.title.clone()).collect()
+        };
+        assert_eq!(titles(&ours), ["Lunch", "Bank holiday", "Dentist", "Gym"]);
+        assert_eq!(
+            ours.get(EventId(0)).unwrap().location.as_deref(),
+            Some("Canteen")
+        );
+        let saved = Storage::new(&path).load().unwrap();
+        assert_eq!(titles(&saved), titles(&ours));
+
+        // Removing an event is merged as well.
+        let mut theirs = command.load().unwrap();
+        theirs.remove(EventId(2));
+        command.save(&mut theirs).unwrap();
+        window.save(&mut ours).unwrap();
+        assert_eq!(titles(&ours), ["Lunch", "Bank holiday", "Gym"]);
+    }
+
+    #[test]
+    fn newer_schema_versions_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        let document = format!(r#"{{"version": {}, "events": []}}"#, SCHEMA_VERSION + 1);
+        fs::write(storage.path(), document).unwrap();
+
+        assert!(matches!(
+            storage.load(),
+            Err(StorageError::UnsupportedVersion(version)) if version == SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn invalid_events_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        let document = r##"{"version": 1, "events": [{"id": 3, "title": "Backwards",
+            "time": {"kind": "all_day", "start": "2026-10-16", "end": "2026-10-15"},
+            "color": "#000000"}]}"##;
+        fs::write(storage.path(), document).unwrap();
+
+        assert!(matches!(storage.load(), Err(StorageError::Invalid(_))));
+    }
+
+    #[test]
+    fn invalid_recurrence_rules_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        let document = r##"{"version": 1, "events": [{"id": 3, "title": "Hourly",
+            "time": {"kind": "all_day", "start": "2026-10-16", "end": "2026-10-16"},
+            "color": "#000000", "recurrence": {"rule": "FREQ=HOURLY"}}]}"##;
+        fs::write(storage.path(), document).unwrap();
+
+        assert!(matches!(storage.load(), Err(StorageError::Invalid(_))));
+    }
+
+    #[test]
+    fn loaded_stores_continue_the_id_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().join("calendar.json"));
+        storage.save(&mut sample_store()).unwrap();
+
+        let mut loaded = storage.load().unwrap();
+        let day = date(2026, 10, 17);
+        let id = loaded.insert(Event::new("Next", EventTime::all_day(day, day).unwrap()));
+        assert_eq!(id, EventId(2));
+    }
+}
@@ -0,0 +1,314 @@
+/*!
+ * Month calendars as plain text, laid out like the output of the `cal`
+ * utility:
+ *
+ * ```text
+ *     October 2026
+ * Su Mo Tu We Th Fr Sa
+ *              1  2  3
+ *  4  5  6  7  8  9 10
+ * ```
+ */
+
+use crate::grid::{week_days, WeekNumbering};
+use crate::locale::{Locale, NameStyle};
+use crate::navigation::MonthCursor;
+use chrono::prelude::*;
+
+/**
+ * Number of week rows every month is padded to, so that months printed side
+ * by side line up.
+ */
+const WEEK_ROWS: usize = 6;
+
+/**
+ * Spaces between months printed side by side.
+ */
+const GUTTER: &str = "  ";
+
+/**
+ * Options for rendering months as text.
+ */
+#[derive(Debug, Clone)]
+pub struct TextCalendar {
+    /** The language of the month and weekday names. */
+    pub locale: Locale,
+    /** The day the weeks start with. */
+    pub week_start: Weekday,
+    /** The scheme of the week numbers printed before every week, if any. */
+    pub week_numbers: Option<WeekNumbering>,
+    /** A day to print in reverse video, normally today. */
+    pub highlight: Option<NaiveDate>,
+}
+
+impl TextCalendar {
+    /**
+     * Function to render a single month.
+     *
+     * Parameters:
+     * - `cursor`: The month to render.
+     * - `with_year`: Whether the heading includes the year.
+     *
+     * Returns:
+     * - The lines of the month, all of the same display width: the heading,
+     *   the weekday names and six weeks, blank if the month has fewer.
+     */
+    pub fn month(&self, cursor: MonthCursor, with_year: bool) -> Vec<String> {
+        let grid = cursor.grid(self.week_start);
+        let width = self.month_width();
+        let heading = match with_year {
+            true => self.locale.month_title(cursor.first_day(), NameStyle::Full),
+            false => self
+                .locale
+                .month_name(cursor.month(), NameStyle::Full)
+                .unwrap_or_default()
+                .to_string(),
+        };
+
+        let mut lines = vec![center(&heading, width)];
+        let names: Vec<String> = week_days(self.week_start)
+            .into_iter()
+            .map(|weekday| {
+                let name = self.locale.weekday_name(weekday, NameStyle::Short);
+                pad_left(&truncate(name, 2), 2)
+            })
+            .collect();
+        lines.push(self.week_number_column(None) + &names.join(" "));
+
+        let numbers = self
+            .week_numbers
+            .map(|numbering| grid.week_numbers(numbering))
+            .unwrap_or_default();
+        for (row, week) in grid.weeks().enumerate() {
+            let days: Vec<String> = week
+                .iter()
+                .map(|cell| self.day(cell.in_month, cell.date))
+                .collect();
+            lines.push(self.week_number_column(numbers.get(row).copied()) + &days.join(" "));
+        }
+        lines.resize(WEEK_ROWS + 2, " ".repeat(width));
+        lines
+    }
+
+    /**
+     * Function to render consecutive months side by side.
+     *
+     * Parameters:
+     * - `first`: The first month to render.
+     * - `count`: The number of months to render.
+     * - `columns`: The number of months printed next to each other.
+     * - `with_year`: Whether the month headings include the year.
+     *
+     * Returns:
+     * - The lines of the rows of months, separated by blank lines.
+     */
+    pub fn months(
+        &self,
+        first: MonthCursor,
+        count: usize,
+        columns: usize,
+        with_year: bool,
+    ) -> Vec<String> {
+        let months: Vec<Vec<String>> = (0..count)
+            .map(|index| self.month(first.add_months(index as i32), with_year))
+            .collect();
+
+        let mut lines = Vec::new();
+        for row in months.chunks(columns.max(1)) {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            for line in 0..WEEK_ROWS + 2 {
+                let parts: Vec<&str> = row.iter().map(|month| month[line].as_str()).collect();
+                lines.push(parts.join(GUTTER).trim_end().to_string());
+            }
+        }
+        lines
+    }
+
+    /**
+     * Function to render a whole year in four rows of three months, headed
+     * by the year.
+     */
+    pub fn year(&self, year: i32) -> Option<Vec<String>> {
+        let first = MonthCursor::new(year, 1)?;
+        let width = 3 * self.month_width() + 2 * GUTTER.len();
+        let mut lines = vec![
+            center(&year.to_string(), width).trim_end().to_string(),
+            String::new(),
+        ];
+        lines.extend(self.months(first, 12, 3, false));
+        Some(lines)
+    }
+
+    /**
+     * Returns the display width of a rendered month.
+     */
+    fn month_width(&self) -> usize {
+        self.week_number_column(None).len() + 7 * 3 - 1
+    }
+
+    /**
+     * Returns the text before every week: its number, or blanks if week
+     * numbers are shown but `number` is `None`.
+     */
+    fn week_number_column(&self, number: Option<u32>) -> String {
+        match (self.week_numbers, number) {
+            (None, _) => String::new(),
+            (Some(_), Some(number)) => format!("{number:>2} "),
+            (Some(_), None) => "   ".to_string(),
+        }
+    }
+
+    /**
+     * Returns a day of the grid, blank for days of adjacent months.
+     */
+    fn day(&self, in_month: bool, date: NaiveDate) -> String {
+        if !in_month {
+            return "  ".to_string();
+        }
+        let day = format!("{:>2}", date.day());
+        match self.highlight == Some(date) {
+            true => format!("\u{1b}[7m{day}\u{1b}[0m"),
+            false => day,
+        }
+    }
+}
+
+/**
+ * Returns the number of terminal columns `text` takes; East Asian wide
+ * characters take two.
+ */
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115f
+            | 0x2e80..=0xa4cf
+            | 0xac00..=0xd7a3
+            | 0xf900..=0xfaff
+            | 0xfe30..=0xfe4f
+            | 0xff00..=0xff60
+            | 0xffe0..=0xffe6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/**
+ * Returns the longest prefix of `text` at most `width` columns wide.
+ */
+fn truncate(text: &str, width: usize) -> String {
+    let mut truncated = String::new();
+    for c in text.chars() {
+        truncated.push(c);
+        if display_width(&truncated) > width {
+            truncated.pop();
+            break;
+        }
+    }
+    truncated
+}
+
+fn pad_left(text: &str, width: usize) -> String {
+    " ".repeat(width.saturating_sub(display_width(text))) + text
+}
+
+/**
+ * Centers `text` in `width` columns, truncating it if it is wider.
+ */
+fn center(text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    let space = width - display_width(&text);
+    format!(
+        "{}{}{}",
+        " ".repeat(space / 2),
+        text,
+        " ".repeat(space - space / 2)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(week_start: Weekday) -> TextCalendar {
+        TextCalendar {
+            locale: Locale::default(),
+            week_start,
+            week_numbers: None,
+            highlight: None,
+        }
+    }
+
+    fn october() -> MonthCursor {
+        MonthCursor::new(2026, 10).unwrap()
+    }
+
+    #[test]
+    fn a_month_is_laid_out_like_cal() {
+        let lines = calendar(Weekday::Sun).month(october(), true);
+        assert_eq!(
+            lines,
+            [
+                "    October 2026    ",
+                "Su Mo Tu We Th Fr Sa",
+                "             1  2  3",
+                " 4  5  6  7  8  9 10",
+                "11 12 13 14 15 16 17",
+                "18 19 20 21 22 23 24",
+                "25 26 27 28 29 30 31",
+                "                    ",
+            ]
+        );
+    }
+
+    #[test]
+    fn weeks_can_start_on_monday_and_be_numbered() {
+        let mut calendar = calendar(Weekday::Mon);
+        calendar.week_numbers = Some(WeekNumbering::Iso);
+        calendar.highlight = NaiveDate::from_ymd_opt(2026, 10, 16);
+        let lines = calendar.month(october(), false);
+        assert_eq!(lines[0], "        October        ");
+        assert_eq!(lines[1], "   Mo Tu We Th Fr Sa Su");
+        assert_eq!(lines[2], "40           1  2  3  4");
+        assert_eq!(lines[4], "42 12 13 14 15 \u{1b}[7m16\u{1b}[0m 17 18");
+    }
+
+    #[test]
+    fn months_are_printed_side_by_side() {
+        let lines = calendar(Weekday::Sun).months(october().previous_month(), 3, 3, true);
+        assert_eq!(lines.len(), 8);
+        assert_eq!(
+            lines[0],
+            "   September 2026         October 2026         November 2026"
+        );
+        assert_eq!(
+            lines[2],
+            "       1  2  3  4  5               1  2  3   1  2  3  4  5  6  7"
+        );
+
+        let lines = calendar(Weekday::Sun).months(october(), 4, 3, true);
+        assert_eq!(lines.len(), 17);
+        assert_eq!(lines[8], "");
+        assert_eq!(lines[9].trim(), "January 2027");
+    }
+
+    #[test]
+    fn a_year_has_twelve_months() {
+        let lines = calendar(Weekday::Sun).year(2026).unwrap();
+        assert_eq!(lines[0].trim(), "2026");
+        assert_eq!(lines.len(), 2 + 4 * 8 + 3);
+        assert!(lines[2].starts_with("      January"));
+        assert!(calendar(Weekday::Sun).year(300_000).is_none());
+    }
+
+    #[test]
+    fn wide_names_are_measured_in_columns() {
+        let mut calendar = calendar(Weekday::Sun);
+        calendar.locale = Locale::find("ja").unwrap();
+        let lines = calendar.month(october(), true);
+        assert_eq!(lines[0], "     2026年10月     ");
+        assert_eq!(lines[1], "日 月 火 水 木 金 土");
+        assert_eq!(display_width(&lines[1]), 20);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::event::{EventStore, EventTime, Occurrence};
+use chrono::prelude::*;
+use chrono::Duration;
+
+/**
+ * Number of minutes in a day, the height of the time axis.
+ */
+pub const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/**
+ * Shortest duration a timed block is laid out with, so that very short or
+ * zero-length events stay visible and do not overlap the next block.
+ */
+pub const MIN_BLOCK_MINUTES: u32 = 15;
+
+/**
+ * An all-day event drawn as a bar in the strip above the time axis.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllDayBar<'a> {
+    pub occurrence: Occurrence<'a>,
+    /** Index of the first day column the bar covers. */
+    pub first: usize,
+    /** Index of the last day column the bar covers (inclusive). */
+    pub last: usize,
+    /** Row of the strip, so that bars sharing a day do not overlap. */
+    pub row: usize,
+}
+
+/**
+ * A timed event drawn as a block on the time axis of one day.
+ *
+ * Events overlapping each other share the width of the day: a block is drawn
+ * in column `column` of `columns` equally wide columns.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedBlock<'a> {
+    pub occurrence: Occurrence<'a>,
+    /** Index of the day column, always 0 for a single day. */
+    pub day: usize,
+    /** Start of the block in minutes after midnight. */
+    pub start_minute: u32,
+    /** End of the block in minutes after midnight (exclusive, at most `MINUTES_PER_DAY`). */
+    pub end_minute: u32,
+    pub column: usize,
+    pub columns: usize,
+}
+
+/**
+ * The events of one week, laid out for a view with seven day columns against
+ * a 24-hour time axis.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekLayout<'a> {
+    pub days: [NaiveDate; 7],
+    pub all_day: Vec<AllDayBar<'a>>,
+    /** Number of rows of the all-day strip. */
+    pub all_day_rows: usize,
+    pub timed: Vec<TimedBlock<'a>>,
+}
+
+impl<'a> WeekLayout<'a> {
+    /**
+     * Lays out the events of the week starting on `first_day`.
+     *
+     * Parameters:
+     * - `store`: The events to show.
+     * - `first_day`: The date of the first day column.
+     *
+     * Returns:
+     * - The layout of the all-day strip and of the timed blocks of every day.
+     */
+    pub fn new(store: &'a EventStore, first_day: NaiveDate) -> WeekLayout<'a> {
+        let mut days = [first_day; 7];
+        for i in 1..7 {
+            days[i] = days[i - 1].succ_opt().unwrap_or(days[i - 1]);
+        }
+        let occurrences = store.occurrences_between(days[0], days[6]);
+
+        let (all_day, timed): (Vec<Occurrence>, Vec<Occurrence>) = occurrences
+            .into_iter()
+            .partition(|occurrence| occurrence.time.is_all_day());
+        let (all_day, all_day_rows) = layout_all_day(&days, all_day);
+
+        let timed = days
+            .iter()
+            .enumerate()
+            .flat_map(|(index, date)| {
+                let on_day: Vec<Occurrence> = timed
+                    .iter()
+                    .filter(|occurrence| occurrence.time.covers(*date))
+                    .copied()
+                    .collect();
+                layout_day(*date, index, &on_day)
+            })
+            .collect();
+
+        WeekLayout {
+            days,
+            all_day,
+            all_day_rows,
+            timed,
+        }
+    }
+}
+
+/**
+ * Function to find the week a date belongs to.
+ *
+ * Parameters:
+ * - `date`: Any date of the week.
+ * - `week_start`: The day weeks start with.
+ *
+ * Returns:
+ * - The first day of the week containing `date`.
+ */
+pub fn week_containing(date: NaiveDate, week_start: Weekday) -> NaiveDate {
+    let offset = crate::grid::get_last_days_of_prev_month(date.weekday(), week_start);
+    date.checked_sub_signed(Duration::days(offset as i64))
+        .unwrap_or(date)
+}
+
+/**
+ * Function to lay out the timed events of a single day.
+ *
+ * Events are clipped to the day, so events crossing midnight show up on both
+ * days. Overlapping events are grouped and each group is split into as many
+ * columns as needed, every event taking the first column free at its start.
+ *
+ * Parameters:
+ * - `date`: The day to lay out.
+ * - `day`: The index of the day column stored in the blocks.
+ * - `occurrences`: The timed occurrences covering `date`.
+ *
+ * Returns:
+ * - The blocks ordered by start.
+ */
+pub fn layout_day<'a>(
+    date: NaiveDate,
+    day: usize,
+    occurrences: &[Occurrence<'a>],
+) -> Vec<TimedBlock<'a>> {
+    let day_start = date.and_time(NaiveTime::MIN);
+    let minutes = |time: NaiveDateTime| {
+        (time - day_start)
+            .num_minutes()
+            .clamp(0, MINUTES_PER_DAY as i64) as u32
+    };
+
+    let mut blocks: Vec<TimedBlock> = occurrences
+        .iter()
+        .filter_map(|occurrence| {
+            let EventTime::Timed { start, end } = occurrence.time else {
+                return None;
+            };
+            let start_minute = minutes(start).min(MINUTES_PER_DAY - MIN_BLOCK_MINUTES);
+            let end_minute = minutes(end)
+                .max(start_minute + MIN_BLOCK_MINUTES)
+                .min(MINUTES_PER_DAY);
+            Some(TimedBlock {
+                occurrence: *occurrence,
+                day,
+                start_minute,
+                end_minute,
+                column: 0,
+                columns: 1,
+            })
+        })
+        .collect();
+    blocks.sort_by_key(|block| {
+        (
+            block.start_minute,
+            std::cmp::Reverse(block.end_minute),
+            block.occurrence.id,
+        )
+    });
+
+    let mut group_start = 0;
+    let mut group_end = 0;
+    let mut column_ends: Vec<u32> = Vec::new();
+    for index in 0..blocks.len() {
+        if blocks[index].start_minute >= group_end {
+            finish_group(&mut blocks[group_start..index], column_ends.len());
+            group_start = index;
+            column_ends.clear();
+        }
+        let block = &mut blocks[index];
+        block.column = match column_ends
+            .iter()
+            .position(|end| *end <= block.start_minute)
+        {
+            Some(column) => column,
+            None => {
+                column_ends.push(0);
+                column_ends.len() - 1
+            }
+        };
+        column_ends[block.column] = block.end_minute;
+        group_end = group_end.max(block.end_minute);
+    }
+    let len = blocks.len();
+    finish_group(&mut blocks[group_start..len], column_ends.len());
+    blocks
+}
+
+/**
+ * Gives every block of a group of overlapping blocks the column count of the group.
+ */
+fn finish_group(group: &mut [TimedBlock], columns: usize) {
+    for block in group {
+        block.columns = columns.max(1);
+    }
+}
+
+/**
+ * Assigns the all-day occurrences of a week to rows of the all-day strip.
+ *
+ * Returns:
+ * - The bars, longest first within each starting day, and the number of rows used.
+ */
+fn layout_all_day<'a>(
+    days: &[NaiveDate; 7],
+    occurrences: Vec<Occurrence<'a>>,
+) -> (Vec<AllDayBar<'a>>, usize) {
+    let column = |date: NaiveDate| (date - days[0]).num_days().clamp(0, 6) as usize;
+    let mut bars: Vec<AllDayBar> = occurrences
+        .into_iter()
+        .map(|occurrence| AllDayBar {
+            occurrence,
+            first: column(occurrence.time.start_date()),
+            last: column(occurrence.time.end_date()),
+            row: 0,
+        })
+        .collect();
+    bars.sort_by_key(|bar| (bar.first, std::cmp::Reverse(bar.last), bar.occurrence.id));
+
+    let mut row_ends: Vec<usize> = Vec::new();
+    for bar in &mut bars {
+        bar.row = match row_ends.iter().position(|last| *last < bar.first) {
+            Some(row) => row,
+            None => {
+                row_ends.push(0);
+                row_ends.len() - 1
+            }
+        };
+        row_ends[bar.row] = bar.last;
+    }
+    (bars, row_ends.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> NaiveDateTime {
+        date.and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn timed(store: &mut EventStore, title: &str, start: NaiveDateTime, end: NaiveDateTime) {
+        store.insert(Event::new(title, EventTime::timed(start, end).unwrap()));
+    }
+
+    /**
+     * Returns title, start minute, end minute, column and column count of every block.
+     */
+    fn blocks<'a>(layout: &'a WeekLayout) -> Vec<(&'a str, u32, u32, usize, usize)> {
+        layout
+            .timed
+            .iter()
+            .map(|block| {
+                (
+                    block.occurrence.event.title.as_str(),
+                    block.start_minute,
+                    block.end_minute,
+                    block.column,
+                    block.columns,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn weeks_start_on_the_configured_day() {
+        // October 16th 2026 is a Friday.
+        let day = date(2026, 10, 16);
+        assert_eq!(week_containing(day, Weekday::Mon), date(2026, 10, 12));
+        assert_eq!(week_containing(day, Weekday::Sun), date(2026, 10, 11));
+        assert_eq!(week_containing(day, Weekday::Sat), date(2026, 10, 10));
+        assert_eq!(week_containing(day, Weekday::Fri), day);
+    }
+
+    #[test]
+    fn blocks_are_proportional_to_the_duration() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 14);
+        timed(&mut store, "Lunch", at(day, 12, 0), at(day, 13, 30));
+        timed(&mut store, "Reminder", at(day, 17, 0), at(day, 17, 0));
+
+        let layout = WeekLayout::new(&store, date(2026, 10, 12));
+        assert_eq!(
+            blocks(&layout),
+            [
+                ("Lunch", 720, 810, 0, 1),
+                ("Reminder", 1020, 1020 + MIN_BLOCK_MINUTES, 0, 1)
+            ]
+        );
+        assert_eq!(layout.timed[0].day, 2);
+    }
+
+    #[test]
+    fn overlapping_events_are_laid_out_side_by_side() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 12);
+        timed(&mut store, "A", at(day, 9, 0), at(day, 11, 0));
+        timed(&mut store, "B", at(day, 9, 30), at(day, 10, 0));
+        timed(&mut store, "C", at(day, 10, 0), at(day, 10, 30));
+        timed(&mut store, "D", at(day, 10, 15), at(day, 12, 0));
+        timed(&mut store, "E", at(day, 13, 0), at(day, 14, 0));
+
+        let layout = WeekLayout::new(&store, day);
+        assert_eq!(
+            blocks(&layout),
+            [
+                ("A", 540, 660, 0, 3),
+                ("B", 570, 600, 1, 3),
+                ("C", 600, 630, 1, 3),
+                ("D", 615, 720, 2, 3),
+                ("E", 780, 840, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_crossing_midnight_are_split() {
+        let mut store = EventStore::new();
+        let day = date(2026, 10, 13);
+        let next = day.succ_opt().unwrap();
+        timed(&mut store, "Night shift", at(day, 22, 0), at(next, 6, 0));
+
+        let layout = WeekLayout::new(&store, date(2026, 10, 12));
+        assert_eq!(
+            blocks(&layout),
+            [
+                ("Night shift", 1320, MINUTES_PER_DAY, 0, 1),
+                ("Night shift", 0, 360, 0, 1)
+            ]
+        );
+        assert_eq!(layout.timed[0].day, 1);
+        assert_eq!(layout.timed[1].day, 2);
+    }
+
+    #[test]
+    fn all_day_events_are_stacked_in_rows() {
+        let mut store = EventStore::new();
+        let all_day = |start, end| EventTime::all_day(start, end).unwrap();
+        store.insert(Event::new(
+            "Conference",
+            all_day(date(2026, 10, 8), date(2026, 10, 13)),
+        ));
+        store.insert(Event::new(
+            "Holiday",
+            all_day(date(2026, 10, 13), date(2026, 10, 13)),
+        ));
+        store.insert(Event::new(
+            "Trip",
+            all_day(date(2026, 10, 16), date(2026, 10, 20)),
+        ));
+
+        let layout = WeekLayout::new(&store, date(2026, 10, 12));
+        let bars: Vec<_> = layout
+            .all_day
+            .iter()
+            .map(|bar| {
+                (
+                    bar.occurrence.event.title.as_str(),
+                    bar.first,
+                    bar.last,
+                    bar.row,
+                )
+            })
+            .collect();
+        assert_eq!(
+            bars,
+            [
+                ("Conference", 0, 1, 0),
+                ("Holiday", 1, 1, 1),
+                ("Trip", 4, 6, 0)
+            ]
+        );
+        assert_eq!(layout.all_day_rows, 2);
+        assert!(layout.timed.is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+use calendar_core::{BusinessCalendar, HolidayCalendar};
+use chrono::{Duration, NaiveDate, Weekday};
+
+/**
+ * Runs `check` for every day from 2025 to 2027 against calendars with
+ * different weekends, with and without the holidays of several countries.
+ */
+fn for_every_day(check: impl Fn(&BusinessCalendar, NaiveDate)) {
+    let weekends = [
+        vec![Weekday::Sat, Weekday::Sun],
+        vec![Weekday::Fri, Weekday::Sat],
+        vec![Weekday::Sun],
+    ];
+    let mut calendars = Vec::new();
+    for weekend in weekends {
+        let calendar = BusinessCalendar::new(weekend).unwrap();
+        calendars.push(calendar.clone());
+        for code in ["US", "GB", "DE-BY"] {
+            let holidays = HolidayCalendar::load(code, None).unwrap();
+            calendars.push(calendar.clone().with_holidays(holidays));
+        }
+    }
+
+    let first = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+    for calendar in &calendars {
+        for offset in 0..3 * 365 {
+            check(calendar, first + Duration::days(offset));
+        }
+    }
+}
+
+#[test]
+fn adding_and_counting_agree() {
+    for_every_day(|calendar, date| {
+        for days in [1, 2, 5, 10, 30] {
+            let later = calendar.add_business_days(date, days).unwrap();
+            assert!(calendar.is_business_day(later), "{date} + {days}");
+            let earlier = calendar.add_business_days(later, -days).unwrap();
+            assert!(earlier <= date, "{date} + {days} - {days}");
+            // `date` is only counted if it is a business day.
+            let counted = calendar.business_days_between(date, later);
+            let expected = if calendar.is_business_day(date) {
+                days
+            } else {
+                days - 1
+            };
+            assert_eq!(counted, expected, "{date} to {later}");
+        }
+    });
+}
+
+#[test]
+fn counting_matches_checking_every_day() {
+    for_every_day(|calendar, date| {
+        let end = date + Duration::days(17);
+        let mut expected = 0;
+        let mut day = date;
+        while day < end {
+            if calendar.is_business_day(day) {
+                expected += 1;
+            }
+            day = day.succ_opt().unwrap();
+        }
+        assert_eq!(
+            calendar.business_days_between(date, end),
+            expected,
+            "{date}"
+        );
+        assert_eq!(
+            calendar.business_days_between(end, date),
+            -expected,
+            "{date}"
+        );
+    });
+}
+
+#[test]
+fn next_and_previous_are_adjacent_business_days() {
+    for_every_day(|calendar, date| {
+        let next = calendar.next_business_day(date).unwrap();
+        let previous = calendar.previous_business_day(date).unwrap();
+        assert!(previous < date && date < next);
+        assert_eq!(
+            calendar.business_days_between(previous, next),
+            1 + calendar.is_business_day(date) as i64
+        );
+    });
+}
@@ -0,0 +1,145 @@
+use calendar_core::grid::{days_in_month, week_days, week_number, WeekNumbering};
+use calendar_core::{CellKind, MonthGrid};
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::HashSet;
+
+/**
+ * Runs `check` against the grid of every month from 1900 to 2100, once for
+ * every day the week can start with. The range covers every combination of
+ * month length and weekday of the first day.
+ */
+fn for_every_month(check: impl Fn(&MonthGrid)) {
+    for week_start in week_days(Weekday::Mon) {
+        for year in 1900..=2100 {
+            for month in 1..=12 {
+                let grid = MonthGrid::new(year, month, week_start)
+                    .unwrap_or_else(|| panic!("no grid for {year}-{month}"));
+                check(&grid);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_month_shape_is_covered() {
+    let mut shapes = HashSet::new();
+    for year in 1900..=2100 {
+        for month in 1..=12 {
+            let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+            shapes.insert((days_in_month(year, month).unwrap(), first.weekday()));
+        }
+    }
+    assert_eq!(shapes.len(), 4 * 7);
+}
+
+#[test]
+fn grids_consist_of_whole_weeks_starting_on_the_week_start() {
+    for_every_month(|grid| {
+        let len = grid.cells().len();
+        assert_eq!(len % 7, 0, "{}-{}", grid.year(), grid.month());
+        assert!((28..=42).contains(&len), "{}-{}", grid.year(), grid.month());
+        for week in grid.weeks() {
+            assert_eq!(week[0].weekday, grid.week_start());
+            assert_eq!(week[6].weekday, grid.week_start().pred());
+        }
+    });
+}
+
+#[test]
+fn header_labels_match_the_grid_columns() {
+    for_every_month(|grid| {
+        let header = week_days(grid.week_start());
+        for week in grid.weeks() {
+            let columns: Vec<Weekday> = week.iter().map(|cell| cell.weekday).collect();
+            assert_eq!(columns, header);
+        }
+    });
+}
+
+#[test]
+fn cells_are_consecutive_days() {
+    for_every_month(|grid| {
+        for pair in grid.cells().windows(2) {
+            assert_eq!(pair[0].date.succ_opt(), Some(pair[1].date));
+        }
+        for cell in grid.cells() {
+            assert_eq!(cell.weekday, cell.date.weekday());
+        }
+    });
+}
+
+#[test]
+fn in_month_cells_cover_the_whole_month() {
+    for_every_month(|grid| {
+        let in_month: Vec<_> = grid.cells().iter().filter(|cell| cell.in_month).collect();
+        assert_eq!(
+            in_month.len() as u32,
+            days_in_month(grid.year(), grid.month()).unwrap()
+        );
+        assert_eq!(in_month[0].date.day(), 1);
+        for cell in &in_month {
+            assert_eq!(
+                (cell.date.year(), cell.date.month()),
+                (grid.year(), grid.month())
+            );
+        }
+    });
+}
+
+#[test]
+fn padding_never_fills_a_whole_week() {
+    for_every_month(|grid| {
+        let first_week = grid.weeks().next().unwrap();
+        let last_week = grid.weeks().last().unwrap();
+        assert!(first_week.iter().any(|cell| cell.in_month));
+        assert!(last_week.iter().any(|cell| cell.in_month));
+    });
+}
+
+#[test]
+fn cell_kinds_are_ordered_previous_current_next() {
+    for_every_month(|grid| {
+        let kinds: Vec<CellKind> = grid.cells().iter().map(|cell| cell.kind).collect();
+        let previous = kinds
+            .iter()
+            .take_while(|kind| **kind == CellKind::PreviousMonth)
+            .count();
+        let next = kinds
+            .iter()
+            .rev()
+            .take_while(|kind| **kind == CellKind::NextMonth)
+            .count();
+        let current = kinds.len() - previous - next;
+        assert_eq!(
+            current as u32,
+            days_in_month(grid.year(), grid.month()).unwrap()
+        );
+        for cell in grid.cells() {
+            assert_eq!(cell.in_month, cell.kind == CellKind::CurrentMonth);
+        }
+    });
+}
+
+#[test]
+fn week_numbers_match_whole_weeks_of_their_scheme() {
+    for_every_month(|grid| {
+        for numbering in [WeekNumbering::Iso, WeekNumbering::Us, WeekNumbering::Simple] {
+            let numbers = grid.week_numbers(numbering);
+            assert_eq!(numbers.len(), grid.weeks().count());
+            assert!(numbers.iter().all(|week| (1..=53).contains(week)));
+        }
+        // Rows aligned with the weeks of a scheme have a single number.
+        let aligned = match grid.week_start() {
+            Weekday::Mon => Some(WeekNumbering::Iso),
+            Weekday::Sun => Some(WeekNumbering::Us),
+            _ => None,
+        };
+        if let Some(numbering) = aligned {
+            for (week, number) in grid.weeks().zip(grid.week_numbers(numbering)) {
+                for cell in week {
+                    assert_eq!(week_number(cell.date, numbering), number, "{}", cell.date);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,628 @@
+use calendar_core::ical::{self, ExportRange};
+use calendar_core::storage::event_json;
+use calendar_core::{
+    Clock, DraftError, EditScope, Event, EventDraft, EventId, EventStore, Locale, MonthCursor,
+    NameStyle, Settings, Storage, TextCalendar, WeekNumbering,
+};
+use chrono::prelude::*;
+use chrono::Duration;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+/**
+ * Exit code for failures outside the user's control, e.g. a calendar file
+ * that cannot be read or output that cannot be written.
+ */
+const EXIT_FAILURE: i32 = 1;
+
+/**
+ * Exit code for invalid event fields, the same as clap's usage error code.
+ */
+const EXIT_INVALID: i32 = 2;
+
+/**
+ * Exit code for an event identifier that does not exist.
+ */
+const EXIT_NOT_FOUND: i32 = 3;
+
+/**
+ * Number of days `list` covers when no last date is given.
+ */
+const LIST_DAYS: i64 = 7;
+
+/**
+ * The command line of the application.
+ *
+ * Without a subcommand the calendar window is opened, importing the
+ * iCalendar files given as arguments.
+ */
+#[derive(Debug, Parser)]
+#[command(version, about = "A desktop calendar", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /** iCalendar (.ics) files to import before opening the window. */
+    pub files: Vec<String>,
+}
+
+/**
+ * The headless commands, run without opening the window. The event commands
+ * work on the calendar file of the window.
+ */
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /** Print a month calendar like cal(1). */
+    Cal(CalArgs),
+    /** Add an event. */
+    Add(AddArgs),
+    /** List the events between two dates. */
+    List(ListArgs),
+    /** Change the fields of an event. */
+    Edit(EditArgs),
+    /** Delete an event. */
+    Delete(DeleteArgs),
+    /** Export events as iCalendar. */
+    Export(ExportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CalArgs {
+    /** The month to print (1-12); the current month by default. */
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=12))]
+    month: Option<u32>,
+    /** The year to print; the whole year is printed unless a month is given. */
+    #[arg(long, allow_negative_numbers = true)]
+    year: Option<i32>,
+    /** Print the previous, current and next month. */
+    #[arg(short = '3', conflicts_with = "whole_year")]
+    three: bool,
+    /** Print the whole year. */
+    #[arg(short = 'y', long = "whole-year")]
+    whole_year: bool,
+    /** Start the weeks on Monday. */
+    #[arg(short = 'M', long, conflicts_with = "sunday")]
+    monday: bool,
+    /** Start the weeks on Sunday. */
+    #[arg(short = 'S', long)]
+    sunday: bool,
+    /** Number the weeks, with the scheme of the settings or else ISO 8601. */
+    #[arg(short, long)]
+    week_numbers: bool,
+}
+
+/**
+ * The optional fields of an event, shared by `add` and `edit`. They are
+ * validated by `EventDraft` like the fields of the event dialog.
+ */
+#[derive(Debug, Args)]
+pub struct EventFields {
+    /** The start time (HH:MM); events without one last all day. */
+    #[arg(long)]
+    start: Option<String>,
+    /** The end time (HH:MM); an hour after the start by default. */
+    #[arg(long)]
+    end: Option<String>,
+    /** The last date (YYYY-MM-DD) of events lasting several days. */
+    #[arg(long)]
+    end_date: Option<String>,
+    #[arg(long)]
+    location: Option<String>,
+    #[arg(long)]
+    notes: Option<String>,
+    /** The color (#rrggbb) of the event. */
+    #[arg(long)]
+    color: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct AddArgs {
+    title: String,
+    /** The date (YYYY-MM-DD) of the event. */
+    #[arg(long)]
+    date: String,
+    #[command(flatten)]
+    fields: EventFields,
+    /** Print the added event as JSON. */
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /** The first date (YYYY-MM-DD) to list; today by default. */
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /** The last date (YYYY-MM-DD) to list; a week after the first by default. */
+    #[arg(long)]
+    to: Option<NaiveDate>,
+    /** Print the occurrences as a JSON array. */
+    #[arg(long)]
+    json: bool,
+}
+
+/**
+ * Selects the occurrences of a recurring event that `edit` and `delete`
+ * apply to. Events that do not repeat need neither option.
+ */
+#[derive(Debug, Args)]
+pub struct OccurrenceArgs {
+    /** The date (YYYY-MM-DD) of the occurrence, as printed by `list`. */
+    #[arg(long)]
+    occurrence: Option<NaiveDate>,
+    /** Which occurrences of a recurring event to change; required for those. */
+    #[arg(long, value_enum)]
+    scope: Option<Scope>,
+}
+
+/**
+ * The occurrences of a recurring event a change applies to.
+ */
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Scope {
+    /** Only the occurrence given with --occurrence. */
+    This,
+    /** The occurrence given with --occurrence and all later ones. */
+    Following,
+    /** Every occurrence. */
+    All,
+}
+
+impl From<Scope> for EditScope {
+    fn from(scope: Scope) -> EditScope {
+        match scope {
+            Scope::This => EditScope::ThisOccurrence,
+            Scope::Following => EditScope::ThisAndFollowing,
+            Scope::All => EditScope::All,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct EditArgs {
+    /** The identifier of the event, as printed by `list`. */
+    id: u64,
+    #[command(flatten)]
+    occurrences: OccurrenceArgs,
+    #[arg(long)]
+    title: Option<String>,
+    /** Move the event to this date (YYYY-MM-DD), ending on it unless --end-date is given. */
+    #[arg(long)]
+    date: Option<String>,
+    #[command(flatten)]
+    fields: EventFields,
+    /** Make the event last all day. */
+    #[arg(long, conflicts_with_all = ["start", "end"])]
+    all_day: bool,
+    /** Print the edited event as JSON. */
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteArgs {
+    /** The identifier of the event, as printed by `list`. */
+    id: u64,
+    #[command(flatten)]
+    occurrences: OccurrenceArgs,
+    /** Print the deleted event as JSON. */
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /** Export only events on or after this date (YYYY-MM-DD). */
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /** Export only events on or before this date (YYYY-MM-DD). */
+    #[arg(long)]
+    to: Option<NaiveDate>,
+    /** The file to write; standard output by default. */
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /** Export the events as a JSON array instead of iCalendar. */
+    #[arg(long)]
+    json: bool,
+}
+
+impl EventFields {
+    /**
+     * Copies the fields given on the command line into `draft`.
+     */
+    fn fill(self, draft: &mut EventDraft) {
+        let fields = [
+            (self.start, &mut draft.start_time),
+            (self.end, &mut draft.end_time),
+            (self.end_date, &mut draft.end_date),
+            (self.location, &mut draft.location),
+            (self.notes, &mut draft.notes),
+            (self.color, &mut draft.color),
+        ];
+        for (value, field) in fields {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+    }
+}
+
+/**
+ * A command that could not be completed, with the exit code reporting it.
+ */
+struct Failure {
+    code: i32,
+    message: String,
+}
+
+impl Failure {
+    fn new(code: i32, message: impl fmt::Display) -> Failure {
+        Failure {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<DraftError> for Failure {
+    fn from(err: DraftError) -> Failure {
+        Failure::new(EXIT_INVALID, format!("Invalid event: {err}"))
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Failure {
+        Failure::new(EXIT_FAILURE, err)
+    }
+}
+
+/**
+ * Function to run a headless command.
+ *
+ * Parameters:
+ * - `command`: The command to run.
+ * - `settings`: The settings providing the defaults, e.g. the week start.
+ * - `clock`: The source of the current date and time.
+ *
+ * Returns:
+ * - The exit code of the process.
+ */
+pub fn run(command: Command, settings: &Settings, clock: &dyn Clock) -> i32 {
+    let locale = settings.locale();
+    let today = clock.today(settings);
+    let result = match command {
+        Command::Cal(args) => cal(args, settings, today),
+        Command::Add(args) => add(args, &locale),
+        Command::List(args) => list(args, &locale, settings, today),
+        Command::Edit(args) => edit(args, &locale),
+        Command::Delete(args) => delete(args, &locale),
+        Command::Export(args) => export(args, clock.now()),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(failure) => {
+            eprintln!("{}", failure.message);
+            failure.code
+        }
+    }
+}
+
+/**
+ * Function to print the months selected by the `cal` arguments.
+ */
+fn cal(args: CalArgs, settings: &Settings, today: NaiveDate) -> Result<(), Failure> {
+    let week_start = match (args.monday, args.sunday) {
+        (true, _) => Weekday::Mon,
+        (_, true) => Weekday::Sun,
+        _ => settings.week_start,
+    };
+    let week_numbers = args
+        .week_numbers
+        .then(|| settings.week_numbers.unwrap_or(WeekNumbering::Iso));
+    let stdout = io::stdout();
+    let calendar = TextCalendar {
+        locale: settings.locale(),
+        week_start,
+        week_numbers,
+        // Mark today like cal does, unless the output is piped.
+        highlight: stdout.is_terminal().then_some(today),
+    };
+
+    let year = args.year.unwrap_or(today.year());
+    let month = args.month.unwrap_or(match args.year {
+        Some(_) => 1,
+        None => today.month(),
+    });
+    let Some(cursor) = MonthCursor::new(year, month) else {
+        return Err(Failure::new(
+            EXIT_INVALID,
+            format!("Year {year} is out of range"),
+        ));
+    };
+
+    let whole_year =
+        args.whole_year || (args.year.is_some() && args.month.is_none() && !args.three);
+    let lines = if whole_year {
+        calendar.year(year).unwrap_or_default()
+    } else if args.three {
+        calendar.months(cursor.previous_month(), 3, 3, true)
+    } else {
+        calendar.month(cursor, true)
+    };
+
+    let mut out = stdout.lock();
+    for line in lines {
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn add(args: AddArgs, locale: &Locale) -> Result<(), Failure> {
+    let (storage, mut events) = open_storage()?;
+    let mut draft = EventDraft {
+        title: args.title,
+        start_date: args.date,
+        ..EventDraft::default()
+    };
+    args.fields.fill(&mut draft);
+    let id = events.insert(draft.to_event()?);
+    let uid = events.get(id).and_then(|event| event.uid.clone());
+    save(&storage, &mut events)?;
+    // The store assigns the UID, so print the event as it was stored. Saving
+    // renumbers it if the window added an event in the meantime.
+    let id = uid.and_then(|uid| events.find_by_uid(&uid)).unwrap_or(id);
+    let event = events.get(id).expect("the event was just inserted");
+    print_event(id, event, "Added", args.json, locale)
+}
+
+fn list(
+    args: ListArgs,
+    locale: &Locale,
+    settings: &Settings,
+    today: NaiveDate,
+) -> Result<(), Failure> {
+    let from = args.from.unwrap_or(today);
+    let to = match args.to {
+        Some(to) if to < from => {
+            return Err(Failure::new(EXIT_INVALID, "--to is before --from"));
+        }
+        Some(to) => to,
+        None => from
+            .checked_add_signed(Duration::days(LIST_DAYS - 1))
+            .unwrap_or(NaiveDate::MAX),
+    };
+    let (_, mut events) = open_storage()?;
+    events.set_zone(settings.zone());
+
+    // Occurrences of recurring events are printed with their own time.
+    let occurrences: Vec<(EventId, Event)> = events
+        .occurrences_between(from, to)
+        .into_iter()
+        .map(|occurrence| {
+            let event = Event {
+                time: occurrence.time,
+                ..occurrence.event.clone()
+            };
+            (occurrence.id, event)
+        })
+        .collect();
+
+    let mut out = io::stdout().lock();
+    if args.json {
+        let list = occurrences
+            .iter()
+            .map(|(id, event)| event_json(*id, event))
+            .collect();
+        writeln!(out, "{}", Value::Array(list))?;
+        return Ok(());
+    }
+    for (id, event) in &occurrences {
+        writeln!(out, "{}", describe(*id, event, locale))?;
+    }
+    Ok(())
+}
+
+fn edit(args: EditArgs, locale: &Locale) -> Result<(), Failure> {
+    let (storage, mut events) = open_storage()?;
+    let id = EventId(args.id);
+    let (start, scope) = select_occurrences(&events, id, &args.occurrences)?;
+    let event = events
+        .occurrence(id, start)
+        .expect("the occurrence was just selected");
+    let mut draft = EventDraft::from_event(&event);
+    if let Some(title) = args.title {
+        draft.title = title;
+    }
+    if let Some(date) = args.date {
+        draft.start_date = date;
+        draft.end_date.clear();
+    }
+    if args.all_day {
+        draft.start_time.clear();
+        draft.end_time.clear();
+    }
+    args.fields.fill(&mut draft);
+    let edited = draft.apply(&event)?;
+    let uid = events
+        .update_occurrence(id, start, scope, edited)
+        .and_then(|id| events.get(id))
+        .and_then(|event| event.uid.clone());
+    save(&storage, &mut events)?;
+    // Saving may renumber events added meanwhile, so look it up again.
+    let id = uid
+        .and_then(|uid| events.find_by_uid(&uid))
+        .expect("the event was just updated");
+    let event = events.get(id).expect("the event was just updated");
+    print_event(id, event, "Updated", args.json, locale)
+}
+
+fn delete(args: DeleteArgs, locale: &Locale) -> Result<(), Failure> {
+    let (storage, mut events) = open_storage()?;
+    let id = EventId(args.id);
+    let (start, scope) = select_occurrences(&events, id, &args.occurrences)?;
+    let event = events
+        .occurrence(id, start)
+        .expect("the occurrence was just selected");
+    events.remove_occurrence(id, start, scope);
+    save(&storage, &mut events)?;
+    print_event(id, &event, "Deleted", args.json, locale)
+}
+
+/**
+ * Function to find the occurrences of an event a command applies to.
+ *
+ * Recurring events need a scope, and a date unless the scope is `all`, so
+ * that a command never changes the whole series by accident.
+ *
+ * Returns:
+ * - The start of the selected occurrence in the time zone of the event, the
+ *   first one for events that do not repeat or `--scope all`, and the scope.
+ */
+fn select_occurrences(
+    events: &EventStore,
+    id: EventId,
+    args: &OccurrenceArgs,
+) -> Result<(NaiveDateTime, EditScope), Failure> {
+    let event = events.get(id).ok_or_else(|| not_found(id))?;
+    let first = event.time.start();
+    if event.recurrence.is_none() {
+        return Ok((first, EditScope::All));
+    }
+    let scope = args.scope.ok_or_else(|| {
+        Failure::new(
+            EXIT_INVALID,
+            format!(
+                "Event {} repeats; choose the occurrences with --scope this, following or all",
+                id.0
+            ),
+        )
+    })?;
+    let date = match (scope, args.occurrence) {
+        (Scope::All, None) => return Ok((first, scope.into())),
+        (_, Some(date)) => date,
+        (_, None) => {
+            return Err(Failure::new(
+                EXIT_INVALID,
+                "--occurrence is required unless --scope is all",
+            ))
+        }
+    };
+    events
+        .occurrences_between(date, date)
+        .into_iter()
+        .find(|occurrence| occurrence.id == id && occurrence.time.start_date() == date)
+        .map(|occurrence| (occurrence.recurrence_id, scope.into()))
+        .ok_or_else(|| {
+            Failure::new(
+                EXIT_NOT_FOUND,
+                format!("Event {} has no occurrence on {}", id.0, date),
+            )
+        })
+}
+
+fn export(args: ExportArgs, now: DateTime<Utc>) -> Result<(), Failure> {
+    let (_, events) = open_storage()?;
+    let content = match (args.json, args.from, args.to) {
+        (false, None, None) => ical::export(&events, ExportRange::All, now),
+        (false, from, to) => ical::export(
+            &events,
+            ExportRange::Dates(from.unwrap_or(NaiveDate::MIN), to.unwrap_or(NaiveDate::MAX)),
+            now,
+        ),
+        (true, from, to) => {
+            let selected = match (from, to) {
+                (None, None) => events.iter().collect(),
+                (from, to) => events
+                    .events_between(from.unwrap_or(NaiveDate::MIN), to.unwrap_or(NaiveDate::MAX)),
+            };
+            let list = selected
+                .into_iter()
+                .map(|(id, event)| event_json(id, event))
+                .collect();
+            format!("{}\n", Value::Array(list))
+        }
+    };
+    match args.output {
+        Some(path) => std::fs::write(&path, content).map_err(|err| {
+            Failure::new(
+                EXIT_FAILURE,
+                format!("Could not write {}: {}", path.display(), err),
+            )
+        }),
+        None => Ok(io::stdout().lock().write_all(content.as_bytes())?),
+    }
+}
+
+/**
+ * Function to load the calendar file of the window.
+ *
+ * Unlike the window, which starts with an empty calendar, the commands fail
+ * if the file cannot be loaded, so that they never overwrite it.
+ */
+fn open_storage() -> Result<(Storage, EventStore), Failure> {
+    let storage = Storage::default_location()
+        .ok_or_else(|| Failure::new(EXIT_FAILURE, "No data directory available"))?;
+    match storage.load() {
+        Ok(events) => Ok((storage, events)),
+        Err(err) => Err(Failure::new(
+            EXIT_FAILURE,
+            format!("Could not load {}: {}", storage.path().display(), err),
+        )),
+    }
+}
+
+fn save(storage: &Storage, events: &mut EventStore) -> Result<(), Failure> {
+    storage.save(events).map_err(|err| {
+        Failure::new(
+            EXIT_FAILURE,
+            format!("Could not save {}: {}", storage.path().display(), err),
+        )
+    })
+}
+
+fn not_found(id: EventId) -> Failure {
+    Failure::new(EXIT_NOT_FOUND, format!("No event with id {}", id.0))
+}
+
+/**
+ * Function to print an event that has just been changed.
+ *
+ * Parameters:
+ * - `action`: What happened to the event, e.g. "Added".
+ * - `json`: Whether to print the event as JSON instead.
+ */
+fn print_event(
+    id: EventId,
+    event: &Event,
+    action: &str,
+    json: bool,
+    locale: &Locale,
+) -> Result<(), Failure> {
+    let mut out = io::stdout().lock();
+    match json {
+        true => writeln!(out, "{}", event_json(id, event))?,
+        false => writeln!(out, "{action} {}", describe(id, event, locale))?,
+    }
+    Ok(())
+}
+
+/**
+ * Returns a one-line description of an event, e.g.
+ * "12  Fri 16 Oct 2026  09:30–10:30  Dentist (Main St 1)".
+ */
+fn describe(id: EventId, event: &Event, locale: &Locale) -> String {
+    let mut line = format!(
+        "{}  {}  {}  {}",
+        id.0,
+        locale.date(event.time.start_date(), NameStyle::Short),
+        crate::time_label(&event.time, locale),
+        event.title
+    );
+    if let Some(location) = &event.location {
+        line.push_str(&format!(" ({location})"));
+    }
+    line
+}
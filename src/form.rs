@@ -0,0 +1,248 @@
+use crate::{EventForm, EventFormErrors};
+use calendar_core::{DraftError, DraftField, EditScope, EventDraft, Frequency, RecurrenceRule};
+use chrono::NaiveDate;
+use slint::SharedString;
+
+/**
+ * The frequencies offered by the "repeats" choice of the event dialog, after
+ * "does not repeat". The choice after them is a custom RRULE value.
+ */
+const REPEAT_CHOICES: [Frequency; 4] = [
+    Frequency::Daily,
+    Frequency::Weekly,
+    Frequency::Monthly,
+    Frequency::Yearly,
+];
+
+/**
+ * The index of the custom rule in the "repeats" choice.
+ */
+const CUSTOM_REPEAT: i32 = REPEAT_CHOICES.len() as i32 + 1;
+
+/**
+ * The choices of the "applies to" field shown when editing a recurring event.
+ */
+const EDIT_SCOPES: [EditScope; 3] = [
+    EditScope::ThisOccurrence,
+    EditScope::ThisAndFollowing,
+    EditScope::All,
+];
+
+/**
+ * The times a new timed event starts and ends at unless entered otherwise.
+ */
+const DEFAULT_START: &str = "09:00";
+const DEFAULT_END: &str = "10:00";
+
+/**
+ * Function to create the draft of a new event.
+ *
+ * Parameters:
+ * - `date`: The date the event takes place on.
+ */
+pub fn new_draft(date: NaiveDate) -> EventDraft {
+    let date = date.format("%Y-%m-%d").to_string();
+    EventDraft {
+        start_date: date.clone(),
+        start_time: DEFAULT_START.to_string(),
+        end_date: date,
+        end_time: DEFAULT_END.to_string(),
+        ..EventDraft::default()
+    }
+}
+
+/**
+ * Function to fill the fields of the event dialog from a draft.
+ *
+ * All-day drafts get the default times, so that they are there when the
+ * all-day box is unchecked.
+ */
+pub fn event_form(draft: &EventDraft) -> EventForm {
+    let all_day = draft.start_time.trim().is_empty() && draft.end_time.trim().is_empty();
+    let or_default =
+        |time: &str, default: &str| SharedString::from(if all_day { default } else { time });
+    let rule = draft.recurrence.parse::<RecurrenceRule>().ok();
+    let repeat = match draft.recurrence.trim() {
+        "" => 0,
+        _ => REPEAT_CHOICES
+            .iter()
+            .position(|frequency| rule.as_ref() == Some(&RecurrenceRule::new(*frequency)))
+            .map_or(CUSTOM_REPEAT, |index| index as i32 + 1),
+    };
+    EventForm {
+        title: SharedString::from(draft.title.as_str()),
+        all_day,
+        start_date: SharedString::from(draft.start_date.as_str()),
+        start_time: or_default(&draft.start_time, DEFAULT_START),
+        end_date: SharedString::from(draft.end_date.as_str()),
+        end_time: or_default(&draft.end_time, DEFAULT_END),
+        time_zone: SharedString::from(draft.time_zone.as_str()),
+        repeat,
+        recurrence: SharedString::from(draft.recurrence.as_str()),
+        reminders: SharedString::from(draft.reminders.as_str()),
+        calendar: SharedString::from(draft.calendar.as_str()),
+        location: SharedString::from(draft.location.as_str()),
+        notes: SharedString::from(draft.notes.as_str()),
+        scope: 0,
+    }
+}
+
+/**
+ * Function to read which occurrences of a recurring event an edit in the
+ * event dialog applies to.
+ */
+pub fn edit_scope(form: &EventForm) -> EditScope {
+    EDIT_SCOPES
+        .get(form.scope as usize)
+        .copied()
+        .unwrap_or(EditScope::ThisOccurrence)
+}
+
+/**
+ * Function to read the fields of the event dialog into a draft.
+ *
+ * Parameters:
+ * - `form`: The fields as entered.
+ * - `color`: The color of the event, which the dialog does not show.
+ */
+pub fn event_draft(form: &EventForm, color: &str) -> EventDraft {
+    // All-day events have neither times nor a time zone.
+    let timed = |text: &SharedString| {
+        if form.all_day {
+            String::new()
+        } else {
+            text.to_string()
+        }
+    };
+    let recurrence = match form.repeat {
+        0 => String::new(),
+        CUSTOM_REPEAT => form.recurrence.to_string(),
+        index => REPEAT_CHOICES
+            .get(index as usize - 1)
+            .map(|frequency| RecurrenceRule::new(*frequency).to_string())
+            .unwrap_or_default(),
+    };
+    EventDraft {
+        title: form.title.to_string(),
+        start_date: form.start_date.to_string(),
+        start_time: timed(&form.start_time),
+        end_date: form.end_date.to_string(),
+        end_time: timed(&form.end_time),
+        location: form.location.to_string(),
+        notes: form.notes.to_string(),
+        color: color.to_string(),
+        time_zone: timed(&form.time_zone),
+        recurrence,
+        reminders: form.reminders.to_string(),
+        calendar: form.calendar.to_string(),
+    }
+}
+
+/**
+ * Function to place the problems of a draft next to the fields of the event
+ * dialog. Problems with the date and the time of the start or end share one
+ * message.
+ */
+pub fn form_errors(errors: &[(DraftField, DraftError)]) -> EventFormErrors {
+    let mut form = EventFormErrors::default();
+    for (field, error) in errors {
+        let message = SharedString::from(error.to_string());
+        let target = match field {
+            DraftField::Title => &mut form.title,
+            DraftField::StartDate | DraftField::StartTime => &mut form.start,
+            DraftField::EndDate | DraftField::EndTime => &mut form.end,
+            DraftField::TimeZone => &mut form.time_zone,
+            DraftField::Recurrence => &mut form.recurrence,
+            DraftField::Reminders => &mut form.reminders,
+            // The dialog keeps the color of the event as it is.
+            DraftField::Color => continue,
+        };
+        if target.is_empty() {
+            *target = message;
+        }
+    }
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar_core::{EventStore, Settings};
+
+    #[test]
+    fn forms_round_trip_through_drafts() {
+        let mut draft = new_draft(NaiveDate::from_ymd_opt(2026, 10, 16).unwrap());
+        draft.title = "Standup".to_string();
+        draft.recurrence = "FREQ=WEEKLY".to_string();
+        draft.reminders = "15m".to_string();
+        draft.color = "#ff0000".to_string();
+        let form = event_form(&draft);
+        assert_eq!(form.repeat, 2);
+        assert!(!form.all_day);
+        assert_eq!(event_draft(&form, "#ff0000"), draft);
+
+        draft.recurrence = "FREQ=WEEKLY;BYDAY=MO,WE".to_string();
+        let form = event_form(&draft);
+        assert_eq!(form.repeat, CUSTOM_REPEAT);
+        assert_eq!(event_draft(&form, "#ff0000"), draft);
+
+        // Checking the all-day box drops the times.
+        let mut all_day = event_form(&draft);
+        all_day.all_day = true;
+        all_day.time_zone = SharedString::from("Europe/Berlin");
+        let all_day = event_draft(&all_day, "#ff0000");
+        assert_eq!(all_day.start_time, "");
+        assert_eq!(all_day.time_zone, "");
+        assert!(event_form(&all_day).all_day);
+        assert_eq!(event_form(&all_day).start_time, DEFAULT_START);
+
+        // Edits apply to the selected occurrence unless chosen otherwise.
+        let mut form = event_form(&draft);
+        assert_eq!(edit_scope(&form), EditScope::ThisOccurrence);
+        form.scope = 2;
+        assert_eq!(edit_scope(&form), EditScope::All);
+    }
+
+    #[test]
+    fn times_are_entered_in_the_chosen_time_zone() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut form = event_form(&new_draft(day));
+        form.title = SharedString::from("Call");
+        form.start_time = SharedString::from("10:00");
+        form.end_time = SharedString::from("11:00");
+        form.time_zone = SharedString::from("America/New_York");
+        let event = event_draft(&form, "#ff0000").validate(None).unwrap();
+
+        let settings = Settings {
+            time_zone: Some("Europe/Berlin".to_string()),
+            ..Settings::default()
+        };
+        let mut events = EventStore::new();
+        events.set_zone(settings.zone());
+        let id = events.insert(event);
+        let shown = events.events_on(day);
+        assert_eq!(shown[0].start(), day.and_hms_opt(16, 0, 0).unwrap());
+
+        // Editing shows the times as entered, in their own zone.
+        let edited = events.occurrence(id, shown[0].recurrence_id).unwrap();
+        let form = event_form(&EventDraft::from_event(&edited));
+        assert_eq!(form.start_time, "10:00");
+        assert_eq!(form.time_zone, "America/New_York");
+    }
+
+    #[test]
+    fn problems_are_shown_next_to_their_fields() {
+        let draft = EventDraft {
+            start_date: "2026-10-16".to_string(),
+            start_time: "9am".to_string(),
+            end_date: "2026-10-15".to_string(),
+            reminders: "soon".to_string(),
+            ..EventDraft::default()
+        };
+        let errors = form_errors(&draft.validate(None).unwrap_err());
+        assert_eq!(errors.title, "the title is empty");
+        assert_eq!(errors.start, "\"9am\" is not a time (HH:MM)");
+        assert_eq!(errors.end, "");
+        assert!(errors.reminders.contains("soon"));
+    }
+}
@@ -1,248 +1,1664 @@
+mod cli;
+mod form;
+mod notify;
+
+use calendar_core::grid::{week_days, WeekNumbering};
+use calendar_core::ical::{self, ExportRange};
+use calendar_core::quick_add;
+use calendar_core::week::week_containing;
+use calendar_core::week::TimedBlock;
+use calendar_core::{
+    Agenda, Alert, BusinessCalendar, Clock, DayLayout, DayWatch, Event, EventDraft, EventId,
+    EventStore, EventTime, Frequency, Holiday, HolidayCalendar, Locale, MonthCursor, MonthGrid,
+    NameStyle, Occurrence, ReminderState, Scheduler, Settings, SlotSize, Storage, SystemClock,
+    WeekLayout,
+};
 use chrono::prelude::*;
-use datetimeutils::{days_in_month, month_from_index, month_string};
-use slint::{SharedString, VecModel};
+use chrono::{Duration, Months};
+use clap::Parser;
+use notify::{Action, DbusNotifier, Notification, Notifications, Notifier};
+use slint::{ModelRc, SharedString, VecModel};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 slint::include_modules!();
 
 /**
- * Function to calculate the number of days from the previous month that
- * should be displayed at the start of the current month's calendar grid.
- * The calculation is based on the weekday of the first day of the current month.
+ * Maximum number of events listed in a calendar cell before the rest are
+ * summarized as "+N more".
  */
-fn get_last_days_of_prev_month(weekday: Weekday) -> u32 {
-    match weekday {
-        Weekday::Sun => 0, // If the first day is Sunday, no previous month days are needed.
-        Weekday::Mon => 1, // If the first day is Monday, 1 day from the previous month is needed.
-        Weekday::Tue => 2, // Continue similarly for other weekdays.
-        Weekday::Wed => 3,
-        Weekday::Thu => 4,
-        Weekday::Fri => 5,
-        Weekday::Sat => 6,
-    }
+const MAX_EVENTS_PER_CELL: usize = 2;
+
+/**
+ * The days the week can start with, in the order of the choices in the UI.
+ */
+const WEEK_STARTS: [Weekday; 3] = [Weekday::Sun, Weekday::Mon, Weekday::Sat];
+
+/**
+ * The week numbering schemes, in the order of the choices in the UI; `None`
+ * hides the week numbers.
+ */
+const WEEK_NUMBERINGS: [Option<WeekNumbering>; 4] = [
+    None,
+    Some(WeekNumbering::Iso),
+    Some(WeekNumbering::Us),
+    Some(WeekNumbering::Simple),
+];
+
+/**
+ * How often the reminders are checked, in seconds.
+ */
+const REMINDER_POLL_SECONDS: u64 = 15;
+
+/**
+ * How long a snoozed reminder stays hidden, in minutes.
+ */
+const SNOOZE_MINUTES: i64 = 10;
+
+/**
+ * How the views pass the start of the occurrence to open to `edit-event`.
+ */
+const OCCURRENCE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/**
+ * The layouts the calendar can be displayed in.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Month,
+    Week,
+    Day,
+    Year,
+    Agenda,
+    Business,
 }
 
 /**
- * Function to calculate the number of days from the next month that
- * should be displayed at the end of the current month's calendar grid.
- * The calculation is based on the weekday of the last day of the current month.
+ * State shared between the UI callbacks.
  */
-fn get_first_days_of_next_month(weekday: Weekday) -> u32 {
-    match weekday {
-        Weekday::Sun => 6, // If the last day is Sunday, 6 days from the next month are needed.
-        Weekday::Mon => 5, // If the last day is Monday, 5 days from the next month are needed.
-        Weekday::Tue => 4, // Continue similarly for other weekdays.
-        Weekday::Wed => 3,
-        Weekday::Thu => 2,
-        Weekday::Fri => 1,
-        Weekday::Sat => 0, // If the last day is Saturday, no next month days are needed.
+struct AppState {
+    view: View,
+    cursor: MonthCursor,
+    // First day of the week shown in the week view.
+    week: NaiveDate,
+    // The date shown in the day view.
+    day: NaiveDate,
+    // The dates listed by the agenda view.
+    agenda: Agenda,
+    events: EventStore,
+    storage: Option<Storage>,
+    settings: Settings,
+    settings_path: Option<PathBuf>,
+    // Where the scheduler's state is kept between runs, if it could be read.
+    reminders_path: Option<PathBuf>,
+    // The holidays of the configured region, if any.
+    holidays: Option<HolidayCalendar>,
+    // The language month and weekday names are displayed in.
+    locale: Locale,
+    selected: Option<NaiveDate>,
+    // Fires the reminders of the events.
+    reminders: Scheduler,
+    // Shows the reminders that fire on the desktop as well.
+    notifications: Notifications,
+    // Where the current date and time come from.
+    clock: Box<dyn Clock>,
+    // The date today was last highlighted as.
+    day_watch: DayWatch,
+    // The event open in the event dialog with the start of the selected
+    // occurrence, None for a new event.
+    edited: Option<(EventId, NaiveDateTime)>,
+}
+
+impl AppState {
+    /**
+     * Moves the displayed period by the given number of months, weeks, days
+     * or years, depending on the view. The agenda moves by weeks.
+     */
+    fn step(&mut self, periods: i32) {
+        let moved = match self.view {
+            View::Month | View::Business => {
+                self.cursor = self.cursor.add_months(periods);
+                return;
+            }
+            View::Year => {
+                self.cursor = self.cursor.add_months(periods * 12);
+                return;
+            }
+            View::Week => self
+                .week
+                .checked_add_signed(Duration::weeks(periods as i64)),
+            View::Day => self.day.checked_add_signed(Duration::days(periods as i64)),
+            View::Agenda => self
+                .agenda
+                .start()
+                .checked_add_signed(Duration::weeks(periods as i64)),
+        };
+        if let Some(date) = moved {
+            self.show_date(date);
+        }
+    }
+
+    /**
+     * Moves the displayed period by the given number of years.
+     */
+    fn step_years(&mut self, years: i32) {
+        let anchor = match self.view {
+            View::Month | View::Year | View::Business => {
+                self.cursor = self.cursor.add_months(years * 12);
+                return;
+            }
+            View::Week => self.week,
+            View::Day => self.day,
+            View::Agenda => self.agenda.start(),
+        };
+        let months = Months::new(years.unsigned_abs() * 12);
+        let moved = if years < 0 {
+            anchor.checked_sub_months(months)
+        } else {
+            anchor.checked_add_months(months)
+        };
+        if let Some(date) = moved {
+            self.show_date(date);
+        }
+    }
+
+    /**
+     * Shows the month, the week and the day containing `date`, and starts the
+     * agenda on it while the agenda is displayed.
+     */
+    fn show_date(&mut self, date: NaiveDate) {
+        self.cursor = MonthCursor::from_date(date);
+        self.week = week_containing(date, self.settings.week_start);
+        self.day = date;
+        if self.view == View::Agenda {
+            self.agenda = Agenda::new(&self.events, date);
+        }
+    }
+
+    /**
+     * Opens the agenda view starting on the selected day, or today if no
+     * day has been selected.
+     */
+    fn open_agenda(&mut self) {
+        self.view = View::Agenda;
+        let start = self.selected.unwrap_or_else(|| self.today());
+        self.show_date(start);
+    }
+
+    /**
+     * Opens the day view on `date`, selecting it in the month view.
+     */
+    fn open_day(&mut self, date: NaiveDate) {
+        self.selected = Some(date);
+        self.show_date(date);
+        self.view = View::Day;
+    }
+
+    /**
+     * Opens the month view on the month of the displayed year with the given
+     * index, 0 for January.
+     */
+    fn open_month(&mut self, index: usize) {
+        if let Some(cursor) = self.cursor.months_of_year().get(index) {
+            self.cursor = *cursor;
+            self.view = View::Month;
+        }
+    }
+
+    /**
+     * Switches between the month and the week view, or back from the day,
+     * year, agenda and business-day views to the month view.
+     *
+     * The week view opens on the selected day or today if they are part of the
+     * displayed month, on its first week otherwise. The month view opens on
+     * the month most of the displayed week belongs to.
+     */
+    fn toggle_view(&mut self) {
+        match self.view {
+            View::Month => {
+                let today = self.today();
+                let anchor = [self.selected, Some(today)]
+                    .into_iter()
+                    .flatten()
+                    .find(|date| MonthCursor::from_date(*date) == self.cursor)
+                    .unwrap_or(self.cursor.first_day());
+                self.week = week_containing(anchor, self.settings.week_start);
+                self.view = View::Week;
+            }
+            View::Week => {
+                self.cursor = MonthCursor::from_date(self.middle_of_week());
+                self.view = View::Month;
+            }
+            View::Day => {
+                self.cursor = MonthCursor::from_date(self.day);
+                self.view = View::Month;
+            }
+            View::Year | View::Business => self.view = View::Month,
+            View::Agenda => {
+                self.cursor = MonthCursor::from_date(self.agenda.start());
+                self.view = View::Month;
+            }
+        }
+    }
+
+    /**
+     * Returns the current date in the configured time zone.
+     */
+    fn today(&self) -> NaiveDate {
+        self.clock.today(&self.settings)
+    }
+
+    /**
+     * Returns the current wall-clock time in the configured time zone.
+     */
+    fn now(&self) -> NaiveDateTime {
+        self.clock.local_now(&self.settings)
+    }
+
+    /**
+     * Function to notice the date changing while the application is open.
+     *
+     * Returns:
+     * - Whether today is another date than when last checked, so that the
+     *   highlighting of today has to move.
+     */
+    fn roll_over(&mut self) -> bool {
+        let today = self.today();
+        self.day_watch.update(today)
+    }
+
+    /**
+     * Returns the fourth day of the displayed week.
+     */
+    fn middle_of_week(&self) -> NaiveDate {
+        self.week
+            .checked_add_signed(Duration::days(3))
+            .unwrap_or(self.week)
+    }
+
+    /**
+     * Function to fire the reminders that became due, remembering how far
+     * reminders were checked and which alerts are pending, so that the ones
+     * missed while the application is closed are shown on the next start.
+     *
+     * Returns:
+     * - Whether the list of active reminders changed.
+     */
+    fn poll_reminders(&mut self, now: NaiveDateTime) -> bool {
+        let count = self.reminders.active().len();
+        let fired = self.reminders.poll(&self.events, now);
+        if !fired.is_empty() {
+            self.save_reminders();
+        }
+        for alert in &fired {
+            let item = self.reminder_item(alert);
+            let notification = Notification {
+                summary: item.title.to_string(),
+                body: if item.location.is_empty() {
+                    item.time.to_string()
+                } else {
+                    format!("{}\n{}", item.time, item.location)
+                },
+            };
+            self.notifications.show(*alert, &notification);
+        }
+        !fired.is_empty() || self.reminders.active().len() != count
+    }
+
+    /**
+     * Function to describe the occurrence an alert is for.
+     *
+     * Returns:
+     * - The title, date and time, and location, or an empty item if the
+     *   event no longer exists.
+     */
+    fn reminder_item(&self, alert: &Alert) -> ReminderItem {
+        let event = self
+            .events
+            .occurrence(alert.id, alert.start)
+            .or_else(|| self.events.get(alert.id).cloned());
+        let Some(event) = event else {
+            return ReminderItem::default();
+        };
+        ReminderItem {
+            title: SharedString::from(event.title.as_str()),
+            time: SharedString::from(format!(
+                "{} {}",
+                self.locale
+                    .day_month(event.time.start_date(), NameStyle::Short),
+                time_label(&event.time, &self.locale)
+            )),
+            location: SharedString::from(event.location.unwrap_or_default()),
+        }
+    }
+
+    /**
+     * Writes the events back to disk, reporting failures on stderr. Events
+     * changed with the command-line tools in the meantime are merged in.
+     */
+    fn save(&mut self) {
+        if let Some(storage) = &self.storage {
+            if let Err(err) = storage.save(&mut self.events) {
+                eprintln!("Could not save {}: {}", storage.path().display(), err);
+            }
+        }
+    }
+
+    /**
+     * Writes the settings back to disk, reporting failures on stderr.
+     */
+    fn save_settings(&self) {
+        if let Some(path) = &self.settings_path {
+            if let Err(err) = self.settings.save(path) {
+                eprintln!("Could not save {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    /**
+     * Writes the state of the reminders to disk, reporting failures on stderr.
+     */
+    fn save_reminders(&self) {
+        if let Some(path) = &self.reminders_path {
+            if let Err(err) = self.reminders.state().save(path) {
+                eprintln!("Could not save {}: {}", path.display(), err);
+            }
+        }
     }
 }
 
 /**
- * Helper function to insert a sequence of days into the calendar grid.
+ * Function to open the calendar saved in the user's data directory.
  *
- * Parameters:
- * - `boxes`: The vector model that holds the calendar days to be displayed.
- * - `days`: The number of days to insert.
- * - `start_day`: The starting day number for the sequence.
- */
-fn insert_days(boxes: Rc<VecModel<NewBox>>, days: u32, start_day: i32) {
-    for i in 0..days {
-        boxes.insert(
-            i as usize,
-            NewBox {
-                visible: true,
-                day: start_day + i as i32, // Sets the day to start.
-            },
-        );
+ * Returns:
+ * - The saved events and the storage to write them back to. The storage is
+ *   `None` if the saved calendar could not be read, so that it is never
+ *   overwritten with an empty one.
+ */
+fn open_storage() -> (EventStore, Option<Storage>) {
+    let Some(storage) = Storage::default_location() else {
+        eprintln!("No data directory available, events will not be saved");
+        return (EventStore::new(), None);
+    };
+    match storage.load() {
+        Ok(events) => (events, Some(storage)),
+        Err(err) => {
+            eprintln!("Could not load {}: {}", storage.path().display(), err);
+            (EventStore::new(), None)
+        }
     }
 }
 
 /**
- * Function to determine the weekday of a given date.
+ * Function to load the user's settings.
  *
- * Parameters:
- * - `year`: The year part of the date.
- * - `month`: The month part of the date.
- * - `day`: The day part of the date.
+ * Returns:
+ * - The settings and the path to write them back to. The path is `None` if
+ *   the saved settings could not be read, so that they are never overwritten
+ *   with the defaults.
+ */
+fn load_settings() -> (Settings, Option<PathBuf>) {
+    let Some(path) = Settings::default_path() else {
+        return (Settings::default(), None);
+    };
+    match Settings::load(&path) {
+        Ok(settings) => (settings, Some(path)),
+        Err(err) => {
+            eprintln!("Could not load {}: {}", path.display(), err);
+            (Settings::default(), None)
+        }
+    }
+}
+
+/**
+ * Function to load the state of the reminders from the previous run.
  *
  * Returns:
- * - The `Weekday` corresponding to the provided date.
+ * - The state and the path to write it back to. The path is `None` if the
+ *   saved state could not be read, so that it is never overwritten.
  */
-fn get_week_day(year: u64, month: u32, day: u32) -> Weekday {
-    let date = NaiveDate::from_ymd_opt(year as i32, month, day).unwrap();
-    date.weekday()
+fn load_reminders_state() -> (ReminderState, Option<PathBuf>) {
+    let Some(path) = ReminderState::default_path() else {
+        return (ReminderState::default(), None);
+    };
+    match ReminderState::load(&path) {
+        Ok(state) => (state, Some(path)),
+        Err(err) => {
+            eprintln!("Could not load {}: {}", path.display(), err);
+            (ReminderState::default(), None)
+        }
+    }
 }
 
 /**
- * Function to get the current year as a `u64`.
+ * Function to load the holidays of the region named in the settings.
+ *
+ * Returns:
+ * - The holidays, or `None` if no region is configured or its holidays
+ *   could not be loaded.
  */
-fn current_year() -> u64 {
-    Utc::now().year() as u64
+fn load_holidays(settings: &Settings) -> Option<HolidayCalendar> {
+    let code = settings.holidays.as_deref()?;
+    let dir = HolidayCalendar::default_dir();
+    match HolidayCalendar::load(code, dir.as_deref()) {
+        Ok(holidays) => Some(holidays),
+        Err(err) => {
+            eprintln!("Could not load the holidays of {}: {}", code, err);
+            None
+        }
+    }
 }
 
 /**
- * Function to get the current month as a `u32`.
+ * Function to list the holidays between two dates.
+ *
+ * Returns:
+ * - The holidays grouped by date, empty if no holidays are configured.
  */
-fn current_month() -> u32 {
-    Utc::now().month()
+fn holidays_between(
+    holidays: Option<&HolidayCalendar>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> BTreeMap<NaiveDate, Vec<Holiday>> {
+    holidays
+        .map(|holidays| holidays.holidays_between(start, end))
+        .unwrap_or_default()
 }
 
 /**
- * Function to get the current day as a `u32`.
+ * Function to import iCalendar files into the event store.
+ *
+ * Problems are reported on stderr; components that cannot be imported are
+ * skipped while the rest of the file is still imported.
+ *
+ * Parameters:
+ * - `events`: The store receiving the imported events.
+ * - `settings`: The settings naming the time zone UTC times are converted to.
+ * - `paths`: The `.ics` files to import.
+ *
+ * Returns:
+ * - Whether any event was added or updated.
  */
-fn current_day() -> u32 {
-    Utc::now().day()
+fn import_files(
+    events: &mut EventStore,
+    settings: &Settings,
+    paths: impl Iterator<Item = String>,
+) -> bool {
+    let mut imported = false;
+    for path in paths {
+        let result = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|input| {
+                match settings.zone() {
+                    Some(zone) => ical::import(events, &input, &zone),
+                    None => ical::import(events, &input, &Local),
+                }
+                .map_err(|err| err.to_string())
+            });
+        match result {
+            Ok(report) => {
+                imported |= !report.added.is_empty() || !report.updated.is_empty();
+                println!(
+                    "Imported {}: {} added, {} updated, {} skipped",
+                    path,
+                    report.added.len(),
+                    report.updated.len(),
+                    report.skipped.len()
+                );
+                for err in report.skipped {
+                    eprintln!("{}: {}", path, err);
+                }
+            }
+            Err(err) => eprintln!("Could not import {}: {}", path, err),
+        }
+    }
+    imported
 }
 
 /**
- * Function to calculate the number of days in a given month of a given year.
+ * Function to export the events of a month to an iCalendar file.
+ *
+ * The file is written to the user's download directory, falling back to the
+ * home directory.
  *
  * Parameters:
- * - `year`: The year for which to calculate the days.
- * - `month`: The month for which to calculate the days.
+ * - `events`: The events to export.
+ * - `cursor`: The month to export.
  *
  * Returns:
- * - The number of days in the specified year and month.
+ * - The path of the written file, or an error message.
  */
-fn generate_month(year: u64, month: u32) -> u64 {
-    let current_month = month_from_index(month as u64);
-    days_in_month(year, current_month.unwrap())
+fn export_month(
+    events: &EventStore,
+    cursor: MonthCursor,
+    now: DateTime<Utc>,
+) -> Result<PathBuf, String> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("No download directory available")?;
+    let path = dir.join(format!(
+        "calendar-{:04}-{:02}.ics",
+        cursor.year(),
+        cursor.month()
+    ));
+    let content = ical::export(events, ExportRange::Month(cursor), now);
+    std::fs::write(&path, content)
+        .map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+    Ok(path)
 }
 
 /**
  * Main function to load the calendar data for the specified month and year.
  *
- * This function populates the calendar grid with days from the previous month,
- * the current month, and the next month to ensure the grid is fully filled.
+ * The grid itself is computed by `calendar_core::MonthGrid`; this function only
+ * converts its cells into the `NewBox` items rendered by the UI. Recurring
+ * events are expanded for the dates of the grid only. A holiday takes the
+ * place of one of the events listed in its cell.
  *
  * Parameters:
  * - `boxes`: The vector model that holds the calendar days to be displayed.
- * - `year`: The year for which the calendar is being generated.
- * - `month`: The month for which the calendar is being generated.
- */
-fn load_calendar(boxes: Rc<VecModel<NewBox>>, year: u64, month: u32) {
-    // Start by handling the days from the next month that will be shown at the end of the current month's grid.
-    let days_of_month = generate_month(year, month);
-    let last_weekday_of_month = get_week_day(year, month, days_of_month as u32);
-    let first_days_of_next_month = get_first_days_of_next_month(last_weekday_of_month);
-    insert_days(boxes.clone(), first_days_of_next_month, 1);
-
-    // Now handle the days of the current month.
-    insert_days(boxes.clone(), days_of_month as u32, 1);
-
-    // Finally, handle the days from the previous month that will be shown at the beginning of the current month's grid.
-    let (prev_year, prev_month) = if month == 1 {
-        (year - 1, 12) // Handle the case where the current month is January.
+ * - `grid`: The month grid to display.
+ * - `events`: The events to show in the cells of the grid.
+ * - `holidays`: The holidays to show in the cells of the grid, if any.
+ * - `today`: The current date, highlighted if it is part of the grid.
+ * - `selected`: The date the user last clicked on, if any.
+ * - `locale`: The language holidays are labeled in.
+ */
+fn load_calendar(
+    boxes: Rc<VecModel<NewBox>>,
+    grid: &MonthGrid,
+    events: &EventStore,
+    holidays: Option<&HolidayCalendar>,
+    today: NaiveDate,
+    selected: Option<NaiveDate>,
+    locale: &Locale,
+) {
+    let (first, last) = grid.range();
+    let mut occurrences = events.occurrences_by_date(first, last);
+    let mut holidays = holidays_between(holidays, first, last);
+
+    for cell in grid.cells() {
+        let day_events = occurrences.remove(&cell.date).unwrap_or_default();
+        let holiday = holiday_label(&holidays.remove(&cell.date).unwrap_or_default(), locale);
+        let shown = if holiday.is_empty() {
+            MAX_EVENTS_PER_CELL
+        } else {
+            MAX_EVENTS_PER_CELL - 1
+        };
+        let chips: Vec<EventChip> = day_events
+            .iter()
+            .take(shown)
+            .map(|occurrence| {
+                let event = occurrence.event;
+                EventChip {
+                    title: SharedString::from(event.title.as_str()),
+                    color: slint::Color::from_rgb_u8(event.color.r, event.color.g, event.color.b),
+                }
+            })
+            .collect();
+
+        let kind = match cell.kind {
+            calendar_core::CellKind::PreviousMonth => CellKind::PreviousMonth,
+            calendar_core::CellKind::CurrentMonth => CellKind::CurrentMonth,
+            calendar_core::CellKind::NextMonth => CellKind::NextMonth,
+        };
+
+        boxes.push(NewBox {
+            kind,
+            day: cell.date.day() as i32,
+            events: ModelRc::new(VecModel::from(chips)),
+            more: day_events.len().saturating_sub(shown) as i32,
+            holiday: SharedString::from(holiday),
+            is_today: cell.date == today,
+            is_weekend: cell.is_weekend(),
+            is_selected: selected == Some(cell.date),
+        });
+    }
+}
+
+/**
+ * Function to describe the holidays of a day, e.g. "Christmas Day (observed)".
+ */
+fn holiday_label(holidays: &[Holiday], locale: &Locale) -> String {
+    holidays
+        .iter()
+        .map(|holiday| {
+            if holiday.observed {
+                locale.observed(&holiday.name)
+            } else {
+                holiday.name.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/**
+ * Function to load the year overview.
+ *
+ * Every month is drawn as a miniature of the grid `load_calendar` displays,
+ * marking the days with events, holidays and today. Recurring events are
+ * expanded once for the dates of all twelve grids.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `cursor`: Any month of the year to display.
+ * - `events`: The events to mark.
+ * - `holidays`: The holidays to mark, if any.
+ * - `week_start`: The day the weeks of the grids start with.
+ * - `today`: The current date, highlighted if it is part of the year.
+ * - `locale`: The language of the month and weekday names.
+ */
+fn load_year(
+    ui: &AppWindow,
+    cursor: MonthCursor,
+    events: &EventStore,
+    holidays: Option<&HolidayCalendar>,
+    week_start: Weekday,
+    today: NaiveDate,
+    locale: &Locale,
+) {
+    let grids = cursor.months_of_year().map(|month| month.grid(week_start));
+    let first = grids[0].range().0;
+    let last = grids[11].range().1;
+    let busy = events.occurrences_by_date(first, last);
+    let holidays = holidays_between(holidays, first, last);
+
+    let months: Vec<MiniMonth> = grids
+        .iter()
+        .map(|grid| {
+            let days: Vec<MiniDay> = grid
+                .cells()
+                .iter()
+                .map(|cell| MiniDay {
+                    day: cell.date.day() as i32,
+                    in_month: cell.in_month,
+                    is_today: cell.date == today,
+                    is_weekend: cell.is_weekend(),
+                    is_holiday: holidays.contains_key(&cell.date),
+                    has_events: busy.contains_key(&cell.date),
+                })
+                .collect();
+            let name = locale.month_name(grid.month(), NameStyle::Full);
+            MiniMonth {
+                name: SharedString::from(name.expect("grid month is always valid")),
+                days: ModelRc::new(VecModel::from(days)),
+            }
+        })
+        .collect();
+
+    let weekdays: Vec<SharedString> = week_days(week_start)
+        .into_iter()
+        .map(|weekday| {
+            // At most two letters fit the columns of the miniatures.
+            let name = locale.weekday_name(weekday, NameStyle::Short);
+            SharedString::from(name.chars().take(2).collect::<String>())
+        })
+        .collect();
+
+    ui.set_year_months(ModelRc::new(VecModel::from(months)));
+    ui.set_year_weekdays(ModelRc::new(VecModel::from(weekdays)));
+}
+
+/**
+ * Function to load the week view.
+ *
+ * Converts the layout computed by `calendar_core::WeekLayout` into the day
+ * columns, all-day bars and timed blocks rendered by the `WeekView` component.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `layout`: The events of the week to display.
+ * - `today`: The current date, highlighted if it is part of the week.
+ * - `locale`: The language of the column headings.
+ */
+fn load_week(ui: &AppWindow, layout: &WeekLayout, today: NaiveDate, locale: &Locale) {
+    let columns: Vec<WeekColumn> = layout
+        .days
+        .iter()
+        .map(|date| WeekColumn {
+            label: SharedString::from(locale.day(*date, NameStyle::Short)),
+            is_today: *date == today,
+            is_weekend: matches!(date.weekday(), Weekday::Sat | Weekday::Sun),
+        })
+        .collect();
+
+    let all_day: Vec<AllDayItem> = layout
+        .all_day
+        .iter()
+        .map(|bar| AllDayItem {
+            title: SharedString::from(bar.occurrence.event.title.as_str()),
+            color: ui_color(bar.occurrence.event.color),
+            first: bar.first as i32,
+            last: bar.last as i32,
+            row: bar.row as i32,
+        })
+        .collect();
+
+    let timed: Vec<TimedItem> = layout
+        .timed
+        .iter()
+        .map(|block| timed_item(block, locale))
+        .collect();
+
+    ui.set_week_columns(ModelRc::new(VecModel::from(columns)));
+    ui.set_week_all_day(ModelRc::new(VecModel::from(all_day)));
+    ui.set_week_all_day_rows(layout.all_day_rows as i32);
+    ui.set_week_timed(ModelRc::new(VecModel::from(timed)));
+}
+
+/**
+ * Function to load the day view.
+ *
+ * Converts the layout computed by `calendar_core::DayLayout` into the slots,
+ * timed blocks and agenda rendered by the `DayView` component.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `layout`: The events of the day to display.
+ * - `slot_size`: The length of the slots of the time axis.
+ * - `now`: The current wall-clock time, marked if it falls on the day.
+ * - `locale`: The language dates are displayed in.
+ */
+fn load_day(
+    ui: &AppWindow,
+    layout: &DayLayout,
+    slot_size: SlotSize,
+    now: NaiveDateTime,
+    locale: &Locale,
+) {
+    let slots: Vec<SharedString> = slot_size
+        .slots()
+        .map(|minute| SharedString::from(format!("{:02}:{:02}", minute / 60, minute % 60)))
+        .collect();
+
+    let timed: Vec<TimedItem> = layout
+        .timed
+        .iter()
+        .map(|block| timed_item(block, locale))
+        .collect();
+
+    let agenda: Vec<AgendaItem> = layout
+        .agenda
+        .iter()
+        .map(|occurrence| agenda_item(occurrence, locale))
+        .collect();
+
+    let index = SlotSize::ALL.iter().position(|size| *size == slot_size);
+    ui.set_slot_size_index(index.unwrap_or_default() as i32);
+    ui.set_day_slot_minutes(slot_size.minutes() as i32);
+    ui.set_day_slots(ModelRc::new(VecModel::from(slots)));
+    ui.set_day_timed(ModelRc::new(VecModel::from(timed)));
+    ui.set_day_agenda(ModelRc::new(VecModel::from(agenda)));
+    ui.set_day_now_minute(layout.now_minute(now).map_or(-1, |minute| minute as i32));
+}
+
+/**
+ * Function to load the agenda view.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `agenda`: The dates to list.
+ * - `events`: The events to list.
+ * - `today`: The current date, highlighted if it is listed.
+ * - `locale`: The language dates are displayed in.
+ */
+fn load_agenda(
+    ui: &AppWindow,
+    agenda: &Agenda,
+    events: &EventStore,
+    today: NaiveDate,
+    locale: &Locale,
+) {
+    let days: Vec<AgendaDayItem> = agenda
+        .days(events)
+        .iter()
+        .map(|day| {
+            let items: Vec<AgendaItem> = day
+                .occurrences
+                .iter()
+                .map(|occurrence| agenda_item(occurrence, locale))
+                .collect();
+            AgendaDayItem {
+                label: SharedString::from(locale.date(day.date, NameStyle::Full)),
+                is_today: day.date == today,
+                events: ModelRc::new(VecModel::from(items)),
+            }
+        })
+        .collect();
+
+    ui.set_agenda_days(ModelRc::new(VecModel::from(days)));
+    ui.set_agenda_has_more(agenda.has_more());
+}
+
+/**
+ * Function to identify an occurrence within its event for the `edit-event`
+ * callback, by its start in the event's time zone formatted with
+ * `OCCURRENCE_FORMAT`.
+ */
+fn occurrence_key(occurrence: &Occurrence) -> SharedString {
+    SharedString::from(
+        occurrence
+            .recurrence_id
+            .format(OCCURRENCE_FORMAT)
+            .to_string(),
+    )
+}
+
+/**
+ * Function to convert an occurrence into its entry in the day view's agenda
+ * or the agenda view.
+ */
+fn agenda_item(occurrence: &Occurrence, locale: &Locale) -> AgendaItem {
+    let event = occurrence.event;
+    AgendaItem {
+        id: occurrence.id.0 as i32,
+        occurrence: occurrence_key(occurrence),
+        title: SharedString::from(event.title.as_str()),
+        time: SharedString::from(time_label(&occurrence.time, locale)),
+        location: SharedString::from(event.location.as_deref().unwrap_or_default()),
+        notes: SharedString::from(event.notes.as_deref().unwrap_or_default()),
+        color: ui_color(event.color),
+    }
+}
+
+/**
+ * Function to convert a timed block of the week or day view into its UI item.
+ */
+fn timed_item(block: &TimedBlock, locale: &Locale) -> TimedItem {
+    let event = block.occurrence.event;
+    TimedItem {
+        id: block.occurrence.id.0 as i32,
+        occurrence: occurrence_key(&block.occurrence),
+        title: SharedString::from(event.title.as_str()),
+        time: SharedString::from(time_label(&block.occurrence.time, locale)),
+        color: ui_color(event.color),
+        day: block.day as i32,
+        start: block.start_minute as i32,
+        end: block.end_minute as i32,
+        column: block.column as i32,
+        columns: block.columns as i32,
+    }
+}
+
+/**
+ * Function to describe when an event takes place, e.g. "09:00–10:30".
+ *
+ * Events spanning several days include the dates of their start and end.
+ */
+fn time_label(time: &EventTime, locale: &Locale) -> String {
+    let day = |date: NaiveDate| locale.day_month(date, NameStyle::Short);
+    match *time {
+        EventTime::AllDay { start, end } if start == end => locale.all_day().to_string(),
+        EventTime::AllDay { start, end } => format!("{}\u{2013}{}", day(start), day(end)),
+        EventTime::Timed { start, end } if time.start_date() == time.end_date() => {
+            format!("{}\u{2013}{}", start.format("%H:%M"), end.format("%H:%M"))
+        }
+        EventTime::Timed { start, end } => format!(
+            "{} {}\u{2013}{} {}",
+            day(start.date()),
+            start.format("%H:%M"),
+            day(end.date()),
+            end.format("%H:%M")
+        ),
+    }
+}
+
+/**
+ * Function to describe the event a quick-add text creates, e.g.
+ * "Lunch with Sam, Sat 17 Oct 2026 12:30–13:30 at Cafe Luna".
+ */
+fn quick_add_preview(event: &Event, locale: &Locale) -> String {
+    let time = &event.time;
+    // Events spanning several days already show their dates.
+    let mut preview = if time.start_date() == time.end_date() {
+        format!(
+            "{}, {} {}",
+            event.title,
+            locale.date(time.start_date(), NameStyle::Short),
+            time_label(time, locale)
+        )
     } else {
-        (year, month - 1) // Otherwise, simply subtract one month.
+        format!("{}, {}", event.title, time_label(time, locale))
     };
+    if let Some(location) = &event.location {
+        preview.push_str(&format!(" at {location}"));
+    }
+    if let Some(rule) = event.recurrence.as_ref().and_then(|r| r.rule.as_ref()) {
+        preview.push_str(match rule.frequency {
+            Frequency::Daily => ", repeats daily",
+            Frequency::Weekly => ", repeats weekly",
+            Frequency::Monthly => ", repeats monthly",
+            Frequency::Yearly => ", repeats yearly",
+        });
+    }
+    preview
+}
+
+/**
+ * Function to open the event dialog.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `heading`: The title of the dialog.
+ * - `draft`: The fields the dialog starts out with.
+ */
+fn open_event_dialog(ui: &AppWindow, heading: &str, draft: &EventDraft) {
+    ui.set_event_heading(SharedString::from(heading));
+    ui.set_event_form(form::event_form(draft));
+    ui.set_event_errors(EventFormErrors::default());
+    ui.set_editing(true);
+}
 
-    let first_weekday_of_month: Weekday = get_week_day(year, month, 1);
-    let last_days_of_prev_month = get_last_days_of_prev_month(first_weekday_of_month);
-    let days_of_prev_month = generate_month(prev_year, prev_month);
-    let start_day = days_of_prev_month - last_days_of_prev_month as u64;
+/**
+ * Function to connect to the desktop's notification daemon, reporting the
+ * actions picked on notifications through the window's
+ * `notification-action` callback.
+ *
+ * Returns:
+ * - The notifier, or None if reminders can only be shown in the window.
+ */
+fn connect_notifier(ui: &AppWindow) -> Option<Box<dyn Notifier>> {
+    let ui_handle = ui.as_weak();
+    let on_action = move |id: u32, action: Action| {
+        let _ = ui_handle.upgrade_in_event_loop(move |ui| {
+            ui.invoke_notification_action(id as i32, SharedString::from(action.key()));
+        });
+    };
+    match DbusNotifier::connect(on_action) {
+        Ok(notifier) => Some(Box::new(notifier)),
+        Err(err) => {
+            eprintln!("{err}, showing reminders in the window only");
+            None
+        }
+    }
+}
 
-    insert_days(boxes, last_days_of_prev_month, start_day as i32 + 1);
+/**
+ * Function to display the active reminders, in the order of the scheduler.
+ */
+fn load_reminders(ui: &AppWindow, state: &AppState) {
+    let items: Vec<ReminderItem> = state
+        .reminders
+        .active()
+        .iter()
+        .map(|alert| state.reminder_item(alert))
+        .collect();
+    ui.set_reminders(ModelRc::new(VecModel::from(items)));
+}
+
+/**
+ * Function to convert an event color into a Slint color.
+ */
+fn ui_color(color: calendar_core::Color) -> slint::Color {
+    slint::Color::from_rgb_u8(color.r, color.g, color.b)
 }
 
 /**
  * Function to run the calendar UI.
  *
  * This function is responsible for setting up the UI elements with the appropriate
- * month, year, and days, and handling user interactions such as moving to the next month.
+ * month, year, and days for the month the cursor points to, or the events of
+ * the displayed week or day in the week and day views.
  *
  * Parameters:
  * - `ui`: The main UI window.
  * - `boxes`: The vector model that holds the calendar days to be displayed.
- * - `year`: The year for which the calendar is being generated.
- * - `month`: The month for which the calendar is being generated.
+ * - `state`: The month to display, the events to show in it and the settings
+ *   controlling the layout.
  */
-fn run_calendar(ui: &AppWindow, boxes: Rc<VecModel<NewBox>>, year: u64, month: u32) {
-    let current_month = month_from_index(month as u64);
-
-    load_calendar(boxes.clone(), year, month);
+fn run_calendar(ui: &AppWindow, boxes: Rc<VecModel<NewBox>>, state: &AppState) {
+    let week_start = state.settings.week_start;
+    // The week view is titled after the month most of the week belongs to.
+    let cursor = match state.view {
+        View::Month | View::Business => state.cursor,
+        View::Week => MonthCursor::from_date(state.middle_of_week()),
+        View::Day => MonthCursor::from_date(state.day),
+        View::Year => state.cursor,
+        View::Agenda => MonthCursor::from_date(state.agenda.start()),
+    };
 
-    let updated_month = month_string(current_month.unwrap());
+    match state.view {
+        View::Month => load_calendar(
+            boxes.clone(),
+            &cursor.grid(week_start),
+            &state.events,
+            state.holidays.as_ref(),
+            state.today(),
+            state.selected,
+            &state.locale,
+        ),
+        View::Week => load_week(
+            ui,
+            &WeekLayout::new(&state.events, state.week),
+            state.today(),
+            &state.locale,
+        ),
+        View::Day => load_day(
+            ui,
+            &DayLayout::new(&state.events, state.day),
+            state.settings.slot_size,
+            state.now(),
+            &state.locale,
+        ),
+        View::Year => load_year(
+            ui,
+            cursor,
+            &state.events,
+            state.holidays.as_ref(),
+            week_start,
+            state.today(),
+            &state.locale,
+        ),
+        View::Agenda => load_agenda(
+            ui,
+            &state.agenda,
+            &state.events,
+            state.today(),
+            &state.locale,
+        ),
+        View::Business => ui.set_business_rules(SharedString::from(business_rules(
+            &state.settings,
+            state.holidays.as_ref(),
+            &state.locale,
+        ))),
+    }
+    ui.set_view(match state.view {
+        View::Month => ViewKind::Month,
+        View::Week => ViewKind::Week,
+        View::Day => ViewKind::Day,
+        View::Year => ViewKind::Year,
+        View::Agenda => ViewKind::Agenda,
+        View::Business => ViewKind::Business,
+    });
 
-    // Update the UI with the month name and year.
-    ui.set_month(SharedString::from(updated_month));
-    let year_str = format!(" {}", year.to_string());
-    ui.set_year(SharedString::from(year_str));
+    // The day view is titled with the full date, the year view with the year
+    // alone and the other views with the month.
+    let heading = |style: NameStyle| match state.view {
+        View::Day => state.locale.date(state.day, style),
+        View::Year => cursor.year().to_string(),
+        _ => state.locale.month_title(cursor.first_day(), style),
+    };
+    ui.set_heading(SharedString::from(heading(NameStyle::Full)));
+    ui.set_short_heading(SharedString::from(heading(NameStyle::Short)));
 
     // Set the populated boxes model into the UI.
     ui.set_boxes(boxes.clone().into());
+
+    // The header follows the same week start as the grid.
+    ui.set_weekdays(get_week_days(week_start, &state.locale).into());
+    let index = WEEK_STARTS.iter().position(|day| *day == week_start);
+    ui.set_week_start_index(index.unwrap_or_default() as i32);
+
+    // Number the rows of the month grid if enabled.
+    let numbering = state.settings.week_numbers;
+    let week_numbers: Vec<i32> = match numbering {
+        Some(numbering) if state.view == View::Month => cursor
+            .grid(week_start)
+            .week_numbers(numbering)
+            .into_iter()
+            .map(|week| week as i32)
+            .collect(),
+        _ => Vec::new(),
+    };
+    ui.set_week_numbers(ModelRc::new(VecModel::from(week_numbers)));
+    let index = WEEK_NUMBERINGS
+        .iter()
+        .position(|choice| *choice == numbering);
+    ui.set_week_numbering_index(index.unwrap_or_default() as i32);
+}
+
+/**
+ * Function to describe the weekend days and holidays business days are
+ * computed with.
+ */
+fn business_rules(
+    settings: &Settings,
+    holidays: Option<&HolidayCalendar>,
+    locale: &Locale,
+) -> String {
+    let weekend: Vec<&str> = week_days(Weekday::Mon)
+        .into_iter()
+        .filter(|day| settings.weekend.contains(day))
+        .map(|day| locale.weekday_name(day, NameStyle::Full))
+        .collect();
+    let weekend = (!weekend.is_empty()).then(|| weekend.join(", "));
+    let holidays = holidays.map(|holidays| holidays.name.as_str());
+    locale.business_rules(weekend.as_deref(), holidays)
+}
+
+/**
+ * Function to parse a date entered in the business-day calculator.
+ *
+ * Returns:
+ * - The date, or the message to show if `text` is not a YYYY-MM-DD date.
+ */
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("'{}' is not a date (YYYY-MM-DD)", text.trim()))
+}
+
+/**
+ * Function to build a callback of the business-day calculator.
+ *
+ * Parameters:
+ * - `state`: The shared application state, providing the weekend and
+ *   holiday settings.
+ * - `compute`: Computes the result text from the business-day calendar, the
+ *   language to format dates in and the callback's arguments.
+ *
+ * Returns:
+ * - A callback returning the text to show, or why there is no result.
+ */
+fn business_callback<A>(
+    state: &Rc<RefCell<AppState>>,
+    compute: impl Fn(&BusinessCalendar, &Locale, A) -> Result<String, String>,
+) -> impl Fn(A) -> SharedString {
+    let state = state.clone();
+    move |args| {
+        let state = state.borrow();
+        let result = state
+            .settings
+            .business_calendar(state.holidays.as_ref())
+            .ok_or_else(|| "Every day of the week is a weekend day".to_string())
+            .and_then(|calendar| compute(&calendar, &state.locale, args));
+        SharedString::from(result.unwrap_or_else(|err| err))
+    }
+}
+
+/**
+ * Function to build a navigation callback for the UI.
+ *
+ * Parameters:
+ * - `ui`: The main UI window.
+ * - `state`: The shared application state.
+ * - `step`: Changes the displayed period or view of the state.
+ *
+ * Returns:
+ * - A callback that updates the state and redraws the calendar.
+ */
+fn navigate(
+    ui: &AppWindow,
+    state: &Rc<RefCell<AppState>>,
+    step: fn(&mut AppState),
+) -> impl FnMut() + 'static {
+    let ui_handle = ui.as_weak();
+    let state = state.clone();
+    move || {
+        let ui = ui_handle.unwrap();
+        let mut state = state.borrow_mut();
+        step(&mut state);
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state); // Load and display the new month's calendar.
+    }
 }
 
 /**
  * Function to generate and return the list of weekdays for the UI.
  *
+ * Parameters:
+ * - `week_start`: The day the weeks of the grid start with.
+ * - `locale`: The language of the names.
+ *
  * Returns:
- * - An `Rc<VecModel<Weekdays>>` containing the names of the weekdays.
+ * - An `Rc<VecModel<Weekdays>>` containing the names of the weekdays in display order.
  */
-fn get_week_days() -> Rc<VecModel<Weekdays>> {
-    let week_vec = vec![
-        Weekdays {
-            day: SharedString::from("Sunday"),
-        },
-        Weekdays {
-            day: SharedString::from("Monday"),
-        },
-        Weekdays {
-            day: SharedString::from("Tuesday"),
-        },
-        Weekdays {
-            day: SharedString::from("Wednesday"),
-        },
-        Weekdays {
-            day: SharedString::from("Thursday"),
-        },
-        Weekdays {
-            day: SharedString::from("Friday"),
-        },
-        Weekdays {
-            day: SharedString::from("Saturday"),
-        },
-    ];
+fn get_week_days(week_start: Weekday, locale: &Locale) -> Rc<VecModel<Weekdays>> {
+    let week_vec: Vec<Weekdays> = week_days(week_start)
+        .into_iter()
+        .map(|weekday| Weekdays {
+            day: SharedString::from(locale.weekday_name(weekday, NameStyle::Full)),
+            short: SharedString::from(locale.weekday_name(weekday, NameStyle::Short)),
+        })
+        .collect();
 
-    let weekdays = Rc::new(slint::VecModel::<Weekdays>::from(Vec::from(week_vec)));
-    weekdays
+    Rc::new(slint::VecModel::<Weekdays>::from(week_vec))
 }
 
 /**
  * The main entry point of the application.
  *
  * This function sets up the UI, loads the initial calendar, and handles user interactions
- * such as navigating to the next month.
+ * such as navigating between months and years.
  *
  * Returns:
  * - A `Result` indicating whether the application started successfully or encountered an error.
  */
 fn main() -> Result<(), slint::PlatformError> {
+    let args = cli::Cli::parse();
+    let (settings, settings_path) = load_settings();
+    // Headless commands print their result without opening the window.
+    if let Some(command) = args.command {
+        std::process::exit(cli::run(command, &settings, &SystemClock));
+    }
+
     let ui = AppWindow::new()?; // Initialize the UI.
     let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
 
     let new_boxes = boxes.clone();
 
-    let year = current_year(); // Get the current year.
-    let mut month = current_month(); // Get the current month.
-    let day = current_day(); // Get the current day.
+    let clock = SystemClock;
+    // Print the current day of the week in the configured time zone.
+    println!("Today is {}", clock.today(&settings).weekday());
+
+    let (mut events, storage) = open_storage();
+    events.set_zone(settings.zone());
+    let imported = import_files(&mut events, &settings, args.files.into_iter()); // Import the .ics files given as arguments.
+    let current = clock.today(&settings);
+    let now = clock.local_now(&settings);
+    let (reminders, reminders_path) = load_reminders_state();
+    let state = Rc::new(RefCell::new(AppState {
+        view: View::Month,
+        cursor: MonthCursor::from_date(current),
+        week: week_containing(current, settings.week_start),
+        day: current,
+        agenda: Agenda::new(&events, current),
+        events,
+        storage,
+        holidays: load_holidays(&settings),
+        locale: settings.locale(),
+        reminders: Scheduler::new(reminders.checked, reminders.alerts, now),
+        notifications: Notifications::new(connect_notifier(&ui)),
+        clock: Box::new(clock),
+        day_watch: DayWatch::new(current),
+        edited: None,
+        settings,
+        settings_path,
+        reminders_path,
+        selected: None,
+    }));
+    if imported {
+        state.borrow_mut().save(); // Keep the imported events.
+    }
+    run_calendar(&ui, new_boxes, &state.borrow()); // Load and display the current month's calendar.
+
+    let locales = Locale::available();
+    let names: Vec<SharedString> = locales
+        .iter()
+        .map(|locale| SharedString::from(locale.name()))
+        .collect();
+    ui.set_languages(ModelRc::new(VecModel::from(names)));
+    let index = locales
+        .iter()
+        .position(|locale| *locale == state.borrow().locale);
+    ui.set_language_index(index.unwrap_or_default() as i32);
+
+    ui.on_previous_month(navigate(&ui, &state, |state| state.step(-1)));
+    ui.on_next_month(navigate(&ui, &state, |state| state.step(1)));
+    ui.on_previous_year(navigate(&ui, &state, |state| state.step_years(-1)));
+    ui.on_next_year(navigate(&ui, &state, |state| state.step_years(1)));
+    ui.on_today(navigate(&ui, &state, |state| {
+        state.show_date(state.today())
+    }));
+    ui.on_toggle_view(navigate(&ui, &state, AppState::toggle_view));
+    ui.on_show_year(navigate(&ui, &state, |state| state.view = View::Year));
+    ui.on_show_agenda(navigate(&ui, &state, AppState::open_agenda));
+    ui.on_load_more_agenda(navigate(&ui, &state, |state| {
+        state.agenda.load_more(&state.events);
+    }));
 
-    let weekday = get_week_day(year, month, day);
-    let weekday_str: String = format!("Today is {}", weekday);
-    println!("{}", weekday_str); // Print the current day of the week.
+    let ui_handle = ui.as_weak();
+    let business_state = state.clone();
+    ui.on_show_business_days(move || {
+        let ui = ui_handle.unwrap();
+        let mut state = business_state.borrow_mut();
+        state.view = View::Business;
+        let start = state.selected.unwrap_or_else(|| state.today());
+        ui.set_business_start(SharedString::from(start.format("%Y-%m-%d").to_string()));
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+    });
+    let add = business_callback(
+        &state,
+        |calendar, locale, (start, days): (SharedString, SharedString)| {
+            let start = parse_date(&start)?;
+            let days: i64 = days
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a number of days", days.trim()))?;
+            let end = calendar
+                .add_business_days(start, days)
+                .ok_or("The result is out of range")?;
+            Ok(format!(
+                "{days} business days after {}: {}",
+                locale.date(start, NameStyle::Short),
+                locale.date(end, NameStyle::Short)
+            ))
+        },
+    );
+    ui.on_business_add(move |start, days| add((start, days)));
+    let count = business_callback(
+        &state,
+        |calendar, locale, (start, end): (SharedString, SharedString)| {
+            let (start, end) = (parse_date(&start)?, parse_date(&end)?);
+            Ok(format!(
+                "{} business days from {} until {}",
+                calendar.business_days_between(start, end),
+                locale.date(start, NameStyle::Short),
+                locale.date(end, NameStyle::Short)
+            ))
+        },
+    );
+    ui.on_business_count(move |start, end| count((start, end)));
+    ui.on_business_next(business_callback(
+        &state,
+        |calendar, locale, start: SharedString| {
+            let start = parse_date(&start)?;
+            let next = calendar
+                .next_business_day(start)
+                .ok_or("The result is out of range")?;
+            Ok(format!(
+                "Next working day: {}",
+                locale.date(next, NameStyle::Short)
+            ))
+        },
+    ));
+    ui.on_business_previous(business_callback(
+        &state,
+        |calendar, locale, start: SharedString| {
+            let start = parse_date(&start)?;
+            let previous = calendar
+                .previous_business_day(start)
+                .ok_or("The result is out of range")?;
+            Ok(format!(
+                "Previous working day: {}",
+                locale.date(previous, NameStyle::Short)
+            ))
+        },
+    ));
 
-    ui.set_weekdays(get_week_days().clone().into()); // Set the weekday labels in the UI.
+    let ui_handle = ui.as_weak();
+    let year_state = state.clone();
+    ui.on_open_month(move |index| {
+        let ui = ui_handle.unwrap();
+        let mut state = year_state.borrow_mut();
+        state.open_month(index as usize);
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+    });
 
-    run_calendar(&ui, new_boxes, year, month); // Load and display the current month's calendar.
+    let ui_handle = ui.as_weak();
+    let export_state = state.clone();
+    ui.on_export_month(move || {
+        let ui = ui_handle.unwrap();
+        let state = export_state.borrow();
+        let status = match export_month(&state.events, state.cursor, state.clock.now()) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(err) => err,
+        };
+        ui.set_status(SharedString::from(status));
+    });
 
     let ui_handle = ui.as_weak();
-    ui.on_next_month(move || {
+    let settings_state = state.clone();
+    ui.on_week_start_changed(move |index| {
         let ui = ui_handle.unwrap();
-        month += 1; // Move to the next month.
+        let mut state = settings_state.borrow_mut();
+        let Some(week_start) = WEEK_STARTS.get(index as usize) else {
+            return;
+        };
+        state.settings.week_start = *week_start;
+        state.save_settings();
+        // Keep the week view on the days it was showing.
+        let middle = state.middle_of_week();
+        state.week = week_containing(middle, *week_start);
         let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
-        run_calendar(&ui, boxes, year, month); // Load and display the new month's calendar.
+        run_calendar(&ui, boxes, &state); // Redraw the grid and header with the new week start.
     });
 
-    ui.run() // Start the UI event loop.
+    let ui_handle = ui.as_weak();
+    let numbering_state = state.clone();
+    ui.on_week_numbering_changed(move |index| {
+        let ui = ui_handle.unwrap();
+        let mut state = numbering_state.borrow_mut();
+        let Some(numbering) = WEEK_NUMBERINGS.get(index as usize) else {
+            return;
+        };
+        state.settings.week_numbers = *numbering;
+        state.save_settings();
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state); // Show, hide or renumber the week column.
+    });
+
+    let ui_handle = ui.as_weak();
+    let language_state = state.clone();
+    ui.on_language_changed(move |index| {
+        let ui = ui_handle.unwrap();
+        let mut state = language_state.borrow_mut();
+        let Some(locale) = locales.get(index as usize) else {
+            return;
+        };
+        state.settings.locale = Some(locale.code.clone());
+        state.locale = locale.clone();
+        state.save_settings();
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state); // Redraw every name in the new language.
+    });
+
+    let ui_handle = ui.as_weak();
+    let select_state = state.clone();
+    ui.on_select_day(move |index| {
+        let ui = ui_handle.unwrap();
+        let mut state = select_state.borrow_mut();
+        let grid = state.cursor.grid(state.settings.week_start);
+        let Some(cell) = grid.cells().get(index as usize) else {
+            return;
+        };
+        state.open_day(cell.date);
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+    });
+
+    let ui_handle = ui.as_weak();
+    let slot_state = state.clone();
+    ui.on_slot_size_changed(move |index| {
+        let ui = ui_handle.unwrap();
+        let mut state = slot_state.borrow_mut();
+        let Some(slot_size) = SlotSize::ALL.get(index as usize) else {
+            return;
+        };
+        state.settings.slot_size = *slot_size;
+        state.save_settings();
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+    });
+
+    // Move the current-time line of the day view along every minute, and
+    // the highlighting of today once the date changes.
+    let ui_handle = ui.as_weak();
+    let clock_state = state.clone();
+    let clock = slint::Timer::default();
+    clock.start(
+        slint::TimerMode::Repeated,
+        std::time::Duration::from_secs(60),
+        move || {
+            let mut state = clock_state.borrow_mut();
+            if state.roll_over() || state.view == View::Day {
+                let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+                run_calendar(&ui_handle.unwrap(), boxes, &state);
+            }
+        },
+    );
+
+    // Fire reminders alongside the event loop, catching up on missed ones first.
+    state.borrow_mut().poll_reminders(now);
+    load_reminders(&ui, &state.borrow());
+    let ui_handle = ui.as_weak();
+    let reminder_state = state.clone();
+    let reminder_timer = slint::Timer::default();
+    reminder_timer.start(
+        slint::TimerMode::Repeated,
+        std::time::Duration::from_secs(REMINDER_POLL_SECONDS),
+        move || {
+            let mut state = reminder_state.borrow_mut();
+            let now = state.now();
+            if state.poll_reminders(now) {
+                load_reminders(&ui_handle.unwrap(), &state);
+            }
+        },
+    );
+
+    let ui_handle = ui.as_weak();
+    let snooze_state = state.clone();
+    ui.on_snooze_reminder(move |index| {
+        let mut state = snooze_state.borrow_mut();
+        let Some(alert) = state.reminders.active().get(index as usize).copied() else {
+            return;
+        };
+        let until = state.now() + Duration::minutes(SNOOZE_MINUTES);
+        state.reminders.snooze(&alert, until);
+        state.notifications.withdraw(&alert);
+        load_reminders(&ui_handle.unwrap(), &state);
+    });
+
+    let ui_handle = ui.as_weak();
+    let dismiss_state = state.clone();
+    ui.on_dismiss_reminder(move |index| {
+        let mut state = dismiss_state.borrow_mut();
+        let Some(alert) = state.reminders.active().get(index as usize).copied() else {
+            return;
+        };
+        state.reminders.dismiss(&alert);
+        state.notifications.withdraw(&alert);
+        load_reminders(&ui_handle.unwrap(), &state);
+    });
+
+    let ui_handle = ui.as_weak();
+    let notification_state = state.clone();
+    ui.on_notification_action(move |id, key| {
+        let mut state = notification_state.borrow_mut();
+        let Some(action) = Action::from_key(&key) else {
+            return;
+        };
+        let Some(alert) = state.notifications.take(id as u32) else {
+            return;
+        };
+        let ui = ui_handle.unwrap();
+        match action {
+            Action::Snooze => {
+                let until = state.now() + Duration::minutes(SNOOZE_MINUTES);
+                state.reminders.snooze(&alert, until);
+            }
+            Action::Open => {
+                state.reminders.dismiss(&alert);
+                state.open_day(alert.start.date());
+                let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+                run_calendar(&ui, boxes, &state);
+                let _ = ui.show(); // Bring the window up if it was hidden.
+            }
+            Action::Dismiss => {
+                state.reminders.dismiss(&alert);
+            }
+        }
+        load_reminders(&ui, &state);
+    });
+
+    let ui_handle = ui.as_weak();
+    let new_state = state.clone();
+    ui.on_new_event(move || {
+        let mut state = new_state.borrow_mut();
+        let date = state.selected.unwrap_or_else(|| state.today());
+        state.edited = None;
+        let ui = ui_handle.unwrap();
+        ui.set_event_recurring(false);
+        open_event_dialog(&ui, state.locale.new_event(), &form::new_draft(date));
+    });
+
+    let ui_handle = ui.as_weak();
+    let edit_state = state.clone();
+    ui.on_edit_event(move |id, start| {
+        let mut state = edit_state.borrow_mut();
+        let id = EventId(id as u64);
+        let Ok(start) = NaiveDateTime::parse_from_str(&start, OCCURRENCE_FORMAT) else {
+            return;
+        };
+        // Recurring events open as the selected occurrence.
+        let Some(event) = state.events.occurrence(id, start) else {
+            return;
+        };
+        let draft = EventDraft::from_event(&event);
+        state.edited = Some((id, start));
+        let ui = ui_handle.unwrap();
+        ui.set_event_recurring(event.recurrence.is_some());
+        open_event_dialog(&ui, state.locale.edit_event(), &draft);
+    });
+
+    let ui_handle = ui.as_weak();
+    let save_state = state.clone();
+    ui.on_save_event(move |fields| {
+        let ui = ui_handle.unwrap();
+        let mut state = save_state.borrow_mut();
+        let original = state
+            .edited
+            .and_then(|(id, start)| state.events.occurrence(id, start));
+        let color = original
+            .as_ref()
+            .map(|event| event.color.to_hex())
+            .unwrap_or_default();
+        let draft = form::event_draft(&fields, &color);
+        let event = match draft.validate(original.as_ref()) {
+            Ok(event) => event,
+            Err(errors) => {
+                ui.set_event_errors(form::form_errors(&errors));
+                return;
+            }
+        };
+        match state.edited {
+            Some((id, start)) => {
+                let scope = form::edit_scope(&fields);
+                state.events.update_occurrence(id, start, scope, event);
+            }
+            None => {
+                state.events.insert(event);
+            }
+        }
+        state.save();
+        ui.set_editing(false);
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+        load_reminders(&ui, &state);
+    });
+
+    let ui_handle = ui.as_weak();
+    let preview_state = state.clone();
+    ui.on_quick_add_changed(move |text| {
+        let ui = ui_handle.unwrap();
+        let state = preview_state.borrow();
+        let (preview, valid) = match text.trim() {
+            "" => (String::new(), true),
+            text => match quick_add::parse(text, state.today()).to_event() {
+                Ok(event) => (quick_add_preview(&event, &state.locale), true),
+                Err(err) => (err.to_string(), false),
+            },
+        };
+        ui.set_quick_add_preview(SharedString::from(preview));
+        ui.set_quick_add_valid(valid);
+    });
+
+    let ui_handle = ui.as_weak();
+    let quick_add_state = state.clone();
+    ui.on_quick_add(move |text| {
+        let ui = ui_handle.unwrap();
+        let mut state = quick_add_state.borrow_mut();
+        let Ok(event) = quick_add::parse(&text, state.today()).to_event() else {
+            return false;
+        };
+        let status = format!("Added {}", quick_add_preview(&event, &state.locale));
+        let date = event.time.start_date();
+        state.events.insert(event);
+        state.save();
+        state.show_date(date);
+        ui.set_status(SharedString::from(status));
+        let boxes = Rc::new(slint::VecModel::<NewBox>::from(Vec::new()));
+        run_calendar(&ui, boxes, &state);
+        load_reminders(&ui, &state);
+        true
+    });
+
+    let result = ui.run(); // Start the UI event loop.
+
+    // Remember until when reminders were shown and which were not dismissed.
+    state.borrow().save_reminders();
+    result
 }
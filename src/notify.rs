@@ -0,0 +1,403 @@
+use calendar_core::Alert;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type;
+use zbus::zvariant::Value;
+use zbus::MatchRule;
+
+/**
+ * The well-known name, object path and interface of the notification daemon.
+ */
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/**
+ * The name notifications are sent under.
+ */
+const APP_NAME: &str = "idk2";
+
+/**
+ * The reason of a `NotificationClosed` signal for notifications closed by
+ * the user.
+ */
+const CLOSED_BY_USER: u32 = 2;
+
+/**
+ * What the user did with the notification of a reminder.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Snooze,
+    /** Show the event in the window; also sent when the notification is clicked. */
+    Open,
+    /** The notification was closed. */
+    Dismiss,
+}
+
+impl Action {
+    /**
+     * The actions offered as buttons, in the order they are shown.
+     */
+    const BUTTONS: [Action; 2] = [Action::Snooze, Action::Open];
+
+    /**
+     * Returns the action key used in the notification protocol.
+     */
+    pub fn key(self) -> &'static str {
+        match self {
+            Action::Snooze => "snooze",
+            Action::Open => "open",
+            Action::Dismiss => "dismiss",
+        }
+    }
+
+    /**
+     * Returns the action of a key, mapping the click on the notification
+     * itself (`"default"`) to `Open`.
+     */
+    pub fn from_key(key: &str) -> Option<Action> {
+        match key {
+            "snooze" => Some(Action::Snooze),
+            "open" | "default" => Some(Action::Open),
+            "dismiss" => Some(Action::Dismiss),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::Snooze => "Snooze",
+            Action::Open => "Open",
+            Action::Dismiss => "Dismiss",
+        }
+    }
+}
+
+/**
+ * The text of a notification.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+}
+
+/**
+ * Errors reported by a notification backend.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyError {
+    /** There is no session bus or no notification daemon on it. */
+    Unavailable(String),
+    /** The daemon rejected a request. */
+    Failed(String),
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Unavailable(reason) => {
+                write!(f, "no notification daemon available: {reason}")
+            }
+            NotifyError::Failed(reason) => write!(f, "notification failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for NotifyError {}
+
+/**
+ * A place notifications can be shown, e.g. the desktop's notification
+ * daemon. The actions the user picks are reported by the backend itself,
+ * since they arrive independently of the calls.
+ */
+pub trait Notifier {
+    /**
+     * Shows a notification with the actions of `Action::BUTTONS`.
+     *
+     * Returns:
+     * - The identifier of the notification, which reported actions refer to.
+     */
+    fn show(&mut self, notification: &Notification) -> Result<u32, NotifyError>;
+
+    /**
+     * Withdraws a notification that may still be shown.
+     */
+    fn close(&mut self, id: u32) -> Result<(), NotifyError>;
+}
+
+/**
+ * A notifier talking to the daemon of the freedesktop.org desktop
+ * notifications specification over the D-Bus session bus.
+ */
+pub struct DbusNotifier {
+    proxy: Proxy<'static>,
+}
+
+impl DbusNotifier {
+    /**
+     * Function to connect to the notification daemon of the session.
+     *
+     * Parameters:
+     * - `on_action`: Called with the notification identifier and the action
+     *   whenever the user picks an action of, or closes, a notification. It
+     *   is called from a background thread.
+     *
+     * Returns:
+     * - The notifier, or `NotifyError::Unavailable` if no daemon is running.
+     */
+    pub fn connect(
+        on_action: impl Fn(u32, Action) + Send + 'static,
+    ) -> Result<DbusNotifier, NotifyError> {
+        let unavailable = |err: zbus::Error| NotifyError::Unavailable(err.to_string());
+        let connection = Connection::session().map_err(unavailable)?;
+        let proxy = Proxy::new(&connection, DESTINATION, PATH, INTERFACE).map_err(unavailable)?;
+        // Fails unless a daemon owns the name.
+        let _: (String, String, String, String) = proxy
+            .call("GetServerInformation", &())
+            .map_err(unavailable)?;
+
+        // Both signals are read from one stream in the order they were sent,
+        // so an action is handled before the closing of its notification.
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .path(PATH)
+            .and_then(|rule| rule.interface(INTERFACE))
+            .map_err(unavailable)?
+            .build();
+        let signals =
+            MessageIterator::for_match_rule(rule, &connection, None).map_err(unavailable)?;
+        std::thread::spawn(move || {
+            for message in signals.flatten() {
+                let header = message.header();
+                match header.member().map(|member| member.as_str()) {
+                    Some("ActionInvoked") => {
+                        let Ok((id, key)) = message.body().deserialize::<(u32, String)>() else {
+                            continue;
+                        };
+                        if let Some(action) = Action::from_key(&key) {
+                            on_action(id, action);
+                        }
+                    }
+                    Some("NotificationClosed") => {
+                        if let Ok((id, CLOSED_BY_USER)) = message.body().deserialize::<(u32, u32)>()
+                        {
+                            on_action(id, Action::Dismiss);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+        Ok(DbusNotifier { proxy })
+    }
+}
+
+impl Notifier for DbusNotifier {
+    fn show(&mut self, notification: &Notification) -> Result<u32, NotifyError> {
+        // Clicking the notification itself opens the event as well.
+        let mut actions = vec!["default", Action::Open.label()];
+        for action in Action::BUTTONS {
+            actions.extend([action.key(), action.label()]);
+        }
+        let hints: HashMap<&str, Value> = HashMap::new();
+        self.proxy
+            .call(
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    "x-office-calendar",
+                    notification.summary.as_str(),
+                    notification.body.as_str(),
+                    actions,
+                    hints,
+                    // Reminders stay until the user reacts to them.
+                    0i32,
+                ),
+            )
+            .map_err(|err| NotifyError::Failed(err.to_string()))
+    }
+
+    fn close(&mut self, id: u32) -> Result<(), NotifyError> {
+        self.proxy
+            .call::<_, _, ()>("CloseNotification", &(id,))
+            .map_err(|err| NotifyError::Failed(err.to_string()))
+    }
+}
+
+/**
+ * Shows the reminders that go off as desktop notifications and remembers
+ * which alert each notification belongs to.
+ *
+ * Reminders are always listed in the window as well; without a notification
+ * daemon that list is the only notice. A backend that fails is dropped, so
+ * that later reminders go straight to the window.
+ */
+pub struct Notifications {
+    backend: Option<Box<dyn Notifier>>,
+    shown: BTreeMap<u32, Alert>,
+}
+
+impl Notifications {
+    pub fn new(backend: Option<Box<dyn Notifier>>) -> Notifications {
+        Notifications {
+            backend,
+            shown: BTreeMap::new(),
+        }
+    }
+
+    /**
+     * Function to show the notification of an alert that went off.
+     *
+     * Returns:
+     * - Whether the notification was shown by the backend.
+     */
+    pub fn show(&mut self, alert: Alert, notification: &Notification) -> bool {
+        let Some(backend) = &mut self.backend else {
+            return false;
+        };
+        match backend.show(notification) {
+            Ok(id) => {
+                self.shown.insert(id, alert);
+                true
+            }
+            Err(err) => {
+                eprintln!("{err}, showing reminders in the window only");
+                self.backend = None;
+                false
+            }
+        }
+    }
+
+    /**
+     * Returns the alert of a notification the user acted on, forgetting it.
+     */
+    pub fn take(&mut self, id: u32) -> Option<Alert> {
+        self.shown.remove(&id)
+    }
+
+    /**
+     * Function to close the notification of an alert handled in the window.
+     */
+    pub fn withdraw(&mut self, alert: &Alert) {
+        let ids: Vec<u32> = self
+            .shown
+            .iter()
+            .filter(|(_, shown)| *shown == alert)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in ids {
+            self.shown.remove(&id);
+            if let Some(backend) = &mut self.backend {
+                // The user may already have closed it.
+                let _ = backend.close(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar_core::EventId;
+    use chrono::NaiveDate;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /**
+     * Records the calls of `Notifications`, failing once `fail` is set.
+     */
+    #[derive(Default)]
+    struct Mock {
+        shown: Vec<Notification>,
+        closed: Vec<u32>,
+        fail: bool,
+    }
+
+    struct MockNotifier(Rc<RefCell<Mock>>);
+
+    impl Notifier for MockNotifier {
+        fn show(&mut self, notification: &Notification) -> Result<u32, NotifyError> {
+            let mut mock = self.0.borrow_mut();
+            if mock.fail {
+                return Err(NotifyError::Failed("daemon went away".to_string()));
+            }
+            mock.shown.push(notification.clone());
+            Ok(mock.shown.len() as u32 + 100)
+        }
+
+        fn close(&mut self, id: u32) -> Result<(), NotifyError> {
+            self.0.borrow_mut().closed.push(id);
+            Ok(())
+        }
+    }
+
+    fn mock() -> (Notifications, Rc<RefCell<Mock>>) {
+        let mock = Rc::new(RefCell::new(Mock::default()));
+        let backend = Box::new(MockNotifier(mock.clone()));
+        (Notifications::new(Some(backend)), mock)
+    }
+
+    fn alert(id: u64, hour: u32) -> Alert {
+        let start = NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap();
+        Alert {
+            id: EventId(id),
+            start,
+            due: start,
+        }
+    }
+
+    fn notification(summary: &str) -> Notification {
+        Notification {
+            summary: summary.to_string(),
+            body: "Oct 16 14:00–15:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn notifications_are_mapped_back_to_their_alerts() {
+        let (mut notifications, mock) = mock();
+        assert!(notifications.show(alert(1, 14), &notification("Dentist")));
+        assert!(notifications.show(alert(2, 15), &notification("Standup")));
+        assert_eq!(mock.borrow().shown[0].summary, "Dentist");
+
+        assert_eq!(notifications.take(102), Some(alert(2, 15)));
+        assert_eq!(notifications.take(102), None);
+        assert_eq!(notifications.take(7), None);
+
+        notifications.withdraw(&alert(1, 14));
+        notifications.withdraw(&alert(2, 15));
+        assert_eq!(mock.borrow().closed, [101]);
+        assert_eq!(notifications.take(101), None);
+    }
+
+    #[test]
+    fn failing_backends_fall_back_to_the_window() {
+        let (mut notifications, mock) = mock();
+        mock.borrow_mut().fail = true;
+        assert!(!notifications.show(alert(1, 14), &notification("Dentist")));
+
+        // The backend is not asked again once it failed.
+        mock.borrow_mut().fail = false;
+        assert!(!notifications.show(alert(2, 15), &notification("Standup")));
+        assert!(mock.borrow().shown.is_empty());
+
+        let mut without = Notifications::new(None);
+        assert!(!without.show(alert(1, 14), &notification("Dentist")));
+    }
+
+    #[test]
+    fn action_keys_follow_the_specification() {
+        for action in [Action::Snooze, Action::Open, Action::Dismiss] {
+            assert_eq!(Action::from_key(action.key()), Some(action));
+        }
+        assert_eq!(Action::from_key("default"), Some(Action::Open));
+        assert_eq!(Action::from_key("reply"), None);
+    }
+}